@@ -0,0 +1,70 @@
+use soroban_sdk::{contractimpl, symbol_short, Address, Env, Error, Vec};
+
+use crate::{
+    read_admin, ClaimableBalanceContract, ClaimableBalanceContractArgs, ClaimableBalanceContractClient, DataKey,
+    DepositError,
+};
+
+#[contractimpl]
+impl ClaimableBalanceContract {
+    /// Adds a token to the allowlist, so that only listed tokens can be deposited from then on. Only callable by
+    /// the admin.
+    pub fn allow_token(env: Env, token: Address) {
+        // Require that the admin authorizes the change
+        read_admin(&env).require_auth();
+
+        let mut allowed = read_allowlist(&env);
+        if allowed.contains(&token) {
+            panic!("token is already allowed");
+        }
+        allowed.push_back(token.clone());
+        env.storage().instance().set(&DataKey::TokenAllowlist, &allowed);
+
+        env.events().publish((symbol_short!("tok_allow"), token), ());
+    }
+
+    /// Removes a token from the allowlist; once the list is empty every token can be deposited again. Balances
+    /// already holding the token are unaffected. Only callable by the admin.
+    pub fn disallow_token(env: Env, token: Address) {
+        // Require that the admin authorizes the change
+        read_admin(&env).require_auth();
+
+        let mut allowed = read_allowlist(&env);
+        let position = allowed.first_index_of(&token).expect("token is not allowed");
+        allowed.remove(position);
+        env.storage().instance().set(&DataKey::TokenAllowlist, &allowed);
+
+        env.events().publish((symbol_short!("tok_deny"), token), ());
+    }
+
+    /// Returns the tokens accepted by deposits; an empty list accepts every token.
+    pub fn allowed_tokens(env: Env) -> Vec<Address> {
+        read_allowlist(&env)
+    }
+
+    /// Returns whether the token can currently be deposited.
+    pub fn is_token_allowed(env: Env, token: Address) -> bool {
+        is_allowed(&env, &token)
+    }
+}
+
+/// Helper function rejecting a deposit of a token left out of a non-empty allowlist.
+pub(crate) fn check_token_allowed(env: &Env, token: &Address) {
+    if !is_allowed(env, token) {
+        env.panic_with_error(Error::from(DepositError::TokenNotAllowed));
+    }
+}
+
+/// Helper function checking whether the allowlist is empty or lists the token.
+fn is_allowed(env: &Env, token: &Address) -> bool {
+    let allowed = read_allowlist(env);
+    allowed.is_empty() || allowed.contains(token)
+}
+
+/// Helper function to read the token allowlist, empty when no token was ever listed.
+fn read_allowlist(env: &Env) -> Vec<Address> {
+    env.storage()
+        .instance()
+        .get(&DataKey::TokenAllowlist)
+        .unwrap_or(Vec::new(env))
+}
@@ -0,0 +1,79 @@
+use soroban_sdk::{contractimpl, token, Address, Env, Map, Vec};
+
+use crate::{
+    create_balance, deliver_claim, receive, tvl, ClaimableBalance, ClaimableBalanceContract,
+    ClaimableBalanceContractArgs, ClaimableBalanceContractClient, TimeBound, MAX_AMOUNT,
+};
+
+#[contractimpl]
+impl ClaimableBalanceContract {
+    /// Deposits several tokens as one balance, e.g. a vesting package mixing a governance token and a stablecoin.
+    /// The first asset is the balance's main token; a claim pays out the whole basket at once.
+    pub fn deposit_basket(
+        env: Env,
+        from: Address,                 // Address sending the tokens
+        assets: Vec<(Address, i128)>,  // Token contract addresses and the amount of each
+        claimants: Vec<Address>,       // Allowed claimants
+        time_bound: TimeBound,         // Time-bound constraint
+    ) -> u64 {
+        let Some((token, amount)) = assets.first() else {
+            panic!("basket must not be empty");
+        };
+
+        // Every further asset is a distinct token with a positive amount
+        let mut seen = Map::new(&env);
+        seen.set(token.clone(), ());
+        let mut basket = Vec::new(&env);
+        for (asset, asset_amount) in assets.slice(1..).iter() {
+            if seen.contains_key(asset.clone()) {
+                panic!("duplicate asset");
+            }
+            if asset_amount <= 0 || asset_amount > MAX_AMOUNT {
+                panic!("asset amount must be positive and at most MAX_AMOUNT");
+            }
+            seen.set(asset.clone(), ());
+            basket.push_back((asset, asset_amount));
+        }
+
+        let mut claimable_balance =
+            ClaimableBalance::new(&env, from, token, amount, claimants, time_bound);
+        claimable_balance.basket = basket;
+        create_balance(&env, claimable_balance)
+    }
+}
+
+/// Helper function pulling the further assets of a basket from the depositor, keeping the amounts actually received.
+pub(crate) fn pull_basket(env: &Env, claimable_balance: &mut ClaimableBalance) {
+    let mut received_basket = Vec::new(env);
+    for (asset, amount) in claimable_balance.basket.iter() {
+        let received = receive(env, &asset, &claimable_balance.depositor, amount);
+        tvl::adjust_total_locked(env, &asset, received);
+        received_basket.push_back((asset, received));
+    }
+    claimable_balance.basket = received_basket;
+}
+
+/// Helper function paying the further assets of a claimed basket to `destination`.
+pub(crate) fn release_basket(
+    env: &Env,
+    claimable_balance: &ClaimableBalance,
+    claimant: &Address,
+    destination: &Address,
+) {
+    for (asset, amount) in claimable_balance.basket.iter() {
+        deliver_claim(env, &asset, claimant, destination, amount);
+        tvl::adjust_total_locked(env, &asset, -amount);
+    }
+}
+
+/// Helper function returning the further assets of a refunded basket to its depositor.
+pub(crate) fn refund_basket(env: &Env, claimable_balance: &ClaimableBalance, recipient: &Address) {
+    for (asset, amount) in claimable_balance.basket.iter() {
+        token::Client::new(env, &asset).transfer(
+            &env.current_contract_address(),
+            recipient,
+            &amount,
+        );
+        tvl::adjust_total_locked(env, &asset, -amount);
+    }
+}
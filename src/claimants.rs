@@ -0,0 +1,97 @@
+use soroban_sdk::{contractimpl, symbol_short, Address, Env};
+
+use crate::{
+    check_time_bound, index, is_expired, read_balance, read_config, rotation, write_balance,
+    Airdrop, ClaimableBalance, ClaimableBalanceContract, ClaimableBalanceContractArgs,
+    ClaimableBalanceContractClient, DataKey, DistributionMode, Vesting,
+};
+
+#[contractimpl]
+impl ClaimableBalanceContract {
+    /// Adds a claimant to a balance that has not unlocked yet. Only callable by the depositor, and only on balances
+    /// deposited without `immutable`.
+    pub fn add_claimant(env: Env, caller: Address, balance_id: u64, claimant: Address) {
+        let mut claimable_balance = read_balance(&env, balance_id);
+        check_editable(&env, &caller, &claimable_balance);
+
+        if index::is_claimant(&env, balance_id, &claimant) {
+            panic!("claimant is already a claimant");
+        }
+        if claimable_balance.claimants.len() >= read_config(&env).max_claimants {
+            panic!("too many claimants");
+        }
+        if matches!(claimable_balance.distribution, DistributionMode::EqualSplit)
+            && claimable_balance.amount < claimable_balance.claimants.len() as i128 + 1
+        {
+            panic!("amount is too small to split equally");
+        }
+
+        claimable_balance.claimants.push_back(claimant.clone());
+        index::add_claimant_entry(&env, balance_id, &claimant);
+        write_balance(&env, balance_id, &claimable_balance);
+
+        env.events()
+            .publish((symbol_short!("cl_add"), balance_id, caller), claimant);
+    }
+
+    /// Removes a claimant, and any condition the balance assigned to it, from a balance that has not unlocked yet.
+    /// Only callable by the depositor, and only on balances deposited without `immutable`.
+    pub fn remove_claimant(env: Env, caller: Address, balance_id: u64, claimant: Address) {
+        let mut claimable_balance = read_balance(&env, balance_id);
+        check_editable(&env, &caller, &claimable_balance);
+
+        let position = claimable_balance
+            .claimants
+            .first_index_of(&claimant)
+            .expect("claimant is not allowed to claim this balance");
+        if claimable_balance.claimants.len() == 1 {
+            panic!("balance needs at least one claimant");
+        }
+
+        claimable_balance.claimants.remove(position);
+        claimable_balance.claimant_predicates.remove(claimant.clone());
+        claimable_balance.claimant_locks.remove(claimant.clone());
+        env.storage()
+            .persistent()
+            .remove(&DataKey::PendingRotation(balance_id, claimant.clone()));
+        index::remove_claimant_entry(&env, balance_id, &claimant);
+        write_balance(&env, balance_id, &claimable_balance);
+
+        env.events()
+            .publish((symbol_short!("cl_remove"), balance_id, caller), claimant);
+    }
+}
+
+/// Helper function panicking unless the caller, authorized as the depositor, may still edit the claimants of the
+/// balance. The admin cannot, so a compromised admin key cannot redirect locked funds.
+fn check_editable(env: &Env, caller: &Address, claimable_balance: &ClaimableBalance) {
+    // Require that the depositor authorizes the edit
+    caller.require_auth();
+    if *caller != claimable_balance.depositor {
+        panic!("only the depositor can edit claimants");
+    }
+
+    if claimable_balance.immutable {
+        panic!("balance is immutable");
+    }
+    if check_time_bound(env, claimable_balance) || is_expired(env, &claimable_balance.time_bound) {
+        panic!("claimants can only be edited while the balance is locked");
+    }
+
+    // Shares, turns and approvals are tied to the claimants they were set up for
+    rotation::check_rotatable(claimable_balance);
+    if !matches!(claimable_balance.airdrop, Airdrop::None) {
+        panic!("claimants of airdrops are set by their Merkle root");
+    }
+    if let Vesting::Milestones(schedule) = &claimable_balance.vesting {
+        if schedule.arbiter.is_some() {
+            panic!("claimants of arbitrated milestones cannot be edited");
+        }
+    }
+    if !claimable_balance.allocations.is_empty()
+        || !claimable_balance.claim_windows.is_empty()
+        || matches!(claimable_balance.distribution, DistributionMode::Weighted(_))
+    {
+        panic!("claimants of allocated, weighted or windowed balances cannot be edited");
+    }
+}
@@ -0,0 +1,30 @@
+use soroban_sdk::{contractclient, contractimpl, Address, Env};
+
+use crate::{
+    pay_full_claim, read_balance_hot, ClaimableBalanceContract, ClaimableBalanceContractArgs,
+    ClaimableBalanceContractClient,
+};
+
+/// Interface that contract claimants implement to consent to claims made on their behalf.
+#[contractclient(name = "ClaimantContractClient")]
+pub trait ClaimantContract {
+    /// Returns whether the contract accepts being paid the balance now.
+    fn accept_claim(env: Env, balance_id: u64, token: Address) -> bool;
+}
+
+#[contractimpl]
+impl ClaimableBalanceContract {
+    /// Claims a balance for a contract claimant that cannot authorize calls, e.g. a DAO or vault without
+    /// `__check_auth`: the claimant consents through `accept_claim` instead. Callable by anyone; the tokens always go
+    /// to the claimant. Returns the amount paid.
+    pub fn claim_by_contract(env: Env, claimant: Address, balance_id: u64) -> i128 {
+        let claimable_balance = read_balance_hot(&env, balance_id);
+
+        // Ask the claimant contract for its consent in place of an authorization
+        if !ClaimantContractClient::new(&env, &claimant).accept_claim(&balance_id, &claimable_balance.token) {
+            panic!("claimant contract declined the claim");
+        }
+
+        pay_full_claim(&env, balance_id, claimant.clone(), claimant, None)
+    }
+}
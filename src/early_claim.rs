@@ -0,0 +1,123 @@
+use soroban_sdk::{contractimpl, contracttype, token, Address, Env};
+
+use crate::{
+    check_claim_hook, check_claimant_predicate, check_claims_not_paused, check_gate, check_time_bound,
+    close_balance, current_time, deliver_claim, events, freeze, history, index, math, read_balance_hot, timelock_core,
+    Airdrop, BalanceStatus, ClaimableBalance, ClaimableBalanceContract, ClaimableBalanceContractArgs,
+    ClaimableBalanceContractClient, DeadManSwitch, DistributionMode, Hashlock, Multisig, TimeBoundKind, Vesting,
+    WithdrawalCap, BPS_DENOMINATOR,
+};
+
+/// Struct describing the terms under which a balance can be claimed before it unlocks.
+#[derive(Clone)]
+#[contracttype]
+pub struct EarlyClaimTerms {
+    pub penalty_bps: u32,            // Share of the amount forfeited by an early claim, in basis points
+    pub earliest: u64,               // UNIX timestamp from which an early claim is possible
+    pub recipient: Option<Address>,  // Receiver of the penalty, e.g. a treasury; the depositor when `None`
+}
+
+/// Enum representing whether a balance can be claimed early (contract types cannot hold `Option` of custom types).
+#[derive(Clone, Default)]
+#[contracttype]
+pub enum EarlyClaim {
+    #[default]
+    None,                      // The balance can only be claimed once unlocked
+    Penalty(EarlyClaimTerms),  // The balance can be claimed early by forfeiting a penalty
+}
+
+#[contractimpl]
+impl ClaimableBalanceContract {
+    /// Claims a balance before its unlock time, forfeiting the penalty of its early-claim terms to the depositor or
+    /// the configured recipient. Returns the amount paid to the claimant.
+    pub fn claim_early(env: Env, claimant: Address, balance_id: u64) -> i128 {
+        // Require that claimant authorizes the claim
+        claimant.require_auth();
+        check_claims_not_paused(&env);
+
+        let claimable_balance = read_balance_hot(&env, balance_id);
+        freeze::check_not_frozen(&claimable_balance);
+        let EarlyClaim::Penalty(terms) = &claimable_balance.early_claim else {
+            panic!("balance cannot be claimed early");
+        };
+        if current_time(&env) < terms.earliest {
+            panic!("early claims are not open yet");
+        }
+        if check_time_bound(&env, &claimable_balance) {
+            panic!("balance is unlocked, use claim");
+        }
+
+        // Every claim condition other than the time still applies
+        if !index::is_claimant(&env, balance_id, &claimant) {
+            panic!("claimant is not allowed to claim this balance");
+        }
+        if !check_claimant_predicate(&env, &claimable_balance, &claimant) {
+            panic!("claimant predicate is not fulfilled");
+        }
+        check_gate(&env, balance_id, &claimable_balance, &claimant);
+
+        // Split off the penalty and pay the rest, if the compliance hook allows that amount
+        let penalty = math::checked(&env, math::bps_of(claimable_balance.amount, terms.penalty_bps));
+        let payout = claimable_balance.amount - penalty;
+        check_claim_hook(&env, &claimant, &claimable_balance.token, payout);
+        let penalty_recipient = terms.recipient.clone().unwrap_or(claimable_balance.depositor.clone());
+        history::record_claim(&env, balance_id, &claimant, payout);
+        close_balance(&env, balance_id, &claimable_balance, BalanceStatus::Claimed);
+
+        if penalty > 0 {
+            token::Client::new(&env, &claimable_balance.token).transfer(
+                &env.current_contract_address(),
+                &penalty_recipient,
+                &penalty,
+            );
+        }
+        deliver_claim(&env, &claimable_balance.token, &claimant, &claimant, payout);
+
+        events::publish_early_claim(
+            &env,
+            balance_id,
+            claimant,
+            claimable_balance.token,
+            payout,
+            penalty,
+            penalty_recipient,
+        );
+        payout
+    }
+}
+
+/// Helper function rejecting early-claim terms that are malformed or combined with features paying out in parts.
+pub(crate) fn validate_early_claim(claimable_balance: &ClaimableBalance) {
+    let EarlyClaim::Penalty(terms) = &claimable_balance.early_claim else {
+        return;
+    };
+    if terms.penalty_bps as i128 > BPS_DENOMINATOR {
+        panic!("penalty cannot exceed 10000 bps");
+    }
+    if !matches!(claimable_balance.time_bound.kind, TimeBoundKind::After)
+        || terms.earliest >= claimable_balance.time_bound.timestamp
+    {
+        panic!("early claims require an After time bound later than the earliest early claim");
+    }
+    if !matches!(claimable_balance.vesting, Vesting::None)
+        || !matches!(claimable_balance.distribution, DistributionMode::FirstComeFirstServed)
+        || !claimable_balance.allocations.is_empty()
+        || !matches!(claimable_balance.airdrop, Airdrop::None)
+        || !matches!(claimable_balance.hashlock, Hashlock::None)
+        || !matches!(claimable_balance.multisig, Multisig::None)
+        || !matches!(claimable_balance.withdrawal_cap, WithdrawalCap::None)
+        || claimable_balance.escrow
+        || !claimable_balance.basket.is_empty()
+    {
+        panic!("early claims are only supported on plain winner-takes-all balances");
+    }
+
+    // An early claim only waives the wait: it must neither preempt a depositor still sending heartbeats nor skip
+    // the oracle and holding conditions of the balance
+    if !matches!(claimable_balance.dead_man_switch, DeadManSwitch::None) {
+        panic!("early claims cannot bypass a dead man's switch");
+    }
+    if !timelock_core::is_time_only(&claimable_balance.predicate) {
+        panic!("early claims require a predicate that only depends on time");
+    }
+}
@@ -0,0 +1,154 @@
+use soroban_sdk::{contractimpl, symbol_short, token, Address, Env};
+
+use crate::{
+    check_time_bound, close_balance, deliver_claim, events, history, math, pay_full_claim, read_balance,
+    write_balance, BalanceStatus, ClaimableBalance, ClaimableBalanceContract, ClaimableBalanceContractArgs,
+    ClaimableBalanceContractClient, DataKey,
+};
+
+#[contractimpl]
+impl ClaimableBalanceContract {
+    /// Confirms an escrow as its buyer (the depositor) or seller (the claimant). The second confirmation
+    /// releases the funds to the seller. Returns the amount released, or 0 while waiting for the other party.
+    pub fn confirm(env: Env, party: Address, balance_id: u64) -> i128 {
+        // Require that party authorizes the confirmation
+        party.require_auth();
+
+        let claimable_balance = read_balance(&env, balance_id);
+        if !claimable_balance.escrow {
+            panic!("balance is not an escrow");
+        }
+        check_not_disputed(&claimable_balance);
+        let seller = claimable_balance.claimants.get_unchecked(0);
+        let counterparty = if party == claimable_balance.depositor {
+            seller.clone()
+        } else if party == seller {
+            claimable_balance.depositor.clone()
+        } else {
+            panic!("only the buyer or the seller can confirm the escrow");
+        };
+
+        let key = DataKey::Confirmation(balance_id, party.clone());
+        if env.storage().persistent().has(&key) {
+            panic!("party has already confirmed");
+        }
+        env.storage().persistent().set(&key, &());
+
+        // Publish the confirmation
+        env.events()
+            .publish((symbol_short!("confirm"), balance_id, party), ());
+
+        // Release to the seller once both parties agree
+        if !env
+            .storage()
+            .persistent()
+            .has(&DataKey::Confirmation(balance_id, counterparty))
+        {
+            return 0;
+        }
+        pay_full_claim(&env, balance_id, seller.clone(), seller, None)
+    }
+
+    /// Disputes an escrow that has an arbiter, as its buyer or seller. The escrow is frozen until the arbiter resolves it.
+    pub fn raise_dispute(env: Env, party: Address, balance_id: u64) {
+        // Require that party authorizes the dispute
+        party.require_auth();
+
+        let mut claimable_balance = read_balance(&env, balance_id);
+        if claimable_balance.arbiter.is_none() {
+            panic!("escrow has no arbiter");
+        }
+        if party != claimable_balance.depositor && party != claimable_balance.claimants.get_unchecked(0) {
+            panic!("only the buyer or the seller can dispute the escrow");
+        }
+        if claimable_balance.disputed {
+            panic!("escrow is already disputed");
+        }
+
+        // Disputes must be raised before the timeout hands the funds back to the buyer
+        if !check_time_bound(&env, &claimable_balance) {
+            panic!("dispute window has closed");
+        }
+
+        claimable_balance.disputed = true;
+        write_balance(&env, balance_id, &claimable_balance);
+
+        // Publish the dispute for the arbiter
+        env.events()
+            .publish((symbol_short!("dispute"), balance_id, party), ());
+    }
+
+    /// Settles a disputed escrow by splitting it between buyer and seller; only the arbiter can resolve it.
+    pub fn resolve(env: Env, balance_id: u64, split_to_buyer: i128, split_to_seller: i128) {
+        let claimable_balance = read_balance(&env, balance_id);
+        let arbiter = claimable_balance.arbiter.clone().expect("escrow has no arbiter");
+
+        // Require that arbiter authorizes the resolution
+        arbiter.require_auth();
+
+        if !claimable_balance.disputed {
+            panic!("escrow is not disputed");
+        }
+        if split_to_buyer < 0
+            || split_to_seller < 0
+            || math::checked(&env, math::add(split_to_buyer, split_to_seller)) != claimable_balance.amount
+        {
+            panic!("splits must be non-negative and add up to the amount");
+        }
+
+        // Close the escrow, then pay both sides
+        let seller = claimable_balance.claimants.get_unchecked(0);
+        if split_to_seller > 0 {
+            history::record_claim(&env, balance_id, &seller, split_to_seller);
+        }
+        close_balance(&env, balance_id, &claimable_balance, BalanceStatus::Claimed);
+
+        if split_to_buyer > 0 {
+            token::Client::new(&env, &claimable_balance.token).transfer(
+                &env.current_contract_address(),
+                &claimable_balance.refund_to,
+                &split_to_buyer,
+            );
+        }
+        if split_to_seller > 0 {
+            deliver_claim(&env, &claimable_balance.token, &seller, &seller, split_to_seller);
+        }
+
+        // Publish the arbiter's decision
+        events::publish_resolve(&env, balance_id, arbiter, claimable_balance.token, split_to_buyer, split_to_seller);
+    }
+}
+
+/// Helper function panicking while an escrow dispute awaits the arbiter.
+pub(crate) fn check_not_disputed(claimable_balance: &ClaimableBalance) {
+    if claimable_balance.disputed {
+        panic!("escrow is under dispute");
+    }
+}
+
+/// Helper function panicking unless the buyer of an escrow confirmed the release to the seller.
+pub(crate) fn check_buyer_confirmed(env: &Env, balance_id: u64, claimable_balance: &ClaimableBalance) {
+    if !is_buyer_confirmed(env, balance_id, claimable_balance) {
+        panic!("escrow has not been confirmed by the buyer");
+    }
+}
+
+/// Helper function telling whether the buyer confirmed the release of an escrow; true for other balances.
+pub(crate) fn is_buyer_confirmed(env: &Env, balance_id: u64, claimable_balance: &ClaimableBalance) -> bool {
+    !claimable_balance.escrow
+        || env
+            .storage()
+            .persistent()
+            .has(&DataKey::Confirmation(balance_id, claimable_balance.depositor.clone()))
+}
+
+/// Helper function removing the confirmations of a closed escrow.
+pub(crate) fn clear_confirmations(env: &Env, balance_id: u64, claimable_balance: &ClaimableBalance) {
+    if !claimable_balance.escrow {
+        return;
+    }
+
+    let storage = env.storage().persistent();
+    storage.remove(&DataKey::Confirmation(balance_id, claimable_balance.depositor.clone()));
+    storage.remove(&DataKey::Confirmation(balance_id, claimable_balance.claimants.get_unchecked(0)));
+}
@@ -0,0 +1,320 @@
+use soroban_sdk::{contracttype, symbol_short, Address, Env, Vec};
+
+use crate::{current_time, Call, ClaimableBalance};
+
+/// Version of the event schema, published as the second topic of every structured event. It only changes when the
+/// layout of an event struct does.
+pub const EVENT_VERSION: u32 = 1;
+
+/// Data of a `deposit` event, topics `("deposit", version, balance_id, depositor, token)`.
+#[derive(Clone)]
+#[contracttype]
+pub struct DepositEvent {
+    pub amount: i128,      // Amount of tokens locked
+    pub unlock_time: u64,  // Timestamp of the balance's time bound
+    pub timestamp: u64,    // Ledger timestamp of the deposit
+}
+
+/// Data of a `claim` event, topics `("claim", version, balance_id, claimant, token)`.
+#[derive(Clone)]
+#[contracttype]
+pub struct ClaimEvent {
+    pub amount: i128,          // Amount of tokens claimed, before the protocol fee
+    pub timestamp: u64,        // Ledger timestamp of the claim
+    pub destination: Address,  // Address the tokens were sent to
+}
+
+/// Data of a `refund` event for a cancelled or expired balance, topics `("refund", version, balance_id, refund_to,
+/// token)`.
+#[derive(Clone)]
+#[contracttype]
+pub struct CancelEvent {
+    pub amount: i128,    // Unclaimed remainder returned
+    pub timestamp: u64,  // Ledger timestamp of the refund
+}
+
+/// Data of a `schedule` event for a single or batched operation, topics `("schedule", version, op_id)`.
+#[derive(Clone)]
+#[contracttype]
+pub struct ScheduleEvent {
+    pub calls: Vec<Call>,          // Calls performed by the operation, in order
+    pub eta: u64,                  // Earliest execution time
+    pub predecessor: Option<u64>,  // Operation that has to be executed first, if any
+}
+
+/// Data of a `revoke` event, topics `("revoke", version, balance_id, claimant, token)`.
+#[derive(Clone)]
+#[contracttype]
+pub struct RevokeEvent {
+    pub paid: i128,      // Vested part paid to the claimant
+    pub unvested: i128,  // Unvested part returned
+    pub timestamp: u64,  // Ledger timestamp of the revocation
+}
+
+/// Data of an `early` claim event, topics `("early", version, balance_id, claimant, token)`.
+#[derive(Clone)]
+#[contracttype]
+pub struct EarlyClaimEvent {
+    pub payout: i128,                // Amount paid to the claimant, before the protocol fee
+    pub penalty: i128,               // Amount withheld for claiming before the unlock time
+    pub penalty_recipient: Address,  // Address the penalty was sent to
+    pub timestamp: u64,              // Ledger timestamp of the claim
+}
+
+/// Data of a `swap` claim event, topics `("swap", version, balance_id, claimant, token)`.
+#[derive(Clone)]
+#[contracttype]
+pub struct SwapEvent {
+    pub amount_in: i128,        // Amount of the balance's token swapped
+    pub payout_token: Address,  // Token the claimant received
+    pub received: i128,         // Amount of `payout_token` received
+    pub timestamp: u64,         // Ledger timestamp of the claim
+}
+
+/// Data of a `sweep` event for an abandoned balance, topics `("sweep", version, balance_id, treasury, token)`.
+#[derive(Clone)]
+#[contracttype]
+pub struct SweepEvent {
+    pub amount: i128,    // Unclaimed remainder sent to the treasury
+    pub timestamp: u64,  // Ledger timestamp of the sweep
+}
+
+/// Data of a `resolved` escrow event, topics `("resolved", version, balance_id, arbiter, token)`.
+#[derive(Clone)]
+#[contracttype]
+pub struct ResolveEvent {
+    pub to_buyer: i128,   // Amount returned to the buyer
+    pub to_seller: i128,  // Amount paid to the seller
+    pub timestamp: u64,   // Ledger timestamp of the decision
+}
+
+/// Data of a `top_up` event, topics `("top_up", version, balance_id, depositor, token)`.
+#[derive(Clone)]
+#[contracttype]
+pub struct TopUpEvent {
+    pub amount: i128,    // Amount of tokens added
+    pub total: i128,     // Amount locked in the balance afterwards
+    pub timestamp: u64,  // Ledger timestamp of the top-up
+}
+
+/// Data of a `stream_wd` event, topics `("stream_wd", version, stream_id, recipient, token)`.
+#[derive(Clone)]
+#[contracttype]
+pub struct StreamWithdrawEvent {
+    pub amount: i128,    // Amount of tokens withdrawn
+    pub timestamp: u64,  // Ledger timestamp of the withdrawal
+}
+
+/// Data of a `stream_cx` event, topics `("stream_cx", version, stream_id, sender, token)`.
+#[derive(Clone)]
+#[contracttype]
+pub struct StreamCancelEvent {
+    pub to_recipient: i128,  // Streamed amount paid to the recipient
+    pub to_sender: i128,     // Unstreamed amount returned to the sender
+    pub timestamp: u64,      // Ledger timestamp of the cancellation
+}
+
+/// Helper function publishing the deposit of a balance.
+pub(crate) fn publish_deposit(env: &Env, balance_id: u64, claimable_balance: &ClaimableBalance) {
+    env.events().publish(
+        (
+            symbol_short!("deposit"),
+            EVENT_VERSION,
+            balance_id,
+            claimable_balance.depositor.clone(),
+            claimable_balance.token.clone(),
+        ),
+        DepositEvent {
+            amount: claimable_balance.amount,
+            unlock_time: claimable_balance.time_bound.timestamp,
+            timestamp: current_time(env),
+        },
+    );
+}
+
+/// Helper function publishing a claim on a balance.
+pub(crate) fn publish_claim(
+    env: &Env,
+    balance_id: u64,
+    claimant: Address,
+    token: Address,
+    amount: i128,
+    destination: Address,
+) {
+    env.events().publish(
+        (symbol_short!("claim"), EVENT_VERSION, balance_id, claimant, token),
+        ClaimEvent {
+            amount,
+            timestamp: current_time(env),
+            destination,
+        },
+    );
+}
+
+/// Helper function publishing the refund of what was left of a balance.
+pub(crate) fn publish_cancel(env: &Env, balance_id: u64, claimable_balance: &ClaimableBalance, amount: i128) {
+    env.events().publish(
+        (
+            symbol_short!("refund"),
+            EVENT_VERSION,
+            balance_id,
+            claimable_balance.refund_to.clone(),
+            claimable_balance.token.clone(),
+        ),
+        CancelEvent {
+            amount,
+            timestamp: current_time(env),
+        },
+    );
+}
+
+/// Helper function publishing the scheduling of an operation.
+pub(crate) fn publish_schedule(env: &Env, op_id: u64, calls: Vec<Call>, eta: u64, predecessor: Option<u64>) {
+    env.events().publish(
+        (symbol_short!("schedule"), EVENT_VERSION, op_id),
+        ScheduleEvent {
+            calls,
+            eta,
+            predecessor,
+        },
+    );
+}
+
+/// Helper function publishing the revocation of a balance.
+pub(crate) fn publish_revoke(
+    env: &Env,
+    balance_id: u64,
+    claimant: Address,
+    token: Address,
+    paid: i128,
+    unvested: i128,
+) {
+    env.events().publish(
+        (symbol_short!("revoke"), EVENT_VERSION, balance_id, claimant, token),
+        RevokeEvent {
+            paid,
+            unvested,
+            timestamp: current_time(env),
+        },
+    );
+}
+
+/// Helper function publishing a claim made before the unlock time.
+pub(crate) fn publish_early_claim(
+    env: &Env,
+    balance_id: u64,
+    claimant: Address,
+    token: Address,
+    payout: i128,
+    penalty: i128,
+    penalty_recipient: Address,
+) {
+    env.events().publish(
+        (symbol_short!("early"), EVENT_VERSION, balance_id, claimant, token),
+        EarlyClaimEvent {
+            payout,
+            penalty,
+            penalty_recipient,
+            timestamp: current_time(env),
+        },
+    );
+}
+
+/// Helper function publishing a claim paid out in another token.
+pub(crate) fn publish_swap(
+    env: &Env,
+    balance_id: u64,
+    claimant: Address,
+    token: Address,
+    amount_in: i128,
+    payout_token: Address,
+    received: i128,
+) {
+    env.events().publish(
+        (symbol_short!("swap"), EVENT_VERSION, balance_id, claimant, token),
+        SwapEvent {
+            amount_in,
+            payout_token,
+            received,
+            timestamp: current_time(env),
+        },
+    );
+}
+
+/// Helper function publishing the sweep of an abandoned balance to the treasury.
+pub(crate) fn publish_sweep(env: &Env, balance_id: u64, treasury: Address, token: Address, amount: i128) {
+    env.events().publish(
+        (symbol_short!("sweep"), EVENT_VERSION, balance_id, treasury, token),
+        SweepEvent {
+            amount,
+            timestamp: current_time(env),
+        },
+    );
+}
+
+/// Helper function publishing the arbiter's split of a disputed escrow.
+pub(crate) fn publish_resolve(
+    env: &Env,
+    balance_id: u64,
+    arbiter: Address,
+    token: Address,
+    to_buyer: i128,
+    to_seller: i128,
+) {
+    env.events().publish(
+        (symbol_short!("resolved"), EVENT_VERSION, balance_id, arbiter, token),
+        ResolveEvent {
+            to_buyer,
+            to_seller,
+            timestamp: current_time(env),
+        },
+    );
+}
+
+/// Helper function publishing a top-up of a balance.
+pub(crate) fn publish_top_up(env: &Env, balance_id: u64, claimable_balance: &ClaimableBalance, amount: i128) {
+    env.events().publish(
+        (
+            symbol_short!("top_up"),
+            EVENT_VERSION,
+            balance_id,
+            claimable_balance.depositor.clone(),
+            claimable_balance.token.clone(),
+        ),
+        TopUpEvent {
+            amount,
+            total: claimable_balance.amount,
+            timestamp: current_time(env),
+        },
+    );
+}
+
+/// Helper function publishing a withdrawal from a stream.
+pub(crate) fn publish_stream_withdraw(env: &Env, stream_id: u64, recipient: Address, token: Address, amount: i128) {
+    env.events().publish(
+        (symbol_short!("stream_wd"), EVENT_VERSION, stream_id, recipient, token),
+        StreamWithdrawEvent {
+            amount,
+            timestamp: current_time(env),
+        },
+    );
+}
+
+/// Helper function publishing the cancellation of a stream.
+pub(crate) fn publish_stream_cancel(
+    env: &Env,
+    stream_id: u64,
+    sender: Address,
+    token: Address,
+    to_recipient: i128,
+    to_sender: i128,
+) {
+    env.events().publish(
+        (symbol_short!("stream_cx"), EVENT_VERSION, stream_id, sender, token),
+        StreamCancelEvent {
+            to_recipient,
+            to_sender,
+            timestamp: current_time(env),
+        },
+    );
+}
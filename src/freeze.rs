@@ -0,0 +1,143 @@
+use soroban_sdk::{contractimpl, contracttype, symbol_short, Address, Env};
+
+use crate::rbac::{require_role, Role};
+use crate::{
+    read_admin, read_balance, read_config, refund_balance, write_balance, ClaimableBalance,
+    ClaimableBalanceContract, ClaimableBalanceContractArgs, ClaimableBalanceContractClient, DataKey,
+};
+
+/// Enum representing an admin action on a single balance, e.g. for sanctioned addresses or disputed grants.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum FreezeAction {
+    Freeze,    // Block every claim of the balance
+    Unfreeze,  // Let the claimants claim the balance again
+    Redirect,  // Return a frozen balance to its refund address
+}
+
+/// Struct representing an announced freeze action that can be applied once its delay has passed.
+#[derive(Clone)]
+#[contracttype]
+pub struct PendingFreezeAction {
+    pub action: FreezeAction,  // Announced action
+    pub eta: u64,              // UNIX timestamp from which the action can be applied
+}
+
+#[contractimpl]
+impl ClaimableBalanceContract {
+    /// Announces a freeze action on a balance, applicable after the configured freeze delay.
+    /// Only callable by the admin; replaces any previous announcement for the balance.
+    pub fn announce_freeze_action(env: Env, balance_id: u64, action: FreezeAction) -> u64 {
+        // Require that the admin authorizes the announcement
+        read_admin(&env).require_auth();
+        read_balance(&env, balance_id);
+
+        let eta = env
+            .ledger()
+            .timestamp()
+            .saturating_add(read_config(&env).freeze_delay);
+        let pending = PendingFreezeAction {
+            action: action.clone(),
+            eta,
+        };
+        env.storage()
+            .persistent()
+            .set(&DataKey::PendingFreezeAction(balance_id), &pending);
+
+        env.events()
+            .publish((symbol_short!("frz_annc"), balance_id, action), eta);
+
+        eta
+    }
+
+    /// Applies the announced freeze action once its delay has passed. Only callable by the admin; returns the amount
+    /// returned to the depositor by a redirect, and zero otherwise.
+    pub fn apply_freeze_action(env: Env, balance_id: u64, action: FreezeAction) -> i128 {
+        // Require that the admin authorizes the action
+        read_admin(&env).require_auth();
+
+        // Only the announced action can be applied, and only after the delay
+        let pending_key = DataKey::PendingFreezeAction(balance_id);
+        let pending: PendingFreezeAction = env
+            .storage()
+            .persistent()
+            .get(&pending_key)
+            .expect("no freeze action announced");
+        if pending.action != action {
+            panic!("action does not match the announced freeze action");
+        }
+        if env.ledger().timestamp() < pending.eta {
+            panic!("freeze delay has not elapsed");
+        }
+        env.storage().persistent().remove(&pending_key);
+
+        let mut claimable_balance = read_balance(&env, balance_id);
+        let returned = match action {
+            FreezeAction::Freeze | FreezeAction::Unfreeze => {
+                claimable_balance.frozen = action == FreezeAction::Freeze;
+                write_balance(&env, balance_id, &claimable_balance);
+                0
+            }
+            FreezeAction::Redirect => {
+                if !claimable_balance.frozen {
+                    panic!("only frozen balances can be redirected");
+                }
+                claimable_balance.frozen = false;
+                refund_balance(&env, balance_id, &claimable_balance)
+            }
+        };
+
+        env.events()
+            .publish((symbol_short!("frz_apply"), balance_id, action), returned);
+
+        returned
+    }
+
+    /// Cancels the announced freeze action of a balance. Only callable by the admin.
+    pub fn cancel_freeze_action(env: Env, balance_id: u64) {
+        // Require that the admin authorizes the cancellation
+        read_admin(&env).require_auth();
+
+        remove_freeze_action(&env, balance_id);
+    }
+
+    /// Vetoes the announced freeze action of a balance, as an emergency brake on the admin. Only callable by
+    /// guardians.
+    pub fn veto_freeze_action(env: Env, guardian: Address, balance_id: u64) {
+        // Require that a guardian authorizes the veto
+        require_role(&env, &guardian, Role::Guardian);
+
+        remove_freeze_action(&env, balance_id);
+    }
+
+    /// Returns the announced freeze action of a balance, if any.
+    pub fn pending_freeze_action(env: Env, balance_id: u64) -> Option<PendingFreezeAction> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::PendingFreezeAction(balance_id))
+    }
+
+    /// Returns whether the admin froze the balance.
+    pub fn is_frozen(env: Env, balance_id: u64) -> bool {
+        read_balance(&env, balance_id).frozen
+    }
+}
+
+/// Helper function dropping the announced freeze action of a balance; panics if there is none.
+fn remove_freeze_action(env: &Env, balance_id: u64) {
+    let pending_key = DataKey::PendingFreezeAction(balance_id);
+    if !env.storage().persistent().has(&pending_key) {
+        panic!("no freeze action announced");
+    }
+    env.storage().persistent().remove(&pending_key);
+
+    env.events()
+        .publish((symbol_short!("frz_cncl"), balance_id), ());
+}
+
+/// Helper function panicking if the admin froze the balance.
+pub(crate) fn check_not_frozen(claimable_balance: &ClaimableBalance) {
+    if claimable_balance.frozen {
+        panic!("balance is frozen");
+    }
+}
@@ -0,0 +1,63 @@
+use soroban_sdk::{contractimpl, contracttype, Address, Env, Vec};
+
+use crate::{
+    ClaimableBalanceContract, ClaimableBalanceContractArgs, ClaimableBalanceContractClient, DataKey,
+};
+
+/// Struct recording one payout of a balance to a claimant.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct ClaimRecord {
+    pub claimant: Address,  // Claimant that was paid
+    pub amount: i128,       // Amount paid out
+    pub timestamp: u64,     // Ledger timestamp of the payout
+}
+
+#[contractimpl]
+impl ClaimableBalanceContract {
+    /// Returns up to `limit` payouts made from a balance, oldest first, starting at position `cursor`; the history
+    /// outlives the balance itself.
+    pub fn claim_history(env: Env, balance_id: u64, cursor: u32, limit: u32) -> Vec<ClaimRecord> {
+        let end = read_claim_count(&env, balance_id).min(cursor.saturating_add(limit));
+        let mut records = Vec::new(&env);
+        for index in cursor..end {
+            let record: ClaimRecord = env
+                .storage()
+                .persistent()
+                .get(&DataKey::ClaimRecord(balance_id, index))
+                .unwrap();
+            records.push_back(record);
+        }
+        records
+    }
+
+    /// Returns the number of payouts made from a balance.
+    pub fn claim_count(env: Env, balance_id: u64) -> u32 {
+        read_claim_count(&env, balance_id)
+    }
+}
+
+/// Helper function to read the number of payouts recorded for a balance.
+fn read_claim_count(env: &Env, balance_id: u64) -> u32 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::ClaimCount(balance_id))
+        .unwrap_or(0)
+}
+
+/// Helper function appending a payout to the claim history of a balance, one entry per payout so large airdrops
+/// stay within the ledger entry size limit.
+pub(crate) fn record_claim(env: &Env, balance_id: u64, claimant: &Address, amount: i128) {
+    let index = read_claim_count(env, balance_id);
+    env.storage().persistent().set(
+        &DataKey::ClaimRecord(balance_id, index),
+        &ClaimRecord {
+            claimant: claimant.clone(),
+            amount,
+            timestamp: env.ledger().timestamp(),
+        },
+    );
+    env.storage()
+        .persistent()
+        .set(&DataKey::ClaimCount(balance_id), &(index + 1));
+}
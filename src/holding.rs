@@ -0,0 +1,47 @@
+use soroban_sdk::{contracttype, token, Address, Env};
+
+use crate::{Predicate, MAX_AMOUNT};
+
+/// Struct describing a claim condition on what the claimant holds, e.g. a membership NFT or a governance token.
+#[derive(Clone)]
+#[contracttype]
+pub struct HoldingCondition {
+    pub token: Address,     // SEP-41 token the claimant must hold
+    pub min_balance: i128,  // Smallest balance of the token that satisfies the condition
+}
+
+/// Helper function asking the token whether the claimant holds at least the required balance.
+pub(crate) fn holding_condition_holds(env: &Env, condition: &HoldingCondition, claimant: &Address) -> bool {
+    token::Client::new(env, &condition.token).balance(claimant) >= condition.min_balance
+}
+
+/// Helper function rejecting holding conditions that require nothing, or that are negated: a claimant-agnostic
+/// evaluation assumes holdings are met, which only gives the time view of a predicate when none is under `Not`.
+pub(crate) fn validate_holdings(predicate: &Predicate) {
+    validate_holdings_under(predicate, false);
+}
+
+/// Helper function walking a predicate tree for `validate_holdings`, tracking whether a `Not` encloses it.
+fn validate_holdings_under(predicate: &Predicate, negated: bool) {
+    match predicate {
+        Predicate::Holds(condition) => {
+            if condition.min_balance <= 0 || condition.min_balance > MAX_AMOUNT {
+                panic!("holding condition needs a positive minimum balance");
+            }
+            if negated {
+                panic!("holding conditions cannot be negated");
+            }
+        }
+        Predicate::And(predicates) | Predicate::Or(predicates) => {
+            for inner in predicates.iter() {
+                validate_holdings_under(&inner, negated);
+            }
+        }
+        Predicate::Not(predicates) => {
+            for inner in predicates.iter() {
+                validate_holdings_under(&inner, true);
+            }
+        }
+        Predicate::Unconditional | Predicate::BeforeAbs(_) | Predicate::AfterAbs(_) | Predicate::Price(_) => {}
+    }
+}
@@ -0,0 +1,105 @@
+use soroban_sdk::{contractimpl, Address, Env, Vec};
+
+use crate::{
+    ClaimableBalance, ClaimableBalanceContract, ClaimableBalanceContractArgs,
+    ClaimableBalanceContractClient, DataKey,
+};
+
+#[contractimpl]
+impl ClaimableBalanceContract {
+    /// Returns up to `limit` ids of the open balances the account can claim, starting at position `cursor`.
+    pub fn list_for_claimant(env: Env, claimant: Address, cursor: u32, limit: u32) -> Vec<u64> {
+        list_index(&env, &DataKey::ByClaimant(claimant), cursor, limit)
+    }
+
+    /// Returns up to `limit` ids of the open balances the account deposited, starting at position `cursor`.
+    pub fn list_for_depositor(env: Env, depositor: Address, cursor: u32, limit: u32) -> Vec<u64> {
+        list_index(&env, &DataKey::ByDepositor(depositor), cursor, limit)
+    }
+
+    /// Returns the number of open balances the account deposited, which the configuration may cap.
+    pub fn active_balance_count(env: Env, depositor: Address) -> u32 {
+        active_balance_count(&env, &depositor)
+    }
+}
+
+/// Helper function counting the open balances of a depositor; settled balances leave its index.
+pub(crate) fn active_balance_count(env: &Env, depositor: &Address) -> u32 {
+    env.storage()
+        .persistent()
+        .get::<_, Vec<u64>>(&DataKey::ByDepositor(depositor.clone()))
+        .map_or(0, |balance_ids| balance_ids.len())
+}
+
+/// Helper function adding a new balance to the indexes of its depositor and claimants.
+pub(crate) fn index_balance(env: &Env, balance_id: u64, claimable_balance: &ClaimableBalance) {
+    add_to_index(env, &DataKey::ByDepositor(claimable_balance.depositor.clone()), balance_id);
+    for claimant in claimable_balance.claimants.iter() {
+        add_claimant_entry(env, balance_id, &claimant);
+    }
+}
+
+/// Helper function removing a settled balance from the indexes of its depositor and claimants.
+pub(crate) fn unindex_balance(env: &Env, balance_id: u64, claimable_balance: &ClaimableBalance) {
+    remove_from_index(env, &DataKey::ByDepositor(claimable_balance.depositor.clone()), balance_id);
+    for claimant in claimable_balance.claimants.iter() {
+        remove_claimant_entry(env, balance_id, &claimant);
+    }
+}
+
+/// Helper function marking an address as claimant of a balance and listing the balance for it.
+pub(crate) fn add_claimant_entry(env: &Env, balance_id: u64, claimant: &Address) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::Claimant(balance_id, claimant.clone()), &());
+    add_to_index(env, &DataKey::ByClaimant(claimant.clone()), balance_id);
+}
+
+/// Helper function dropping the claimant marker of an address and unlisting the balance for it.
+pub(crate) fn remove_claimant_entry(env: &Env, balance_id: u64, claimant: &Address) {
+    env.storage()
+        .persistent()
+        .remove(&DataKey::Claimant(balance_id, claimant.clone()));
+    remove_from_index(env, &DataKey::ByClaimant(claimant.clone()), balance_id);
+}
+
+/// Helper function telling whether an address is a claimant of a balance without scanning its claimant list.
+pub(crate) fn is_claimant(env: &Env, balance_id: u64, claimant: &Address) -> bool {
+    env.storage()
+        .persistent()
+        .has(&DataKey::Claimant(balance_id, claimant.clone()))
+}
+
+/// Helper function appending a balance id to an index, unless a duplicate claimant already listed it.
+pub(crate) fn add_to_index(env: &Env, key: &DataKey, balance_id: u64) {
+    let mut balance_ids: Vec<u64> = env.storage().persistent().get(key).unwrap_or(Vec::new(env));
+    if balance_ids.contains(balance_id) {
+        return;
+    }
+    balance_ids.push_back(balance_id);
+    env.storage().persistent().set(key, &balance_ids);
+}
+
+/// Helper function dropping a balance id from an index, and the index itself once empty.
+pub(crate) fn remove_from_index(env: &Env, key: &DataKey, balance_id: u64) {
+    let Some(mut balance_ids) = env.storage().persistent().get::<_, Vec<u64>>(key) else {
+        return;
+    };
+    if let Some(position) = balance_ids.first_index_of(balance_id) {
+        balance_ids.remove(position);
+    }
+
+    if balance_ids.is_empty() {
+        env.storage().persistent().remove(key);
+    } else {
+        env.storage().persistent().set(key, &balance_ids);
+    }
+}
+
+/// Helper function returning one page of an index.
+fn list_index(env: &Env, key: &DataKey, cursor: u32, limit: u32) -> Vec<u64> {
+    let balance_ids: Vec<u64> = env.storage().persistent().get(key).unwrap_or(Vec::new(env));
+    let start = cursor.min(balance_ids.len());
+    let end = cursor.saturating_add(limit).min(balance_ids.len());
+    balance_ids.slice(start..end)
+}
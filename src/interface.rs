@@ -0,0 +1,62 @@
+use soroban_sdk::{contractclient, Address, Env, Vec};
+
+use crate::{ClaimableBalance, DepositOptions, TimeBound};
+
+/// Interface other contracts (DAOs, payroll contracts) can program against to deposit into and query a deployment,
+/// through the generated `ClaimableBalanceClient`.
+#[contractclient(name = "ClaimableBalanceClient")]
+pub trait ClaimableBalanceTrait {
+    /// Deposits a balance locked by a time condition and restricted to specific claimants. Returns its id.
+    fn deposit(
+        env: Env,
+        from: Address,
+        token: Address,
+        amount: i128,
+        claimants: Vec<Address>,
+        time_bound: TimeBound,
+    ) -> u64;
+
+    /// Deposits a balance like `deposit`, with additional optional features enabled. Returns its id.
+    fn deposit_with_options(
+        env: Env,
+        from: Address,
+        token: Address,
+        amount: i128,
+        claimants: Vec<Address>,
+        time_bound: TimeBound,
+        options: DepositOptions,
+    ) -> u64;
+
+    /// Claims a balance for the claimant once its conditions are met.
+    fn claim(env: Env, claimant: Address, balance_id: u64);
+
+    /// Claims a balance, delivering the tokens to `destination` instead of the claimant.
+    fn claim_to(env: Env, claimant: Address, destination: Address, balance_id: u64);
+
+    /// Pays out the portion of a vesting balance that vested since the last claim.
+    fn claim_vested(env: Env, claimant: Address, balance_id: u64) -> i128;
+
+    /// Returns an expired balance to its depositor.
+    fn refund(env: Env, from: Address, balance_id: u64) -> i128;
+
+    /// Returns a balance that has not been settled yet.
+    fn get_balance(env: Env, balance_id: u64) -> ClaimableBalance;
+
+    /// Returns whether the claimant can currently claim the balance.
+    fn is_claimable(env: Env, balance_id: u64, claimant: Address) -> bool;
+
+    /// Returns when the next portion of a balance unlocks; `None` means it expired.
+    fn next_unlock_time(env: Env, balance_id: u64) -> Option<u64>;
+
+    /// Returns the total amount of a vesting balance vested so far.
+    fn vested_amount(env: Env, balance_id: u64) -> i128;
+
+    /// Returns up to `limit` ids of the open balances the account can claim, starting at position `cursor`.
+    fn list_for_claimant(env: Env, claimant: Address, cursor: u32, limit: u32) -> Vec<u64>;
+
+    /// Returns up to `limit` ids of the open balances the account deposited, starting at position `cursor`.
+    fn list_for_depositor(env: Env, depositor: Address, cursor: u32, limit: u32) -> Vec<u64>;
+
+    /// Returns the amount of a token currently locked in open balances.
+    fn total_locked(env: Env, token: Address) -> i128;
+}
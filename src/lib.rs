@@ -1,57 +1,695 @@
 #![no_std]
 
-use soroban_sdk::{contract, contractimpl, contracttype, token, Address, Env, Vec};
+use soroban_sdk::{
+    contract, contractclient, contracterror, contractimpl, contractmeta, contracttype, symbol_short, token, Address,
+    Env, Error,
+    xdr::ToXdr, Bytes, BytesN, Map, String, Vec,
+};
+
+// Metadata embedded in the Wasm so deployments can be identified on-chain
+contractmeta!(key = "name", val = "soroban-timelock");
+contractmeta!(key = "binver", val = "1.0.0");
+contractmeta!(key = "source_repo", val = "https://github.com/jaguuai/soroban-timelock");
 
 /// Enum used as storage keys for the contract.
 #[derive(Clone)]
 #[contracttype]
 pub enum DataKey {
-    Init,     // Indicates whether the contract has been initialized
-    Balance,  // Stores the claimable balance data
+    NextBalanceId,                      // Id assigned to the next deposited balance
+    Balance(u64),                       // Stores a claimable balance by id (persistent storage)
+    BalanceMetadata(u64),               // Claimants, gift message and memo of a balance, kept apart from its claim-relevant record (persistent storage)
+    Admin,                              // Address allowed to update the configuration
+    PendingAdmin,                       // Address proposed as the next admin, until it accepts
+    Config,                             // Stores the admin-tunable configuration
+    RentBudget(u64),                    // Native-asset budget escrowed by a balance's depositor to pay for TTL bumps (persistent storage)
+    LastRentBump(u64),                  // Ledger sequence of the last paid TTL bump of a balance (persistent storage)
+    Claimed(u64, Address),              // Marks a claimant that already claimed its allocation of a balance (persistent storage)
+    Withdrawals(u64, Address),          // Ring buffer of a claimant's recent (timestamp, amount) withdrawals (persistent storage)
+    BalanceWithdrawals(u64),            // Ring buffer of all recent (timestamp, amount) withdrawals of a balance (persistent storage)
+    ClaimedLeaf(u64, BytesN<32>),       // Marks an airdrop leaf of a balance as claimed (persistent storage)
+    PendingDelivery(Address, Address),  // Claimed amount of a token held for a claimant that could not receive it
+    NextOperationId,                    // Id assigned to the next scheduled operation
+    Operation(u64),                     // Stores a scheduled operation (persistent storage)
+    Role(Role, Address),                // Time from which an account holds a role (persistent storage)
+    Paused,                             // Set while paused; holds whether claims are paused too
+    PendingUpgrade,                     // Stores the announced contract upgrade and when it can be installed
+    NextStreamId,                       // Id assigned to the next stream
+    Stream(u64),                        // Stores a payment stream by id (persistent storage)
+    Approvals(u64),                     // Claimant approvals gathered for releasing a k-of-n balance (persistent storage)
+    Confirmation(u64, Address),         // Marks an escrow party that confirmed the release (persistent storage)
+    Claimant(u64, Address),             // Marks an address as claimant of a balance, for lookups without scanning the list (persistent storage)
+    ByClaimant(Address),                // Ids of the open balances an account can claim (persistent storage)
+    ByDepositor(Address),               // Ids of the open balances an account deposited (persistent storage)
+    TvlByToken(Address),                // Amount of a token locked in open balances
+    ReservedByToken(Address),           // Amount of a token held on top of the locked total: bounties, rent budgets, streams and pending deliveries
+    Tokens,                             // Every token deposited so far
+    ClaimRecord(u64, u32),              // Payout of a balance at a position of its append-only history (persistent storage)
+    ClaimCount(u64),                    // Number of payouts recorded in the history of a balance (persistent storage)
+    UsedNonce(Address, u64),            // Marks a nonce of an address as used by a signed payload or consumed explicitly (persistent storage)
+    PendingRotation(u64, Address),      // Replacement address a claimant proposed, until the depositor approves it (persistent storage)
+    PendingExtension(u64),              // Later unlock time the depositor proposed, until every claimant accepts it (persistent storage)
+    PendingFreezeAction(u64),           // Admin action on a balance announced with the freeze delay (persistent storage)
+    Status(u64),                        // Lifecycle state of a balance, kept after the balance is closed (persistent storage)
+    SaltUsed(u64),                      // Marks a salted balance id as taken, even after the balance is settled (persistent storage)
+    StorageVersion,                     // Version of the layout the stored data is in, advanced by `migrate`
+    TokenAllowlist,                     // Tokens accepted by deposits; every token is accepted while it is empty
+    LotteryWinner(u64),                 // Claimant drawn to receive a lottery balance, kept after the balance is settled (persistent storage)
+    MinDeposit(Address),                // Smallest amount of a token a deposit can lock
+    Deployment(u64),                    // Part of a balance deployed to a yield strategy, until recalled (persistent storage)
+    Shortfall(u64),                     // Principal a balance lost to yield strategies, kept after it settles (persistent storage)
 }
 
 /// Enum representing the type of time-bound restriction.
 #[derive(Clone)]
 #[contracttype]
 pub enum TimeBoundKind {
-    Before,  // Claim allowed before a given timestamp
-    After,   // Claim allowed after a given timestamp
+    Before,        // Claim allowed before a given timestamp
+    After,         // Claim allowed after a given timestamp
+    Between(u64),  // Claim allowed from the given timestamp up to this end timestamp (inclusive)
 }
 
 /// Struct representing the time constraint for claiming.
 #[derive(Clone)]
 #[contracttype]
 pub struct TimeBound {
-    pub kind: TimeBoundKind,  // Type of constraint: Before or After
-    pub timestamp: u64,       // UNIX timestamp used as time threshold
+    pub kind: TimeBoundKind,  // Type of constraint: Before, After or Between
+    pub timestamp: u64,       // UNIX timestamp used as time threshold (window start for Between)
+}
+
+/// Enum representing a composable claim condition, like the predicates of Stellar classic claimable balances.
+/// `Not` wraps a vector because contract types cannot box themselves; it holds when none of its predicates holds.
+#[derive(Clone, Default)]
+#[contracttype]
+pub enum Predicate {
+    #[default]
+    Unconditional,            // Always satisfied
+    BeforeAbs(u64),           // Satisfied strictly before the UNIX timestamp
+    AfterAbs(u64),            // Satisfied from the UNIX timestamp on
+    And(Vec<Predicate>),      // Satisfied when all inner predicates are
+    Or(Vec<Predicate>),       // Satisfied when any inner predicate is
+    Not(Vec<Predicate>),      // Satisfied when no inner predicate is
+    Price(PriceCondition),    // Satisfied while an oracle price is above or below a target
+    Holds(HoldingCondition),  // Satisfied while the claimant holds at least a balance of a token; cannot be negated
+}
+
+/// Struct pairing a claimant with its own claim condition, as in Stellar classic claimable balances.
+#[derive(Clone)]
+#[contracttype]
+pub struct Claimant {
+    pub address: Address,      // Address allowed to claim
+    pub predicate: Predicate,  // Condition under which this address can claim
+}
+
+/// Struct giving a claimant an exclusive turn to claim; windows are ordered by priority and must not overlap.
+#[derive(Clone)]
+#[contracttype]
+pub struct ClaimWindow {
+    pub claimant: Address,  // Address allowed to claim during the window
+    pub start: u64,         // UNIX timestamp from which the claimant can claim
+    pub end: u64,           // UNIX timestamp until which the claimant can claim, inclusive
 }
 
 /// Struct representing a claimable token balance with a time lock and designated claimants.
 #[derive(Clone)]
 #[contracttype]
 pub struct ClaimableBalance {
-    pub token: Address,           // Address of the token contract
-    pub amount: i128,             // Amount of tokens to claim
-    pub claimants: Vec<Address>,  // List of addresses allowed to claim
-    pub time_bound: TimeBound,    // Time-bound condition for claiming
+    pub depositor: Address,                            // Address that funded the balance
+    pub refund_to: Address,                            // Receiver of refunds, expiries and revocations; the depositor unless chosen at deposit
+    pub token: Address,                                // Address of the token contract
+    pub amount: i128,                                  // Amount of tokens to claim
+    pub claimants: Vec<Address>,                       // List of addresses allowed to claim
+    pub time_bound: TimeBound,                         // Time-bound condition for claiming
+    pub vesting: Vesting,                              // Optional linear or tranched vesting schedule
+    pub claimed: i128,                                 // Amount already paid out through partial claims
+    pub allocations: Map<Address, i128>,               // Per-claimant shares; empty when the first claimant takes all
+    pub gift_message: Option<String>,                  // Greeting of a gift; gifts can be declined by the recipient or revoked by the sender
+    pub distribution: DistributionMode,                // How the amount is shared between claimants
+    pub hashlock: Hashlock,                            // Optional SHA-256 hashlock whose preimage must be revealed to claim
+    pub withdrawal_cap: WithdrawalCap,                 // Optional per-claimant limit on withdrawals over a rolling window
+    pub airdrop: Airdrop,                              // Optional Merkle root of (address, amount) leaves that replaces the claimant list
+    pub bounty: i128,                                  // Keeper bounty escrowed on top of the amount, paid to whoever distributes or refunds the expired balance
+    pub revocable: bool,                               // Whether the depositor can revoke the unvested part of a vesting balance
+    pub tranches_paid: u32,                            // Number of leading tranches or recurring installments already paid out
+    pub claim_cooldown: u64,                           // Minimum seconds between two partial claims of a vesting balance; 0 when unrestricted
+    pub last_claim_at: u64,                            // UNIX timestamp of the latest partial claim; 0 before the first one
+    pub predicate: Predicate,                          // Additional claim condition, required on top of the time bound
+    pub claimant_predicates: Map<Address, Predicate>,  // Per-claimant conditions; claimants without one only need the balance-wide ones
+    pub claimant_locks: Map<Address, u64>,             // Later unlock times claimants committed to for themselves via `extend_my_lock`
+    pub dead_man_switch: DeadManSwitch,                // Optional heartbeat the depositor must keep sending to hold back the claimants
+    pub multisig: Multisig,                            // Optional k-of-n claimant approval required to release the balance
+    pub escrow: bool,                                  // Whether the depositor (buyer) must confirm before the claimant (seller) is paid
+    pub arbiter: Option<Address>,                      // Optional address settling escrow disputes
+    pub disputed: bool,                                // Whether an escrow dispute awaits the arbiter's resolution
+    pub created_at: u64,                               // UNIX timestamp of the deposit
+    pub basket: Vec<(Address, i128)>,                  // Further tokens and amounts paid out together with the main one
+    pub gate: Option<Address>,                         // Optional contract that must approve each claimant at claim time
+    pub early_claim: EarlyClaim,                       // Optional terms for claiming before the unlock against a penalty
+    pub memo: Option<String>,                          // Optional reference such as a payroll run, invoice id or grant name
+    pub frozen: bool,                                  // Whether the admin blocked every claim of the balance
+    pub immutable: bool,                               // Whether the claimant list is fixed, instead of editable by the depositor and admin before unlock
+    pub claim_windows: Vec<ClaimWindow>,               // Ordered windows restricting when each claimant can claim; empty when unrestricted
+}
+
+/// Struct holding the parts of a balance that claims do not need, stored apart so claims read and write less.
+#[derive(Clone)]
+#[contracttype]
+struct BalanceMetadata {
+    claimants: Vec<Address>,       // List of addresses allowed to claim
+    gift_message: Option<String>,  // Greeting of a gift
+    memo: Option<String>,          // Optional reference
+}
+
+impl ClaimableBalance {
+    /// Creates a winner-takes-all balance without any optional feature enabled.
+    pub fn new(
+        env: &Env,
+        depositor: Address,
+        token: Address,
+        amount: i128,
+        claimants: Vec<Address>,
+        time_bound: TimeBound,
+    ) -> Self {
+        ClaimableBalance {
+            refund_to: depositor.clone(),
+            depositor,
+            token,
+            amount,
+            claimants,
+            time_bound,
+            vesting: Vesting::None,
+            claimed: 0,
+            allocations: Map::new(env),
+            gift_message: None,
+            distribution: DistributionMode::FirstComeFirstServed,
+            hashlock: Hashlock::None,
+            withdrawal_cap: WithdrawalCap::None,
+            airdrop: Airdrop::None,
+            bounty: 0,
+            revocable: false,
+            tranches_paid: 0,
+            claim_cooldown: 0,
+            last_claim_at: 0,
+            predicate: Predicate::Unconditional,
+            claimant_predicates: Map::new(env),
+            claimant_locks: Map::new(env),
+            dead_man_switch: DeadManSwitch::None,
+            multisig: Multisig::None,
+            escrow: false,
+            arbiter: None,
+            disputed: false,
+            created_at: current_time(env),
+            basket: Vec::new(env),
+            gate: None,
+            early_claim: EarlyClaim::None,
+            memo: None,
+            frozen: false,
+            immutable: false,
+            claim_windows: Vec::new(env),
+        }
+    }
+}
+
+/// Struct describing a linear vesting schedule: nothing vests before `cliff`, everything is vested at `end`.
+#[derive(Clone)]
+#[contracttype]
+pub struct VestingSchedule {
+    pub start: u64,  // UNIX timestamp at which vesting starts accruing
+    pub cliff: u64,  // UNIX timestamp before which nothing can be claimed
+    pub end: u64,    // UNIX timestamp at which the full amount is vested
+}
+
+/// Enum representing whether a balance vests over time (contract types cannot hold `Option` of custom types).
+#[derive(Clone, Default)]
+#[contracttype]
+pub enum Vesting {
+    #[default]
+    None,                           // The whole amount is released at once
+    Linear(VestingSchedule),        // The amount is released linearly according to the schedule
+    Tranches(Vec<Tranche>),         // The amount is released in steps, each tranche at its timestamp
+    Recurring(RecurringSchedule),   // The amount is released in equal installments at a fixed interval
+    Milestones(MilestoneSchedule),  // The amount is released milestone by milestone, each once approved
+}
+
+/// Struct describing one step of a tranched unlock schedule.
+#[derive(Clone)]
+#[contracttype]
+pub struct Tranche {
+    pub timestamp: u64,  // UNIX timestamp from which the tranche can be claimed
+    pub amount: i128,    // Amount released by the tranche
+}
+
+/// Struct describing a recurring unlock, e.g. a monthly salary: `amount / installments` unlocks at `first_unlock`
+/// and after every further `interval`, the last installment also carrying the rounding remainder.
+#[derive(Clone)]
+#[contracttype]
+pub struct RecurringSchedule {
+    pub first_unlock: u64,   // UNIX timestamp of the first installment
+    pub interval: u64,       // Seconds between two installments
+    pub installments: u32,   // Number of installments the amount is split into
+}
+
+/// Enum describing the terminal action performed by `settle`, with the amount transferred.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum Settlement {
+    Claimed(i128),      // The caller claimed (the vested part of) the balance as a claimant
+    Refunded(i128),     // The expired balance was returned to the caller as depositor
+    Distributed(i128),  // The caller pushed the remaining per-claimant shares as keeper
+}
+
+/// Enum representing how a balance is shared between its claimants.
+#[derive(Clone, Default)]
+#[contracttype]
+pub enum DistributionMode {
+    #[default]
+    FirstComeFirstServed,           // The first claimant to claim receives the whole amount
+    EqualSplit,                     // Each claimant can claim `amount / claimants.len()` once; the last one also gets the remainder
+    Weighted(Vec<(Address, u32)>),  // Each claimant can claim its pro-rata share of the weights once; the first heaviest also gets the remainder
+    Lottery(BytesN<32>),            // The claimant drawn by `draw` from the depositor's seed, committed here as its SHA-256, receives the whole amount
+}
+
+/// Enum representing whether claiming requires revealing a secret, as in hash time-locked contracts.
+#[derive(Clone, Default)]
+#[contracttype]
+pub enum Hashlock {
+    #[default]
+    None,                // No secret is required
+    Sha256(BytesN<32>),  // Claim requires a preimage whose SHA-256 hash matches
+}
+
+/// Struct describing a rolling withdrawal window: at most `max_amount` per claimant within any `window` seconds.
+#[derive(Clone)]
+#[contracttype]
+pub struct RollingWindow {
+    pub max_amount: i128,  // Maximum amount a claimant can withdraw within the window
+    pub window: u64,       // Length of the rolling window in seconds
+}
+
+/// Enum representing whether withdrawals are capped over a rolling window, per claimant or for the whole balance.
+#[derive(Clone, Default)]
+#[contracttype]
+pub enum WithdrawalCap {
+    #[default]
+    None,                       // Withdrawals are not capped
+    Rolling(RollingWindow),     // Withdrawals are capped per claimant over a rolling window
+    PerBalance(RollingWindow),  // Withdrawals of all claimants together are capped over a rolling window
+}
+
+/// Enum representing whether claimants are proven against a Merkle root instead of listed explicitly.
+#[derive(Clone, Default)]
+#[contracttype]
+pub enum Airdrop {
+    #[default]
+    None,                    // Claimants are listed in the balance
+    MerkleRoot(BytesN<32>),  // Claimants prove a sha256(address xdr || amount) leaf against this root
+}
+
+/// Enum representing a dead man's switch: the depositor keeps postponing the unlock until the heartbeats stop.
+#[derive(Clone, Default)]
+#[contracttype]
+pub enum DeadManSwitch {
+    #[default]
+    None,            // The unlock time is fixed
+    Heartbeat(u64),  // Each heartbeat moves the unlock to this many seconds from now
+}
+
+/// Enum representing whether releasing a balance needs the approval of several claimants.
+#[derive(Clone, Default)]
+#[contracttype]
+pub enum Multisig {
+    #[default]
+    None,            // Any single claimant can claim
+    Threshold(u32),  // This many claimants must approve the destination before `execute_claim` releases the balance
+}
+
+/// Enum representing the bounty a deposit offers to keepers that push its payout or refund once due.
+#[derive(Clone, Default)]
+#[contracttype]
+pub enum Bounty {
+    #[default]
+    None,         // Keepers are not rewarded
+    Fixed(i128),  // Keepers receive a fixed amount of the balance's token
+    Bps(u32),     // Keepers receive this many basis points of the balance's amount
+}
+
+/// Version of the contract code, bumped by every release that changes its storage layout or behavior.
+pub const CONTRACT_VERSION: u32 = 1;
+
+/// Number of basis points in a whole.
+const BPS_DENOMINATOR: i128 = 10_000;
+
+/// Largest amount a balance can lock, so that basis-point math on any amount fits in an i128.
+pub const MAX_AMOUNT: i128 = i128::MAX / BPS_DENOMINATOR;
+
+/// Smallest scheduler `min_delay`, so every operation can be reviewed and cancelled before it runs.
+pub const MIN_DELAY: u64 = 60 * 60;
+
+/// Maximum number of recent withdrawals remembered per claimant for rolling caps.
+const WITHDRAWAL_RING_SIZE: u32 = 8;
+
+/// Maximum length of a balance memo in bytes.
+const MAX_MEMO_LENGTH: u32 = 128;
+
+/// Enum selecting how a deposit pulls the tokens from the depositor.
+#[derive(Clone)]
+enum Funding {
+    Transfer,          // The depositor authorizes a transfer to the contract
+    Allowance,         // The contract spends an allowance the depositor approved beforehand
+    Sponsor(Address),  // A sponsor authorizes the transfer while the recorded depositor gets any refund
+}
+
+/// Struct grouping the optional features that can be enabled on a deposit.
+#[derive(Clone, Default)]
+#[contracttype]
+pub struct DepositOptions {
+    pub vesting: Vesting,                // Release the amount linearly instead of all at once
+    pub distribution: DistributionMode,  // Share the amount between claimants instead of winner-takes-all
+    pub hashlock: Hashlock,              // Require a secret preimage to claim, for atomic swaps
+    pub withdrawal_cap: WithdrawalCap,   // Cap what each claimant can withdraw over a rolling window
+    pub bounty: Bounty,                  // Reward keepers for pushing the payout or refund, escrowed on top of the amount
+    pub revocable: bool,                 // Let the depositor claw back unvested tokens, e.g. when an employee leaves
+    pub claim_cooldown: u64,             // Space out partial claims of a vesting balance by at least this many seconds
+    pub predicate: Predicate,            // Combine extra time conditions with AND / OR / NOT
+    pub dead_man_switch: DeadManSwitch,  // Only let the backup claimants in once the depositor stops sending heartbeats
+    pub multisig: Multisig,              // Require k of the n claimants to approve where the balance goes
+    pub escrow: bool,                    // Release to the seller only when buyer and seller confirm, refund the buyer after the timeout
+    pub arbiter: Option<Address>,        // Let this address split a disputed escrow between buyer and seller
+    pub gate: Option<Address>,           // Let a gate contract decide who may claim, e.g. a KYC allowlist or NFT holders
+    pub early_claim: EarlyClaim,         // Let the claimant claim before the unlock by forfeiting a penalty
+    pub memo: Option<String>,            // Attach a reference such as a payroll run, invoice id or grant name
+    pub immutable: bool,                 // Stop the depositor and admin from adding or removing claimants before unlock
+    pub refund_to: Option<Address>,      // Send refunds, expiries and revocations to this address, e.g. a DAO treasury
+}
+
+/// Struct describing one of the balances funded together by `deposit_batch`.
+#[derive(Clone)]
+#[contracttype]
+pub struct DepositEntry {
+    pub amount: i128,             // Amount of tokens to lock
+    pub claimants: Vec<Address>,  // Allowed claimants
+    pub time_bound: TimeBound,    // Time-bound constraint
+}
+
+/// Struct holding every admin-tunable parameter of the contract.
+#[derive(Clone)]
+#[contracttype]
+pub struct Config {
+    pub max_claimants: u32,              // Maximum number of claimants per balance
+    pub max_active_balances: u32,        // Maximum number of open balances per depositor; 0 for no limit
+    pub min_lock_duration: u64,          // Minimum seconds between deposit and an `After`/`Between` unlock
+    pub max_lock_duration: u64,          // Maximum seconds between deposit and an `After`/`Between` unlock
+    pub claim_hook: Option<Address>,     // Optional compliance hook consulted before every claim
+    pub native_token: Option<Address>,   // Native asset contract used for rent budgets
+    pub rent_bump_fee: i128,             // Native amount paid from the rent budget to the keeper of each TTL bump
+    pub time_source: Option<Address>,    // Optional time-attestation contract trusted over the ledger timestamp
+    pub max_time_divergence: u64,        // Maximum seconds the attested time may differ from the ledger timestamp
+    pub treasury: Option<Address>,       // Receiver of swept expired balances; sweeps are disabled when `None`
+    pub sweep_delay: u64,                // Seconds after a balance's claim window closes before it can be swept
+    pub min_delay: u64,                  // Minimum seconds between scheduling an operation and its eta
+    pub grace_period: u64,               // Seconds after its eta an operation can still be executed; 0 never expires
+    pub upgrade_delay: u64,              // Minimum seconds between announcing an upgrade and installing it
+    pub freeze_delay: u64,               // Minimum seconds between announcing a freeze action on a balance and applying it
+    pub fee_bps: u32,                    // Protocol fee taken from every claim, in basis points
+    pub fee_collector: Option<Address>,  // Address receiving the protocol fee
+    pub fee_exempt: Vec<Address>,        // Tokens and claimants exempt from the protocol fee
+    pub swap_router: Option<Address>,    // AMM router swapping claims paid out in another asset
+    pub strategies: Vec<Address>,        // Yield strategies the admin may deploy locked funds to
+    pub yield_recipient: YieldRecipient, // Receiver of the yield earned on deployed funds
+}
+
+impl Config {
+    /// Enforces the cross-field invariants of the configuration; panics if any of them is violated.
+    pub fn validate(&self) {
+        if self.max_claimants == 0 {
+            panic!("max claimants must be positive");
+        }
+
+        if self.min_lock_duration >= self.max_lock_duration {
+            panic!("min lock duration must be lower than max lock duration");
+        }
+
+        if self.rent_bump_fee < 0 {
+            panic!("rent bump fee must not be negative");
+        }
+
+        if self.min_delay < MIN_DELAY {
+            panic!("min delay is below the floor");
+        }
+
+        if self.fee_bps as i128 > BPS_DENOMINATOR {
+            panic!("fee cannot exceed 10000 bps");
+        }
+        if self.fee_bps > 0 && self.fee_collector.is_none() {
+            panic!("fee requires a fee collector");
+        }
+
+        if matches!(self.yield_recipient, YieldRecipient::Treasury) && self.treasury.is_none() {
+            panic!("yield to the treasury requires a treasury");
+        }
+    }
+}
+
+/// Struct returned by hook contracts to explain why a claim is denied.
+#[derive(Clone)]
+#[contracttype]
+pub struct DenialReason {
+    pub code: u32,  // Hook-defined reason code, surfaced as contract error `HOOK_DENIAL_BASE + code` of the failed claim
+}
+
+/// Enum listing why a claim is refused by a condition outside the contract, surfaced as the contract error code of
+/// the failed call.
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum ClaimError {
+    OracleConditionNotMet = 200,  // The time bound holds, but an oracle price condition of the balance does not
+}
+
+/// First contract error code of compliance hook denials, so hook reason codes cannot be mistaken for `DepositError`,
+/// `MathError` or `ClaimError` codes.
+pub const HOOK_DENIAL_BASE: u32 = 1_000;
+
+/// Interface that compliance hook contracts must implement to approve or deny claims.
+#[contractclient(name = "ClaimHookClient")]
+pub trait ClaimHook {
+    /// Returns `None` to allow the claim, or the reason why it is denied.
+    fn check_claim(env: Env, claimant: Address, token: Address, amount: i128) -> Option<DenialReason>;
+}
+
+/// Interface that gate contracts must implement to decide who may claim a balance.
+#[contractclient(name = "ClaimGateClient")]
+pub trait ClaimGate {
+    /// Returns whether the claimant may claim the balance right now.
+    fn check(env: Env, claimant: Address, balance_id: u64) -> bool;
+}
+
+/// Number of ledgers closed in a day, assuming 5 second ledgers.
+const DAY_IN_LEDGERS: u32 = 17280;
+
+/// Minimum number of ledgers between two paid TTL bumps.
+const RENT_BUMP_INTERVAL: u32 = 7 * DAY_IN_LEDGERS;
+
+/// Number of ledgers the contract data is kept alive for by each TTL bump.
+const RENT_BUMP_EXTEND_TO: u32 = 30 * DAY_IN_LEDGERS;
+
+/// Interface that time-attestation contracts must implement to provide the current time.
+#[contractclient(name = "TimeSourceClient")]
+pub trait TimeSource {
+    /// Returns the attested current UNIX timestamp.
+    fn now(env: Env) -> u64;
 }
 
 #[contract]
 pub struct ClaimableBalanceContract;
 
-/// Internal helper function to evaluate if the current ledger timestamp satisfies the given time-bound condition.
-fn check_time_bound(env: &Env, time_bound: &TimeBound) -> bool {
+/// Internal helper function returning the time used for time-bound checks.
+/// Uses the configured time source when it answers within the allowed divergence, and the ledger timestamp otherwise.
+fn current_time(env: &Env) -> u64 {
     let ledger_timestamp = env.ledger().timestamp();
 
-    match time_bound.kind {
-        TimeBoundKind::Before => ledger_timestamp <= time_bound.timestamp,
-        TimeBoundKind::After => ledger_timestamp >= time_bound.timestamp,
+    let config = read_config(env);
+    let Some(time_source) = config.time_source else {
+        return ledger_timestamp;
+    };
+
+    match TimeSourceClient::new(env, &time_source).try_now() {
+        Ok(Ok(attested)) if attested.abs_diff(ledger_timestamp) <= config.max_time_divergence => attested,
+        _ => ledger_timestamp,
+    }
+}
+
+/// Internal helper function to evaluate if the current time satisfies the time bound and predicate of a balance.
+fn check_time_bound(env: &Env, claimable_balance: &ClaimableBalance) -> bool {
+    let ledger_timestamp = current_time(env);
+    timelock_core::time_bound_holds(&claimable_balance.time_bound, ledger_timestamp)
+        && evaluate_predicate(env, &claimable_balance.predicate, ledger_timestamp)
+}
+
+/// Internal helper function evaluating the claimant's own predicate and claim window, if the balance assigned them.
+fn check_claimant_predicate(env: &Env, claimable_balance: &ClaimableBalance, claimant: &Address) -> bool {
+    let now = current_time(env);
+    if let Some(lock) = claimable_balance.claimant_locks.get(claimant.clone()) {
+        if now < lock {
+            return false;
+        }
     }
+    let predicate_holds = match claimable_balance.claimant_predicates.get(claimant.clone()) {
+        Some(predicate) => evaluate_predicate_for(env, &predicate, now, claimant),
+        None => true,
+    };
+
+    // Holding conditions of the balance-wide predicate are only checked once the claimant is known
+    let holdings_met = !timelock_core::has_holding_condition(&claimable_balance.predicate)
+        || evaluate_predicate_for(env, &claimable_balance.predicate, now, claimant);
+    predicate_holds
+        && holdings_met
+        && timelock_core::claim_window_open(&claimable_balance.claim_windows, claimant, now)
+}
+
+/// Internal helper function evaluating a predicate tree at the given time, querying oracles for price conditions.
+fn evaluate_predicate(env: &Env, predicate: &Predicate, now: u64) -> bool {
+    timelock_core::evaluate_predicate(predicate, now, &|condition| oracle::price_condition_holds(env, condition))
+}
+
+/// Internal helper function evaluating a predicate tree for a claimant, querying tokens for holding conditions.
+fn evaluate_predicate_for(env: &Env, predicate: &Predicate, now: u64, claimant: &Address) -> bool {
+    timelock_core::evaluate_predicate_for(
+        predicate,
+        now,
+        &|condition| oracle::price_condition_holds(env, condition),
+        &|condition| holding::holding_condition_holds(env, condition, claimant),
+    )
 }
 
 #[contractimpl]
 impl ClaimableBalanceContract {
+    /// Sets the admin and the initial configuration when the contract is deployed.
+    pub fn __constructor(env: Env, admin: Address, config: Config) {
+        // Reject inconsistent settings before storing anything
+        config.validate();
+
+        env.storage().instance().set(&DataKey::Admin, &admin);
+        env.storage().instance().set(&DataKey::Config, &config);
+        env.storage()
+            .instance()
+            .set(&DataKey::StorageVersion, &CONTRACT_VERSION);
+
+        // Publish the initial configuration for indexers
+        env.events().publish((symbol_short!("config"),), config);
+    }
+
+    /// Replaces the configuration with a new, validated one, keeping the `min_delay` only the scheduler itself can
+    /// change. Only callable by the admin.
+    pub fn update_config(env: Env, config: Config) {
+        // Require that the admin authorizes the update
+        read_admin(&env).require_auth();
+
+        // Reject inconsistent settings so the deployment cannot be bricked
+        config.validate();
+
+        // The scheduler delay can only be changed by waiting it out, through an operation calling `update_delay`
+        let current = read_config(&env);
+        if config.min_delay != current.min_delay {
+            panic!("min_delay can only be changed by a scheduled operation calling update_delay on the timelock");
+        }
+
+        // Claimants keep their time to exit: the upgrade and freeze delays are only lowered through an operation
+        if config.upgrade_delay < current.upgrade_delay || config.freeze_delay < current.freeze_delay {
+            panic!("upgrade and freeze delays can only be lowered through the timelock");
+        }
+
+        env.storage().instance().set(&DataKey::Config, &config);
+
+        // Publish the new configuration for indexers
+        env.events().publish((symbol_short!("config"),), config);
+    }
+
+    /// Proposes a new admin, which only takes over once it calls `accept_admin`. Only callable by the admin.
+    pub fn transfer_admin(env: Env, new_admin: Address) {
+        // Require that the current admin authorizes the handover
+        read_admin(&env).require_auth();
+
+        env.storage().instance().set(&DataKey::PendingAdmin, &new_admin);
+
+        env.events()
+            .publish((symbol_short!("adm_prop"), new_admin), ());
+    }
+
+    /// Completes an admin handover. Only callable by the proposed admin.
+    pub fn accept_admin(env: Env) {
+        let new_admin: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::PendingAdmin)
+            .expect("no admin transfer is pending");
+
+        // Require that the proposed admin proves control of its address
+        new_admin.require_auth();
+
+        env.storage().instance().set(&DataKey::Admin, &new_admin);
+        env.storage().instance().remove(&DataKey::PendingAdmin);
+
+        env.events().publish((symbol_short!("admin"), new_admin), ());
+    }
+
+    /// Returns the current admin.
+    pub fn admin(env: Env) -> Address {
+        read_admin(&env)
+    }
+
+    /// Returns the current configuration.
+    pub fn get_config(env: Env) -> Config {
+        read_config(&env)
+    }
+
+    /// Pauses new deposits, and claims as well if `include_claims` is set. Only callable by the admin.
+    /// Refunds stay available so depositors can always exit.
+    pub fn pause(env: Env, include_claims: bool) {
+        // Require that the admin authorizes the pause
+        read_admin(&env).require_auth();
+
+        env.storage().instance().set(&DataKey::Paused, &include_claims);
+
+        env.events().publish((symbol_short!("pause"),), include_claims);
+    }
+
+    /// Lifts a pause, restoring deposits and claims. Only callable by the admin.
+    pub fn unpause(env: Env) {
+        // Require that the admin authorizes the unpause
+        read_admin(&env).require_auth();
+
+        env.storage().instance().remove(&DataKey::Paused);
+
+        env.events().publish((symbol_short!("unpause"),), ());
+    }
+
+    /// Returns whether deposits are paused.
+    pub fn is_paused(env: Env) -> bool {
+        env.storage().instance().has(&DataKey::Paused)
+    }
+
+    /// Publishes the live balances among ids `start..start + limit` as events so an indexer can rebuild state
+    /// from the event stream. Only callable by the admin; returns the number of balances published.
+    pub fn emit_state_checkpoint(env: Env, start: u64, limit: u32) -> u32 {
+        // Require that the admin authorizes the checkpoint
+        read_admin(&env).require_auth();
+
+        // Ids are assigned sequentially; settled balances leave gaps that are skipped
+        let end = read_next_balance_id(&env).min(start.saturating_add(limit as u64));
+        let mut published = 0;
+        for balance_id in start..end {
+            if let Some(claimable_balance) = load_balance(&env, balance_id) {
+                env.events()
+                    .publish((symbol_short!("ckpt"), balance_id), claimable_balance);
+                published += 1;
+            }
+        }
+
+        published
+    }
+
     /// Deposits a claimable token balance to the contract, locked by a time condition and restricted to specific claimants.
+    /// Returns the id of the new balance.
     pub fn deposit(
         env: Env,
         from: Address,              // Address sending the tokens
@@ -59,74 +697,2112 @@ impl ClaimableBalanceContract {
         amount: i128,               // Amount of tokens to deposit
         claimants: Vec<Address>,    // Allowed claimants
         time_bound: TimeBound,      // Time-bound constraint
-    ) {
-        // Enforce a maximum number of claimants
-        if claimants.len() > 10 {
-            panic!("too many claimants");
+    ) -> u64 {
+        let claimable_balance = ClaimableBalance::new(&env, from, token, amount, claimants, time_bound);
+        create_balance(&env, claimable_balance)
+    }
+
+    /// Deposits a balance like `deposit`, pulling the tokens through `transfer_from` out of an allowance the
+    /// depositor approved for this contract, e.g. when the depositor is a contract managing allowances.
+    /// The depositor authorizes the deposit but not a nested transfer. Returns the id of the new balance.
+    pub fn deposit_from_allowance(
+        env: Env,
+        from: Address,              // Address that approved the allowance
+        token: Address,             // Token contract address
+        amount: i128,               // Amount of tokens to deposit
+        claimants: Vec<Address>,    // Allowed claimants
+        time_bound: TimeBound,      // Time-bound constraint
+    ) -> u64 {
+        let claimable_balance = ClaimableBalance::new(&env, from, token, amount, claimants, time_bound);
+        create_balance_as(&env, take_next_balance_id(&env), claimable_balance, Funding::Allowance)
+    }
+
+    /// Deposits a balance like `deposit`, paid by the sponsor but recorded for `on_behalf_of`, which becomes the
+    /// depositor receiving refunds, e.g. a treasury funding a subsidiary's grants. Returns the id of the new balance.
+    pub fn deposit_for(
+        env: Env,
+        sponsor: Address,           // Address paying the tokens
+        on_behalf_of: Address,      // Address recorded as the depositor
+        token: Address,             // Token contract address
+        amount: i128,               // Amount of tokens to deposit
+        claimants: Vec<Address>,    // Allowed claimants
+        time_bound: TimeBound,      // Time-bound constraint
+    ) -> u64 {
+        let claimable_balance =
+            ClaimableBalance::new(&env, on_behalf_of, token, amount, claimants, time_bound);
+        let balance_id = create_balance_as(
+            &env,
+            take_next_balance_id(&env),
+            claimable_balance,
+            Funding::Sponsor(sponsor.clone()),
+        );
+
+        env.events()
+            .publish((symbol_short!("sponsor"), balance_id), sponsor);
+        balance_id
+    }
+
+    /// Deposits a claimable token balance like `deposit`, with additional optional features enabled.
+    pub fn deposit_with_options(
+        env: Env,
+        from: Address,              // Address sending the tokens
+        token: Address,             // Token contract address
+        amount: i128,               // Amount of tokens to deposit
+        claimants: Vec<Address>,    // Allowed claimants
+        time_bound: TimeBound,      // Time-bound constraint
+        options: DepositOptions,    // Optional features such as vesting
+    ) -> u64 {
+        let mut claimable_balance =
+            ClaimableBalance::new(&env, from, token, amount, claimants, time_bound);
+        claimable_balance.vesting = options.vesting;
+        claimable_balance.distribution = options.distribution;
+        claimable_balance.hashlock = options.hashlock;
+        claimable_balance.withdrawal_cap = options.withdrawal_cap;
+        claimable_balance.revocable = options.revocable;
+        claimable_balance.claim_cooldown = options.claim_cooldown;
+        claimable_balance.predicate = options.predicate;
+        claimable_balance.dead_man_switch = options.dead_man_switch;
+        claimable_balance.multisig = options.multisig;
+        claimable_balance.escrow = options.escrow;
+        claimable_balance.arbiter = options.arbiter;
+        claimable_balance.gate = options.gate;
+        claimable_balance.immutable = options.immutable;
+        claimable_balance.memo = options.memo;
+        claimable_balance.early_claim = options.early_claim;
+        if let Some(refund_to) = options.refund_to {
+            claimable_balance.refund_to = refund_to;
         }
+        claimable_balance.bounty = match options.bounty {
+            Bounty::None => 0,
+            Bounty::Fixed(bounty) => bounty,
+            Bounty::Bps(bps) => {
+                if bps as i128 > BPS_DENOMINATOR {
+                    panic!("bounty cannot exceed 10000 bps");
+                }
+                math::checked(&env, math::bps_of(claimable_balance.amount, bps))
+            }
+        };
+        create_balance(&env, claimable_balance)
+    }
 
-        // Ensure the contract is not already initialized
-        if is_initialized(&env) {
-            panic!("contract has been already initialized");
+    /// Funds many balances of the same token with a single transfer, e.g. a whole payroll. Returns their ids in order.
+    pub fn deposit_batch(env: Env, from: Address, token: Address, entries: Vec<DepositEntry>) -> Vec<u64> {
+        if entries.is_empty() {
+            panic!("entries must not be empty");
         }
 
-        // Require that 'from' address authorizes this call
+        // Validate every balance before moving any tokens
+        let mut claimable_balances = Vec::new(&env);
+        let mut total = 0;
+        for entry in entries.iter() {
+            let mut claimable_balance = ClaimableBalance::new(
+                &env,
+                from.clone(),
+                token.clone(),
+                entry.amount,
+                entry.claimants,
+                entry.time_bound,
+            );
+            validate_balance(&env, &mut claimable_balance);
+            total = math::checked(&env, math::add(total, claimable_balance.amount));
+            claimable_balances.push_back(claimable_balance);
+        }
+        // Each entry was checked against the open balances alone; the batch as a whole has to fit under the cap
+        validation::check_active_balance_cap(&env, &from, entries.len(), &read_config(&env));
+
+        // Require that the depositor authorizes this call, then pull the aggregate amount at once
         from.require_auth();
+        if receive(&env, &token, &from, total) != total {
+            panic!("token took a fee on transfer; batch deposits need the exact amount");
+        }
 
-        // Transfer tokens from 'from' address to this contract
-        token::Client::new(&env, &token).transfer(&from, &env.current_contract_address(), &amount);
-
-        // Store the claimable balance data in contract storage
-        env.storage().instance().set(
-            &DataKey::Balance,
-            &ClaimableBalance {
-                token,
-                amount,
-                time_bound,
-                claimants,
-            },
-        );
+        let mut balance_ids = Vec::new(&env);
+        for claimable_balance in claimable_balances.iter() {
+            let balance_id = take_next_balance_id(&env);
+            balance_ids.push_back(store_balance(&env, balance_id, claimable_balance));
+        }
+        balance_ids
+    }
+
+    /// Deposits a balance split between claimants, each of which can claim exactly its own share.
+    pub fn deposit_allocated(
+        env: Env,
+        from: Address,                        // Address sending the tokens
+        token: Address,                       // Token contract address
+        allocations: Vec<(Address, i128)>,    // Claimants and the amount reserved for each of them
+        time_bound: TimeBound,                // Time-bound constraint
+    ) -> u64 {
+        if allocations.is_empty() {
+            panic!("allocations must not be empty");
+        }
 
-        // Mark contract as initialized to prevent further deposits
-        env.storage().instance().set(&DataKey::Init, &());
+        // Derive the claimants and the total amount from the allocations
+        let mut claimants = Vec::new(&env);
+        let mut shares = Map::new(&env);
+        let mut amount: i128 = 0;
+        for (claimant, share) in allocations.iter() {
+            if share <= 0 {
+                panic!("allocation must be positive");
+            }
+            if shares.contains_key(claimant.clone()) {
+                panic!("duplicate claimant");
+            }
+            claimants.push_back(claimant.clone());
+            shares.set(claimant, share);
+            amount = math::checked(&env, math::add(amount, share));
+        }
+
+        let mut claimable_balance =
+            ClaimableBalance::new(&env, from, token, amount, claimants, time_bound);
+        claimable_balance.allocations = shares;
+        create_balance(&env, claimable_balance)
     }
 
     /// Allows a designated claimant to claim the locked token balance if the time condition is met.
-    pub fn claim(env: Env, claimant: Address) {
+    pub fn claim(env: Env, claimant: Address, balance_id: u64) {
         // Require that claimant authorizes the claim
         claimant.require_auth();
 
-        // Retrieve the stored claimable balance; panic if already claimed
-        let claimable_balance: ClaimableBalance =
-            env.storage().instance().get(&DataKey::Balance).unwrap();
+        pay_full_claim(&env, balance_id, claimant.clone(), claimant, None);
+    }
 
-        // Check if current time satisfies the time condition
-        if !check_time_bound(&env, &claimable_balance.time_bound) {
-            panic!("time predicate is not fulfilled");
+    /// Claims a balance like `claim`, delivering the tokens to `destination` (e.g. a cold wallet or treasury)
+    /// instead of the authorizing claimant.
+    pub fn claim_to(env: Env, claimant: Address, destination: Address, balance_id: u64) {
+        // Require that claimant authorizes the claim
+        claimant.require_auth();
+
+        pay_full_claim(&env, balance_id, claimant, destination, None);
+    }
+
+    /// Deposits a balance that unlocks in arbitrary steps (e.g. 10% at TGE, then quarterly).
+    /// The amount is the sum of the tranches; `claim` releases every matured tranche at once.
+    pub fn deposit_tranched(
+        env: Env,
+        from: Address,              // Address sending the tokens
+        token: Address,             // Token contract address
+        claimants: Vec<Address>,    // Allowed claimants
+        time_bound: TimeBound,      // Time-bound constraint
+        tranches: Vec<Tranche>,     // Unlock steps, ordered by timestamp
+    ) -> u64 {
+        let amount = tranches
+            .iter()
+            .try_fold(0, |total, tranche| math::add(total, tranche.amount));
+        let amount = math::checked(&env, amount);
+        let mut claimable_balance =
+            ClaimableBalance::new(&env, from, token, amount, claimants, time_bound);
+        claimable_balance.vesting = Vesting::Tranches(tranches);
+        create_balance(&env, claimable_balance)
+    }
+
+    /// Deposits a winner-takes-all balance where each claimant has its own predicate,
+    /// e.g. a beneficiary who can claim any time and a fallback address that can only claim after 30 days.
+    pub fn deposit_with_predicates(
+        env: Env,
+        from: Address,              // Address sending the tokens
+        token: Address,             // Token contract address
+        amount: i128,               // Amount of tokens to deposit
+        claimants: Vec<Claimant>,   // Allowed claimants and their conditions
+        time_bound: TimeBound,      // Time-bound constraint shared by all claimants
+    ) -> u64 {
+        // Split the claimant entries into the claimant list and their predicates
+        let mut addresses = Vec::new(&env);
+        let mut predicates = Map::new(&env);
+        for claimant in claimants.iter() {
+            if predicates.contains_key(claimant.address.clone()) {
+                panic!("duplicate claimant");
+            }
+            addresses.push_back(claimant.address.clone());
+            predicates.set(claimant.address, claimant.predicate);
         }
 
-        // Check if the claimant is among the allowed addresses
-        let claimants = &claimable_balance.claimants;
-        if !claimants.contains(&claimant) {
-            panic!("claimant is not allowed to claim this balance");
+        let mut claimable_balance =
+            ClaimableBalance::new(&env, from, token, amount, addresses, time_bound);
+        claimable_balance.claimant_predicates = predicates;
+        create_balance(&env, claimable_balance)
+    }
+
+    /// Deposits a winner-takes-all balance claimable in turns: each claimant can only claim during its window, the
+    /// next one gets its turn if the previous did not claim, and the depositor can refund it after `reclaim_after`.
+    pub fn deposit_with_windows(
+        env: Env,
+        from: Address,                 // Address sending the tokens
+        token: Address,                // Token contract address
+        amount: i128,                  // Amount of tokens to deposit
+        windows: Vec<ClaimWindow>,     // Claimants in order of priority, with their claim windows
+        reclaim_after: u64,            // UNIX timestamp after which the depositor can refund the balance
+    ) -> u64 {
+        // Windows follow each other and all close before the depositor can reclaim
+        let mut claimants = Vec::new(&env);
+        let mut previous_end = 0;
+        for window in windows.iter() {
+            if window.start > window.end {
+                panic!("claim window ends before it starts");
+            }
+            if window.start < previous_end {
+                panic!("claim windows must be ordered and not overlap");
+            }
+            previous_end = window.end;
+            claimants.push_back(window.claimant);
+        }
+        if reclaim_after < previous_end {
+            panic!("reclaim must come after the last claim window");
         }
 
-        // Transfer the token amount to the claimant
-        token::Client::new(&env, &claimable_balance.token).transfer(
-            &env.current_contract_address(),
-            &claimant,
-            &claimable_balance.amount,
-        );
+        let time_bound = TimeBound {
+            kind: TimeBoundKind::Before,
+            timestamp: reclaim_after,
+        };
+        let mut claimable_balance =
+            ClaimableBalance::new(&env, from, token, amount, claimants, time_bound);
+        claimable_balance.claim_windows = windows;
+        create_balance(&env, claimable_balance)
+    }
 
-        // Remove the claimable balance entry after successful claim
-        env.storage().instance().remove(&DataKey::Balance);
+    /// Pushes the remaining per-claimant shares of an allocated or equally split balance to every claimant
+    /// that has not claimed yet. Callable by anyone once the time bound is satisfied, e.g. by keeper bots,
+    /// which receive the balance's bounty. Returns the total amount distributed.
+    pub fn distribute(env: Env, keeper: Address, balance_id: u64) -> i128 {
+        distribute_balance(&env, keeper, balance_id)
     }
-}
 
-/// Helper function to check if the contract has already been initialized with a deposit.
-fn is_initialized(env: &Env) -> bool {
-    env.storage().instance().has(&DataKey::Init)
-}
+    /// Claims a hashlocked balance by revealing the secret preimage of its hashlock.
+    pub fn claim_with_preimage(env: Env, claimant: Address, balance_id: u64, preimage: Bytes) {
+        // Require that claimant authorizes the claim
+        claimant.require_auth();
+
+        pay_full_claim(&env, balance_id, claimant.clone(), claimant, Some(preimage));
+    }
+
+    /// Returns an expired balance (e.g. an HTLC whose preimage was never revealed) to its refund address.
+    /// Only callable by the depositor.
+    pub fn refund(env: Env, from: Address, balance_id: u64) -> i128 {
+        // Require that depositor authorizes the refund
+        from.require_auth();
+
+        // Retrieve the stored claimable balance; panic if already settled
+        let claimable_balance = read_balance(&env, balance_id);
+
+        if claimable_balance.depositor != from {
+            panic!("only the depositor can refund the balance");
+        }
+        if !is_expired(&env, &claimable_balance.time_bound) {
+            panic!("balance has not expired");
+        }
+
+        refund_balance(&env, balance_id, &claimable_balance)
+    }
+
+    /// Returns an expired balance to its refund address on behalf of anyone, paying the balance's bounty to the keeper.
+    pub fn refund_expired(env: Env, keeper: Address, balance_id: u64) -> i128 {
+        refund_expired_balance(&env, keeper, balance_id)
+    }
+
+    /// Pays out the portion of a vesting balance that vested since the last claim.
+    pub fn claim_vested(env: Env, claimant: Address, balance_id: u64) -> i128 {
+        // Require that claimant authorizes the claim
+        claimant.require_auth();
+
+        pay_vested_claim(&env, balance_id, claimant)
+    }
+
+    /// Claims every balance of the claimant's index that can be claimed right now, up to `max_count` of them to
+    /// bound the budget. Balances needing a preimage, approvals or an escrow confirmation are skipped.
+    /// Returns the `(balance_id, token, amount)` of each claim.
+    pub fn claim_all(env: Env, claimant: Address, max_count: u32) -> Vec<(u64, Address, i128)> {
+        // Require that claimant authorizes the claims
+        claimant.require_auth();
+        check_claims_not_paused(&env);
+
+        // Claims shrink the index, so walk a snapshot of it
+        let balance_ids: Vec<u64> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::ByClaimant(claimant.clone()))
+            .unwrap_or(Vec::new(&env));
+
+        let mut claims = Vec::new(&env);
+        for balance_id in balance_ids.iter() {
+            if claims.len() >= max_count {
+                break;
+            }
+            let claimable_balance = read_balance(&env, balance_id);
+            if !is_ready_to_claim(&env, balance_id, &claimable_balance, &claimant) {
+                continue;
+            }
+
+            let amount = match claimable_balance.vesting {
+                Vesting::Linear(_) => pay_vested_claim(&env, balance_id, claimant.clone()),
+                Vesting::None | Vesting::Tranches(_) | Vesting::Recurring(_) | Vesting::Milestones(_) => {
+                    pay_full_claim(&env, balance_id, claimant.clone(), claimant.clone(), None)
+                }
+            };
+            claims.push_back((balance_id, claimable_balance.token, amount));
+        }
+        claims
+    }
+
+    /// Deposits an airdrop whose claimants and amounts are committed to by a Merkle root,
+    /// allowing far more recipients than an explicit claimant list.
+    pub fn deposit_merkle(
+        env: Env,
+        from: Address,              // Address sending the tokens
+        token: Address,             // Token contract address
+        amount: i128,               // Total amount of tokens to airdrop
+        merkle_root: BytesN<32>,    // Root of the (address, amount) leaves
+        time_bound: TimeBound,      // Time-bound constraint
+    ) -> u64 {
+        let mut claimable_balance =
+            ClaimableBalance::new(&env, from, token, amount, Vec::new(&env), time_bound);
+        claimable_balance.airdrop = Airdrop::MerkleRoot(merkle_root);
+        create_balance(&env, claimable_balance)
+    }
+
+    /// Claims an airdrop leaf by proving `(claimant, amount)` against the Merkle root.
+    pub fn claim_with_proof(
+        env: Env,
+        claimant: Address,
+        balance_id: u64,
+        amount: i128,
+        proof: Vec<BytesN<32>>,
+    ) {
+        // Require that claimant authorizes the claim
+        claimant.require_auth();
+
+        check_claims_not_paused(&env);
+
+        // Retrieve the stored claimable balance; panic if fully claimed
+        let mut claimable_balance = read_balance_hot(&env, balance_id);
+        let Airdrop::MerkleRoot(root) = claimable_balance.airdrop.clone() else {
+            panic!("balance is not a merkle airdrop");
+        };
+
+        // Check if current time satisfies the time condition
+        if !check_time_bound(&env, &claimable_balance) {
+            panic!("time predicate is not fulfilled");
+        }
+
+        // A leaf of zero or less would record a claim without paying anything
+        if amount <= 0 {
+            panic!("amount must be positive");
+        }
+
+        // Verify the leaf against the root and make sure it is only claimed once
+        let leaf = merkle_leaf(&env, &claimant, amount);
+        if !verify_merkle_proof(&env, &leaf, &proof, &root) {
+            panic!("invalid merkle proof");
+        }
+        let leaf_key = DataKey::ClaimedLeaf(balance_id, leaf);
+        if env.storage().persistent().has(&leaf_key) {
+            panic!("leaf has already been claimed");
+        }
+        let unclaimed = math::checked(&env, math::sub(claimable_balance.amount, claimable_balance.claimed));
+        if amount > unclaimed {
+            panic!("airdrop is exhausted");
+        }
+
+        freeze::check_not_frozen(&claimable_balance);
+        strategy::check_not_deployed(&env, balance_id);
+        check_claim_hook(&env, &claimant, &claimable_balance.token, amount);
+        check_gate(&env, balance_id, &claimable_balance, &claimant);
+
+        env.storage().persistent().set(&leaf_key, &());
+
+        // Remove the balance once every leaf has been paid out
+        claimable_balance.claimed = math::checked(&env, math::add(claimable_balance.claimed, amount));
+        tvl::adjust_total_locked(&env, &claimable_balance.token, -amount);
+        history::record_claim(&env, balance_id, &claimant, amount);
+        if claimable_balance.claimed == claimable_balance.amount {
+            close_balance(&env, balance_id, &claimable_balance, BalanceStatus::Claimed);
+        } else {
+            write_balance(&env, balance_id, &claimable_balance);
+        }
+
+        // Transfer only once the claim is recorded
+        deliver_claim(&env, &claimable_balance.token, &claimant, &claimant, amount);
+
+        events::publish_claim(&env, balance_id, claimant.clone(), claimable_balance.token, amount, claimant);
+    }
+
+    /// Retries delivering a claimed amount that was held because the claimant could not receive the token,
+    /// e.g. after the claimant established the missing trustline.
+    pub fn retry_delivery(env: Env, claimant: Address, token: Address) -> i128 {
+        // Require that claimant authorizes the retry
+        claimant.require_auth();
+
+        check_claims_not_paused(&env);
+
+        let pending_key = DataKey::PendingDelivery(claimant.clone(), token.clone());
+        let amount: i128 = env
+            .storage()
+            .persistent()
+            .get(&pending_key)
+            .expect("no pending delivery");
+
+        // This time the transfer must go through
+        env.storage().persistent().remove(&pending_key);
+        tvl::adjust_reserved(&env, &token, -amount);
+        token::Client::new(&env, &token).transfer(&env.current_contract_address(), &claimant, &amount);
+
+        env.events()
+            .publish((symbol_short!("delivered"), claimant, token), amount);
+
+        amount
+    }
+
+    /// Returns the claimed amount of a token held for a claimant that could not receive it.
+    pub fn pending_delivery(env: Env, claimant: Address, token: Address) -> i128 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::PendingDelivery(claimant, token))
+            .unwrap_or(0)
+    }
+
+    /// Sends a gift to a single recipient, who can accept or decline it; the sender can revoke it until accepted.
+    pub fn deposit_gift(
+        env: Env,
+        from: Address,              // Address sending the gift
+        token: Address,             // Token contract address
+        amount: i128,               // Amount of tokens to gift
+        recipient: Address,         // Only address allowed to accept the gift
+        message: String,            // Greeting shown to the recipient
+        time_bound: TimeBound,      // Time-bound constraint on accepting
+    ) -> u64 {
+        let claimants = Vec::from_array(&env, [recipient.clone()]);
+        let mut claimable_balance =
+            ClaimableBalance::new(&env, from.clone(), token.clone(), amount, claimants, time_bound);
+        claimable_balance.gift_message = Some(message.clone());
+        let balance_id = create_balance(&env, claimable_balance);
+
+        // Let the recipient's wallet discover the gift
+        env.events().publish(
+            (symbol_short!("gift"), balance_id, recipient, from),
+            (token, amount, message),
+        );
+
+        balance_id
+    }
+
+    /// Accepts a gift, transferring it to the recipient.
+    pub fn accept(env: Env, recipient: Address, balance_id: u64) -> i128 {
+        // Require that recipient authorizes the acceptance
+        recipient.require_auth();
+
+        read_gift(&env, balance_id);
+        pay_full_claim(&env, balance_id, recipient.clone(), recipient, None)
+    }
+
+    /// Declines a gift, sending it straight back to the sender.
+    pub fn decline(env: Env, recipient: Address, balance_id: u64) -> i128 {
+        // Require that recipient authorizes the decline
+        recipient.require_auth();
+
+        let claimable_balance = read_gift(&env, balance_id);
+        if !index::is_claimant(&env, balance_id, &recipient) {
+            panic!("only the recipient can decline the gift");
+        }
+
+        env.events()
+            .publish((symbol_short!("declined"), balance_id, recipient), ());
+        refund_balance(&env, balance_id, &claimable_balance)
+    }
+
+    /// Revokes a gift that has not been accepted yet, returning it to the sender.
+    pub fn revoke_gift(env: Env, from: Address, balance_id: u64) -> i128 {
+        // Require that sender authorizes the revocation
+        from.require_auth();
+
+        let claimable_balance = read_gift(&env, balance_id);
+        if claimable_balance.depositor != from {
+            panic!("only the sender can revoke the gift");
+        }
+
+        env.events()
+            .publish((symbol_short!("revoked"), balance_id, from), ());
+        refund_balance(&env, balance_id, &claimable_balance)
+    }
+
+    /// Executes whichever terminal action is currently permitted for the caller and reports what happened.
+    pub fn settle(env: Env, caller: Address, balance_id: u64) -> Settlement {
+        // Require that caller authorizes the settlement
+        caller.require_auth();
+
+        // Retrieve the stored claimable balance; panic if already settled
+        let claimable_balance = read_balance(&env, balance_id);
+
+        // Claimants get paid as soon as the time bound allows it
+        if index::is_claimant(&env, balance_id, &caller)
+            && check_time_bound(&env, &claimable_balance)
+            && check_claimant_predicate(&env, &claimable_balance, &caller)
+        {
+            return match claimable_balance.vesting {
+                Vesting::None | Vesting::Tranches(_) | Vesting::Recurring(_) | Vesting::Milestones(_) => {
+                    Settlement::Claimed(pay_full_claim(&env, balance_id, caller.clone(), caller, None))
+                }
+                Vesting::Linear(_) => {
+                    Settlement::Claimed(pay_vested_claim(&env, balance_id, caller))
+                }
+            };
+        }
+
+        // The depositor gets back whatever is left once the balance can no longer be claimed
+        if claimable_balance.depositor == caller && is_expired(&env, &claimable_balance.time_bound) {
+            return Settlement::Refunded(refund_balance(&env, balance_id, &claimable_balance));
+        }
+
+        // Anyone else may push the remaining shares of a balance paying each claimant its own
+        if has_claimant_shares(&claimable_balance)
+            && check_time_bound(&env, &claimable_balance)
+            && (!matches!(claimable_balance.distribution, DistributionMode::Lottery(_))
+                || lottery::read_winner(&env, balance_id).is_some())
+        {
+            return Settlement::Distributed(distribute_balance(&env, caller, balance_id));
+        }
+
+        panic!("no settlement action available");
+    }
+
+    /// Revokes a revocable vesting balance: the vested but unclaimed tokens go to the claimant and the unvested
+    /// remainder returns to the refund address. Only callable by the depositor; returns the amount returned.
+    pub fn revoke(env: Env, balance_id: u64) -> i128 {
+        let claimable_balance = read_balance(&env, balance_id);
+
+        // Require that the depositor authorizes the revocation
+        claimable_balance.depositor.require_auth();
+
+        if !claimable_balance.revocable {
+            panic!("balance is not revocable");
+        }
+        freeze::check_not_frozen(&claimable_balance);
+        milestones::check_no_pending_rejection(&claimable_balance);
+
+        // What vested already belongs to the claimant
+        let vested = vested_total(&env, &claimable_balance);
+        let claimant = claimable_balance.claimants.get_unchecked(0);
+        let paid = vested - claimable_balance.claimed;
+        if paid > 0 {
+            history::record_claim(&env, balance_id, &claimant, paid);
+        }
+        let unvested = claimable_balance.amount - vested;
+        close_balance(&env, balance_id, &claimable_balance, BalanceStatus::Cancelled);
+
+        // Pay the vested part to the claimant and return the unvested remainder to the refund address
+        if paid > 0 {
+            deliver_claim(&env, &claimable_balance.token, &claimant, &claimant, paid);
+        }
+        if unvested > 0 {
+            token::Client::new(&env, &claimable_balance.token).transfer(
+                &env.current_contract_address(),
+                &claimable_balance.refund_to,
+                &unvested,
+            );
+        }
+
+        events::publish_revoke(&env, balance_id, claimant, claimable_balance.token, paid, unvested);
+
+        unvested
+    }
+
+    /// Proves the depositor of a dead man's switch balance is still around, pushing the unlock back by the heartbeat interval.
+    /// Returns the new unlock time.
+    pub fn heartbeat(env: Env, from: Address, balance_id: u64) -> u64 {
+        // Require that depositor authorizes the heartbeat
+        from.require_auth();
+
+        let mut claimable_balance = read_balance(&env, balance_id);
+        if claimable_balance.depositor != from {
+            panic!("only the depositor can send a heartbeat");
+        }
+        let DeadManSwitch::Heartbeat(interval) = claimable_balance.dead_man_switch else {
+            panic!("balance has no dead man's switch");
+        };
+
+        // Once the switch fired the balance belongs to the claimants
+        let now = current_time(&env);
+        if now >= claimable_balance.time_bound.timestamp {
+            panic!("dead man's switch has already fired");
+        }
+
+        claimable_balance.time_bound.timestamp = math::checked(&env, math::add_time(now, interval));
+        write_balance(&env, balance_id, &claimable_balance);
+
+        // Publish the postponed unlock time
+        env.events().publish(
+            (symbol_short!("heartbeat"), balance_id, from),
+            claimable_balance.time_bound.timestamp,
+        );
+
+        claimable_balance.time_bound.timestamp
+    }
+
+    /// Adds more of the same token to a balance before it unlocks, under the same claimants and schedule.
+    /// Only callable by the depositor. Returns the new total.
+    pub fn top_up(env: Env, balance_id: u64, additional_amount: i128) -> i128 {
+        let mut claimable_balance = read_balance(&env, balance_id);
+
+        // Require that depositor authorizes the top-up
+        claimable_balance.depositor.require_auth();
+        check_deposits_not_paused(&env);
+
+        if additional_amount <= 0 {
+            panic!("amount must be positive");
+        }
+        if has_fixed_shares(&claimable_balance) || !claimable_balance.basket.is_empty() {
+            panic!("balances with fixed shares cannot be topped up");
+        }
+        let unlocks_later = match claimable_balance.time_bound.kind {
+            TimeBoundKind::Before => false,
+            TimeBoundKind::After | TimeBoundKind::Between(_) => {
+                current_time(&env) < claimable_balance.time_bound.timestamp
+            }
+        };
+        if !unlocks_later {
+            panic!("balance has already unlocked");
+        }
+
+        // Lock what actually arrived, in case the token takes a fee on transfer
+        let received = receive(
+            &env,
+            &claimable_balance.token,
+            &claimable_balance.depositor,
+            additional_amount,
+        );
+        claimable_balance.amount = math::checked(&env, math::add(claimable_balance.amount, received));
+        if claimable_balance.amount > MAX_AMOUNT {
+            env.panic_with_error(Error::from(DepositError::AmountTooLarge));
+        }
+        write_balance(&env, balance_id, &claimable_balance);
+        tvl::adjust_total_locked(&env, &claimable_balance.token, received);
+
+        // Publish the added amount and the new total
+        events::publish_top_up(&env, balance_id, &claimable_balance, received);
+
+        claimable_balance.amount
+    }
+
+    /// Returns a balance that has not been settled yet, including what was already claimed and when it was deposited.
+    pub fn get_balance(env: Env, balance_id: u64) -> ClaimableBalance {
+        read_balance(&env, balance_id)
+    }
+
+    /// Returns whether the claimant is currently allowed to claim the balance by its time bound, predicates, gate
+    /// and claimant list, so wallets can tell "Claim now" from "Locked" without building a failing transaction.
+    pub fn is_claimable(env: Env, balance_id: u64, claimant: Address) -> bool {
+        let Some(claimable_balance) = env
+            .storage()
+            .persistent()
+            .get::<_, ClaimableBalance>(&DataKey::Balance(balance_id))
+        else {
+            return false;
+        };
+
+        !claimable_balance.frozen
+            && index::is_claimant(&env, balance_id, &claimant)
+            && check_time_bound(&env, &claimable_balance)
+            && check_claimant_predicate(&env, &claimable_balance, &claimant)
+            && gate_allows(&env, balance_id, &claimable_balance, &claimant)
+    }
+
+    /// Returns when the next portion of a balance unlocks: the time bound start, the vesting cliff or the next
+    /// unpaid tranche, whichever comes last. A past time means it is claimable now; `None` means it expired, and
+    /// `u64::MAX` that it waits for a milestone approval.
+    pub fn next_unlock_time(env: Env, balance_id: u64) -> Option<u64> {
+        next_unlock_time(&env, &read_balance(&env, balance_id))
+    }
+
+    /// Returns the seconds left until `next_unlock_time`, 0 once unlocked; `None` means the balance expired.
+    pub fn time_remaining(env: Env, balance_id: u64) -> Option<u64> {
+        let unlock_time = next_unlock_time(&env, &read_balance(&env, balance_id))?;
+        Some(unlock_time.saturating_sub(current_time(&env)))
+    }
+
+    /// Returns the total amount vested so far, including already claimed tokens.
+    pub fn vested_amount(env: Env, balance_id: u64) -> i128 {
+        vested_total(&env, &read_balance(&env, balance_id))
+    }
+
+    /// Escrows a native-asset budget that keepers draw on to keep the balance alive. Only callable by the depositor.
+    pub fn fund_rent(env: Env, from: Address, balance_id: u64, amount: i128) {
+        // Require that 'from' address authorizes this call
+        from.require_auth();
+
+        if amount <= 0 {
+            panic!("amount must be positive");
+        }
+
+        // Only the depositor of a still-locked balance can fund its rent
+        let claimable_balance = read_balance(&env, balance_id);
+        if claimable_balance.depositor != from {
+            panic!("only the depositor can fund rent");
+        }
+
+        // Transfer the native budget from 'from' address to this contract
+        let native_token = read_native_token(&env);
+        token::Client::new(&env, &native_token).transfer(
+            &from,
+            &env.current_contract_address(),
+            &amount,
+        );
+        tvl::adjust_reserved(&env, &native_token, amount);
+
+        let rent_budget = math::checked(&env, math::add(read_rent_budget(&env, balance_id), amount));
+        env.storage()
+            .persistent()
+            .set(&DataKey::RentBudget(balance_id), &rent_budget);
+
+        env.events().publish(
+            (symbol_short!("rent_fund"), balance_id, from),
+            (amount, rent_budget),
+        );
+    }
+
+    /// Extends the TTL of a balance and pays the keeper the configured fee from its rent budget.
+    pub fn bump_rent(env: Env, keeper: Address, balance_id: u64) {
+        bump_rent_for(&env, keeper, balance_id);
+    }
+
+    /// Extends the TTL of a balance, its bookkeeping and the contract instance to `extend_to` ledgers, capped at the
+    /// network maximum. Anyone can call it and pay the rent, e.g. to keep a long vesting schedule from being archived.
+    pub fn extend_ttl(env: Env, balance_id: u64, extend_to: u32) -> u32 {
+        let claimable_balance = read_balance(&env, balance_id);
+        let extend_to = extend_to.min(env.storage().max_ttl());
+        extend_balance_ttl(&env, balance_id, &claimable_balance, extend_to);
+
+        env.events()
+            .publish((symbol_short!("ttl"), balance_id), extend_to);
+        extend_to
+    }
+
+    /// Returns the remaining native-asset rent budget of a balance.
+    pub fn rent_budget(env: Env, balance_id: u64) -> i128 {
+        read_rent_budget(&env, balance_id)
+    }
+}
+
+/// Helper function validating a deposit, pulling the tokens and storing the new claimable balance under a fresh id.
+fn create_balance(env: &Env, claimable_balance: ClaimableBalance) -> u64 {
+    create_balance_as(env, take_next_balance_id(env), claimable_balance, Funding::Transfer)
+}
+
+/// Helper function creating a balance like `create_balance`, under the given id and funded the given way.
+fn create_balance_as(
+    env: &Env,
+    balance_id: u64,
+    mut claimable_balance: ClaimableBalance,
+    funding: Funding,
+) -> u64 {
+    validate_balance(env, &mut claimable_balance);
+
+    // Require that whoever pays, the depositor or its sponsor, authorizes this call
+    let from = match &funding {
+        Funding::Sponsor(sponsor) => sponsor.clone(),
+        Funding::Transfer | Funding::Allowance => claimable_balance.depositor.clone(),
+    };
+    from.require_auth();
+
+    // Pull the tokens into this contract, along with the keeper bounty
+    let amount_due = math::checked(env, math::add(claimable_balance.amount, claimable_balance.bounty));
+    let received = match funding {
+        Funding::Transfer | Funding::Sponsor(_) => receive(env, &claimable_balance.token, &from, amount_due),
+        Funding::Allowance => receive_from_allowance(env, &claimable_balance.token, &from, amount_due),
+    };
+
+    // Fee-on-transfer tokens deliver less than requested: lock only what actually arrived
+    let shortfall = amount_due - received;
+    if shortfall > 0 {
+        if has_fixed_shares(&claimable_balance) {
+            panic!("token took a fee on transfer; balances with fixed shares need the exact amount");
+        }
+        claimable_balance.amount -= shortfall;
+        if claimable_balance.amount <= 0 {
+            panic!("nothing left to lock after the transfer fee");
+        }
+    }
+    basket::pull_basket(env, &mut claimable_balance);
+
+    store_balance(env, balance_id, claimable_balance)
+}
+
+/// Helper function rejecting a deposit that breaks the configuration or combines incompatible features.
+fn validate_balance(env: &Env, claimable_balance: &mut ClaimableBalance) {
+    check_deposits_not_paused(env);
+
+    // Reject bad amounts, claimant lists and unlock times with specific error codes
+    let config = read_config(env);
+    validation::validate_deposit(env, claimable_balance, &config);
+    let time_bound = claimable_balance.time_bound.clone();
+
+    // Keep memos short, as they are stored with the balance
+    if claimable_balance.memo.as_ref().is_some_and(|memo| memo.len() > MAX_MEMO_LENGTH) {
+        panic!("memo is too long");
+    }
+
+    // Reject empty or inverted time windows
+    if let TimeBoundKind::Between(end) = time_bound.kind {
+        if time_bound.timestamp >= end {
+            panic!("time window start must be before its end");
+        }
+    }
+
+    // Reject vesting schedules that are not ordered
+    if let Vesting::Linear(vesting) = &claimable_balance.vesting {
+        if vesting.start > vesting.cliff || vesting.cliff > vesting.end || vesting.start >= vesting.end {
+            panic!("vesting schedule must satisfy start <= cliff <= end and start < end");
+        }
+    }
+
+    // Tranches must unlock in order and add up to the amount
+    if let Vesting::Tranches(tranches) = &claimable_balance.vesting {
+        if tranches.is_empty() {
+            panic!("tranches must not be empty");
+        }
+        let mut total = 0;
+        let mut previous: Option<u64> = None;
+        for tranche in tranches.iter() {
+            if tranche.amount <= 0 {
+                panic!("tranche amount must be positive");
+            }
+            if previous.is_some_and(|timestamp| tranche.timestamp <= timestamp) {
+                panic!("tranches must be ordered by timestamp");
+            }
+            previous = Some(tranche.timestamp);
+            total = math::checked(env, math::add(total, tranche.amount));
+        }
+        if total != claimable_balance.amount {
+            panic!("tranches must add up to the amount");
+        }
+    }
+
+    milestones::validate_milestones(env, claimable_balance);
+    early_claim::validate_early_claim(claimable_balance);
+    lottery::validate_lottery(claimable_balance);
+    holding::validate_holdings(&claimable_balance.predicate);
+    for (_, predicate) in claimable_balance.claimant_predicates.iter() {
+        holding::validate_holdings(&predicate);
+    }
+
+    // Cooldowns space out partial claims, which only vesting balances have
+    if claimable_balance.claim_cooldown > 0 && matches!(claimable_balance.vesting, Vesting::None) {
+        panic!("only vesting balances can have a claim cooldown");
+    }
+
+    // Recurring unlocks need a positive interval and installments of at least one token
+    if let Vesting::Recurring(schedule) = &claimable_balance.vesting {
+        if schedule.interval == 0 || schedule.installments == 0 {
+            panic!("recurring schedule needs a positive interval and installment count");
+        }
+        if claimable_balance.amount < schedule.installments as i128 {
+            panic!("amount is too small to split into installments");
+        }
+    }
+
+    // A dead man's switch unlocks after its timestamp and keeps every postponement within the lock duration range
+    if let DeadManSwitch::Heartbeat(interval) = claimable_balance.dead_man_switch {
+        if !matches!(time_bound.kind, TimeBoundKind::After) {
+            panic!("dead man's switch requires an After time bound");
+        }
+        if interval < config.min_lock_duration || interval > config.max_lock_duration {
+            panic!("heartbeat interval is out of bounds");
+        }
+    }
+
+    // k-of-n balances are released at once, with a threshold the claimants can reach
+    if let Multisig::Threshold(threshold) = claimable_balance.multisig {
+        if threshold == 0 || threshold > claimable_balance.claimants.len() {
+            panic!("approval threshold must be between 1 and the number of claimants");
+        }
+        if !matches!(claimable_balance.vesting, Vesting::None)
+            || !matches!(claimable_balance.distribution, DistributionMode::FirstComeFirstServed)
+        {
+            panic!("k-of-n balances cannot vest or be split");
+        }
+    }
+
+    // An escrow pays its single seller at once, and times out back to the buyer
+    if claimable_balance.escrow {
+        if claimable_balance.claimants.len() != 1 {
+            panic!("escrow must have a single seller");
+        }
+        if !matches!(time_bound.kind, TimeBoundKind::Before) {
+            panic!("escrow requires a Before time bound as its timeout");
+        }
+        if !matches!(claimable_balance.vesting, Vesting::None)
+            || !matches!(claimable_balance.multisig, Multisig::None)
+        {
+            panic!("escrow cannot vest or require approvals");
+        }
+    }
+    if claimable_balance.arbiter.is_some() && !claimable_balance.escrow {
+        panic!("only escrows can have an arbiter");
+    }
+
+    // Rolling caps need a positive amount over a non-empty window
+    if let WithdrawalCap::Rolling(cap) | WithdrawalCap::PerBalance(cap) = &claimable_balance.withdrawal_cap {
+        if cap.max_amount <= 0 || cap.window == 0 {
+            panic!("withdrawal cap must have a positive amount and window");
+        }
+    }
+
+    // Caps clip linearly vested claims; a lump sum or step larger than the cap could never be paid
+    if !matches!(claimable_balance.withdrawal_cap, WithdrawalCap::None)
+        && !matches!(claimable_balance.vesting, Vesting::Linear(_))
+    {
+        panic!("withdrawal caps require linear vesting");
+    }
+
+    // Revocable grants vest to a single beneficiary
+    if claimable_balance.revocable {
+        if let Vesting::None = claimable_balance.vesting {
+            panic!("only vesting balances can be revocable");
+        }
+        if claimable_balance.claimants.len() != 1 {
+            panic!("revocable balances must have a single claimant");
+        }
+    }
+
+    // Bounties are paid out, never charged
+    if claimable_balance.bounty < 0 {
+        panic!("bounty must not be negative");
+    }
+
+    // Baskets are paid out whole, to a single winner
+    if !claimable_balance.basket.is_empty()
+        && (!matches!(claimable_balance.vesting, Vesting::None)
+            || !matches!(claimable_balance.distribution, DistributionMode::FirstComeFirstServed)
+            || !claimable_balance.allocations.is_empty()
+            || claimable_balance.arbiter.is_some())
+    {
+        panic!("baskets cannot vest, be split or be arbitrated");
+    }
+
+    // Equal splits need a positive share for every claimant, paid out at once
+    if let DistributionMode::EqualSplit = claimable_balance.distribution {
+        if !matches!(claimable_balance.vesting, Vesting::None) {
+            panic!("equal split cannot be combined with vesting");
+        }
+        if claimable_balance.amount < claimable_balance.claimants.len() as i128 {
+            panic!("amount is too small to split equally");
+        }
+    }
+
+    // Weighted splits need exactly one positive weight per claimant
+    if let DistributionMode::Weighted(weights) = &claimable_balance.distribution {
+        if !matches!(claimable_balance.vesting, Vesting::None) || !claimable_balance.allocations.is_empty() {
+            panic!("weighted split cannot be combined with vesting or allocations");
+        }
+        if weights.len() != claimable_balance.claimants.len() {
+            panic!("every claimant needs exactly one weight");
+        }
+        let mut weighted = Vec::new(env);
+        for (address, weight) in weights.iter() {
+            if weight == 0 {
+                panic!("weights must be positive");
+            }
+            if !claimable_balance.claimants.contains(&address) || weighted.contains(&address) {
+                panic!("every claimant needs exactly one weight");
+            }
+            weighted.push_back(address);
+        }
+    }
+}
+
+/// Helper function storing a funded balance under its new id and listing it for its depositor and claimants.
+fn store_balance(env: &Env, balance_id: u64, claimable_balance: ClaimableBalance) -> u64 {
+    // Publish the deposit so indexers can track the lock without re-simulation
+    events::publish_deposit(env, balance_id, &claimable_balance);
+    // Publish the memo for reconciliation, alongside the deposit
+    if let Some(memo) = &claimable_balance.memo {
+        env.events()
+            .publish((symbol_short!("memo"), balance_id), memo.clone());
+    }
+
+    // List the balance for its depositor and claimants, then store it, keeping both alive
+    index::index_balance(env, balance_id, &claimable_balance);
+    write_balance(env, balance_id, &claimable_balance);
+    tvl::adjust_total_locked(env, &claimable_balance.token, claimable_balance.amount);
+    tvl::adjust_reserved(env, &claimable_balance.token, claimable_balance.bounty);
+
+    balance_id
+}
+
+/// Helper function pulling tokens from an address into the contract, returning the amount actually received.
+fn receive(env: &Env, token: &Address, from: &Address, amount: i128) -> i128 {
+    let token_client = token::Client::new(env, token);
+    let held_before = token_client.balance(&env.current_contract_address());
+    token_client.transfer(from, &env.current_contract_address(), &amount);
+    token_client.balance(&env.current_contract_address()) - held_before
+}
+
+/// Helper function spending an allowance the depositor granted this contract, returning the amount actually received.
+fn receive_from_allowance(env: &Env, token: &Address, from: &Address, amount: i128) -> i128 {
+    let token_client = token::Client::new(env, token);
+    let contract = env.current_contract_address();
+    let held_before = token_client.balance(&contract);
+    token_client.transfer_from(&contract, from, &contract, &amount);
+    token_client.balance(&contract) - held_before
+}
+
+/// Helper function telling whether the amount of a balance is pinned by allocations, tranches, milestones or
+/// airdrop leaves.
+fn has_fixed_shares(claimable_balance: &ClaimableBalance) -> bool {
+    !claimable_balance.allocations.is_empty()
+        || matches!(
+            claimable_balance.vesting,
+            Vesting::Tranches(_) | Vesting::Recurring(_) | Vesting::Milestones(_)
+        )
+        || !matches!(claimable_balance.airdrop, Airdrop::None)
+}
+
+/// Helper function paying the whole balance, or the claimant's allocation, of an authorized claimant to `destination`.
+fn pay_full_claim(
+    env: &Env,
+    balance_id: u64,
+    claimant: Address,
+    destination: Address,
+    preimage: Option<Bytes>,
+) -> i128 {
+    check_claims_not_paused(env);
+
+    // Retrieve the stored claimable balance; panic if already claimed
+    let mut claimable_balance = read_balance_hot(env, balance_id);
+
+    // Vesting balances are released gradually through `claim_vested`
+    if let Vesting::Linear(_) = claimable_balance.vesting {
+        panic!("balance is vesting, use claim_vested");
+    }
+
+    // Check time bound, claimant membership, hashlock and gate
+    check_claim(env, balance_id, &claimable_balance, &claimant, preimage.as_ref());
+    multisig::check_approvals(env, balance_id, &claimable_balance, &destination);
+    escrow::check_buyer_confirmed(env, balance_id, &claimable_balance);
+    escrow::check_not_disputed(&claimable_balance);
+
+    // Tranched and recurring balances release every matured step that has not been paid yet
+    let payout = if let Vesting::Tranches(_) | Vesting::Recurring(_) = &claimable_balance.vesting {
+        let (payout, tranches_paid) = matured_steps(env, &claimable_balance, claimable_balance.tranches_paid);
+        if payout == 0 {
+            panic!("no tranche has matured since the last claim");
+        }
+        start_cooldown(env, &mut claimable_balance);
+        claimable_balance.tranches_paid = tranches_paid;
+        payout
+    } else if let Vesting::Milestones(mut schedule) = claimable_balance.vesting.clone() {
+        // Milestone balances release every approved milestone that has not been paid yet
+        let payout = milestones::release_approved(env, &mut schedule);
+        if payout == 0 {
+            panic!("no milestone has been approved since the last claim");
+        }
+        start_cooldown(env, &mut claimable_balance);
+        claimable_balance.vesting = Vesting::Milestones(schedule);
+        payout
+    } else {
+        // Allocated and equally split balances pay each claimant its own share exactly once
+        match claimable_balance.allocations.get(claimant.clone()) {
+            Some(share) => {
+                mark_claimed(env, balance_id, &claimant);
+                share
+            }
+            None => match &claimable_balance.distribution {
+                DistributionMode::FirstComeFirstServed => claimable_balance.amount - claimable_balance.claimed,
+                DistributionMode::EqualSplit => {
+                    mark_claimed(env, balance_id, &claimant);
+                    let claimable_balance = with_metadata(env, balance_id, claimable_balance.clone());
+                    math::checked(env, timelock_core::equal_share(&claimable_balance))
+                }
+                DistributionMode::Weighted(weights) => {
+                    mark_claimed(env, balance_id, &claimant);
+                    math::checked(env, timelock_core::weighted_share(claimable_balance.amount, weights, &claimant))
+                }
+                DistributionMode::Lottery(_) => {
+                    lottery::check_winner(env, balance_id, &claimant);
+                    claimable_balance.amount
+                }
+            },
+        }
+    };
+
+    // Let the compliance hook judge the amount actually paid
+    check_claim_hook(env, &claimant, &claimable_balance.token, payout);
+
+    // Remove the claimable balance entry once everything has been paid out
+    claimable_balance.claimed += payout;
+    tvl::adjust_total_locked(env, &claimable_balance.token, -payout);
+    history::record_claim(env, balance_id, &claimant, payout);
+    if claimable_balance.claimed == claimable_balance.amount {
+        close_balance(env, balance_id, &claimable_balance, BalanceStatus::Claimed);
+    } else {
+        write_balance(env, balance_id, &claimable_balance);
+    }
+
+    // Transfer the payout to the destination, or hold it if the destination cannot receive it yet
+    deliver_claim(env, &claimable_balance.token, &claimant, &destination, payout);
+    basket::release_basket(env, &claimable_balance, &claimant, &destination);
+
+    // Publish the claim with the paid amount, the claim time and where the tokens went
+    events::publish_claim(env, balance_id, claimant, claimable_balance.token, payout, destination);
+
+    payout
+}
+
+/// Helper function flagging a claimant of a balance as paid; panics if it already claimed its share.
+fn mark_claimed(env: &Env, balance_id: u64, claimant: &Address) {
+    let claimed_key = DataKey::Claimed(balance_id, claimant.clone());
+    if env.storage().persistent().has(&claimed_key) {
+        panic!("claimant has already claimed its allocation");
+    }
+    env.storage().persistent().set(&claimed_key, &());
+}
+
+/// Helper function paying the newly vested portion of a balance to an authorized claimant.
+fn pay_vested_claim(env: &Env, balance_id: u64, claimant: Address) -> i128 {
+    check_claims_not_paused(env);
+
+    // Retrieve the stored claimable balance; panic if fully claimed
+    let mut claimable_balance = read_balance_hot(env, balance_id);
+    let Vesting::Linear(vesting) = claimable_balance.vesting.clone() else {
+        panic!("balance has no vesting schedule");
+    };
+
+    // Check time bound, claimant membership, hashlock and gate
+    check_claim(env, balance_id, &claimable_balance, &claimant, None);
+
+    // Only pay what vested since the previous claim
+    let vested = vested_amount(env, &vesting, claimable_balance.amount);
+    let mut payout = vested - claimable_balance.claimed;
+    if payout <= 0 {
+        panic!("nothing has vested since the last claim");
+    }
+    start_cooldown(env, &mut claimable_balance);
+
+    // Pay no more than the rolling withdrawal cap allows; the rest stays claimable
+    let headroom =
+        withdrawal_headroom(env, balance_id, &claimable_balance.withdrawal_cap, &claimant);
+    if headroom <= 0 {
+        panic!("withdrawal cap reached for the current window");
+    }
+    payout = payout.min(headroom);
+    check_claim_hook(env, &claimant, &claimable_balance.token, payout);
+    record_withdrawal(env, balance_id, &claimable_balance.withdrawal_cap, &claimant, payout);
+
+    // Track progress, and remove the balance once everything has been paid out
+    claimable_balance.claimed += payout;
+    tvl::adjust_total_locked(env, &claimable_balance.token, -payout);
+    history::record_claim(env, balance_id, &claimant, payout);
+    if claimable_balance.claimed == claimable_balance.amount {
+        close_balance(env, balance_id, &claimable_balance, BalanceStatus::Claimed);
+    } else {
+        write_balance(env, balance_id, &claimable_balance);
+    }
+
+    deliver_claim(env, &claimable_balance.token, &claimant, &claimant, payout);
+
+    events::publish_claim(env, balance_id, claimant.clone(), claimable_balance.token, payout, claimant);
+
+    payout
+}
+
+/// Helper function panicking while the claim cooldown of the balance runs, then restarting it for the current claim.
+fn start_cooldown(env: &Env, claimable_balance: &mut ClaimableBalance) {
+    let now = current_time(env);
+    if now < cooldown_end(claimable_balance) {
+        panic!("claim cooldown has not elapsed");
+    }
+    claimable_balance.last_claim_at = now;
+}
+
+/// Helper function returning the UNIX timestamp from which the next partial claim of the balance is allowed.
+fn cooldown_end(claimable_balance: &ClaimableBalance) -> u64 {
+    if claimable_balance.last_claim_at == 0 {
+        return 0;
+    }
+    claimable_balance.last_claim_at.saturating_add(claimable_balance.claim_cooldown)
+}
+
+/// Helper function returning the ledger sequence from which the next paid TTL bump of a balance is allowed.
+fn next_rent_bump_sequence(env: &Env, balance_id: u64) -> u32 {
+    let last_bump: Option<u32> = env
+        .storage()
+        .persistent()
+        .get(&DataKey::LastRentBump(balance_id));
+    match last_bump {
+        Some(last_bump) => last_bump + RENT_BUMP_INTERVAL,
+        None => 0,
+    }
+}
+
+/// Helper function extending the TTL of a balance and the contract instance, paying the keeper from the balance's rent budget.
+fn bump_rent_for(env: &Env, keeper: Address, balance_id: u64) {
+    // Only bump while there is a balance to keep alive
+    let balance_key = DataKey::Balance(balance_id);
+    if !env.storage().persistent().has(&balance_key) {
+        panic!("no balance to keep alive");
+    }
+
+    // Rate-limit paid bumps so keepers cannot drain the budget
+    let sequence = env.ledger().sequence();
+    if sequence < next_rent_bump_sequence(env, balance_id) {
+        panic!("rent bump is not due yet");
+    }
+
+    let fee = read_config(env).rent_bump_fee;
+    let rent_budget = read_rent_budget(env, balance_id);
+    if rent_budget < fee {
+        panic!("rent budget is exhausted");
+    }
+
+    env.storage()
+        .persistent()
+        .set(&DataKey::LastRentBump(balance_id), &sequence);
+    env.storage()
+        .persistent()
+        .set(&DataKey::RentBudget(balance_id), &(rent_budget - fee));
+    tvl::adjust_reserved(env, &read_native_token(env), -fee);
+
+    extend_balance_ttl(env, balance_id, &read_balance(env, balance_id), RENT_BUMP_EXTEND_TO);
+
+    // Pay the keeper for the bump
+    if fee > 0 {
+        token::Client::new(env, &read_native_token(env)).transfer(
+            &env.current_contract_address(),
+            &keeper,
+            &fee,
+        );
+    }
+
+    env.events().publish(
+        (symbol_short!("rent_bump"), balance_id, keeper),
+        (fee, sequence),
+    );
+}
+
+/// Helper function keeping the contract instance, a balance, its rent bookkeeping and the indexes listing it alive
+/// for `extend_to` ledgers; entries that were never written are skipped.
+fn extend_balance_ttl(env: &Env, balance_id: u64, claimable_balance: &ClaimableBalance, extend_to: u32) {
+    env.storage().instance().extend_ttl(extend_to, extend_to);
+
+    let mut keys = Vec::from_array(
+        env,
+        [
+            DataKey::Balance(balance_id),
+            DataKey::Status(balance_id),
+            DataKey::RentBudget(balance_id),
+            DataKey::LastRentBump(balance_id),
+            DataKey::ByDepositor(claimable_balance.depositor.clone()),
+        ],
+    );
+    // Claims work on the record alone and leave the metadata and claimant entries to full writes and rent bumps
+    if has_metadata(claimable_balance) {
+        keys.push_back(DataKey::BalanceMetadata(balance_id));
+    }
+    for claimant in claimable_balance.claimants.iter() {
+        keys.push_back(DataKey::Claimant(balance_id, claimant.clone()));
+        keys.push_back(DataKey::ByClaimant(claimant));
+    }
+    for key in keys.iter() {
+        if env.storage().persistent().has(&key) {
+            env.storage().persistent().extend_ttl(&key, extend_to, extend_to);
+        }
+    }
+}
+
+/// Helper function returning the rolling window of a withdrawal cap and the key its withdrawals are tracked under.
+fn withdrawals_key(balance_id: u64, cap: &WithdrawalCap, claimant: &Address) -> Option<(RollingWindow, DataKey)> {
+    match cap {
+        WithdrawalCap::None => None,
+        WithdrawalCap::Rolling(window) => {
+            Some((window.clone(), DataKey::Withdrawals(balance_id, claimant.clone())))
+        }
+        WithdrawalCap::PerBalance(window) => {
+            Some((window.clone(), DataKey::BalanceWithdrawals(balance_id)))
+        }
+    }
+}
+
+/// Helper function reading the tracked withdrawals that still fall within the rolling window.
+fn recent_withdrawals(env: &Env, window: &RollingWindow, key: &DataKey) -> Vec<(u64, i128)> {
+    let now = current_time(env);
+    let withdrawals: Vec<(u64, i128)> = env
+        .storage()
+        .persistent()
+        .get(key)
+        .unwrap_or(Vec::new(env));
+
+    let mut recent = Vec::new(env);
+    for (timestamp, amount) in withdrawals.iter() {
+        if now < timestamp + window.window {
+            recent.push_back((timestamp, amount));
+        }
+    }
+    recent
+}
+
+/// Helper function returning how much a claimant may still withdraw from a balance in the current window.
+fn withdrawal_headroom(
+    env: &Env,
+    balance_id: u64,
+    cap: &WithdrawalCap,
+    claimant: &Address,
+) -> i128 {
+    let Some((window, key)) = withdrawals_key(balance_id, cap, claimant) else {
+        return i128::MAX;
+    };
+
+    let withdrawn: i128 = recent_withdrawals(env, &window, &key)
+        .iter()
+        .map(|(_, amount)| amount)
+        .sum();
+    window.max_amount - withdrawn
+}
+
+/// Helper function appending a withdrawal to the claimant's ring buffer of recent withdrawals.
+fn record_withdrawal(
+    env: &Env,
+    balance_id: u64,
+    cap: &WithdrawalCap,
+    claimant: &Address,
+    amount: i128,
+) {
+    let Some((window, key)) = withdrawals_key(balance_id, cap, claimant) else {
+        return;
+    };
+
+    // A full buffer of in-window withdrawals cannot evict anything without undercounting
+    let mut recent = recent_withdrawals(env, &window, &key);
+    if recent.len() >= WITHDRAWAL_RING_SIZE {
+        panic!("too many withdrawals in the current window");
+    }
+
+    recent.push_back((current_time(env), amount));
+    env.storage().persistent().set(&key, &recent);
+}
+
+/// Helper function transferring a claimed amount to the claimant. If the claimant cannot receive the token
+/// (e.g. a classic account without trustline), the amount is held as a pending delivery instead.
+fn deliver(env: &Env, token: &Address, claimant: &Address, amount: i128) {
+    let transfer = token::Client::new(env, token).try_transfer(
+        &env.current_contract_address(),
+        claimant,
+        &amount,
+    );
+    if transfer.is_ok() {
+        return;
+    }
+
+    let pending_key = DataKey::PendingDelivery(claimant.clone(), token.clone());
+    let pending: i128 = env.storage().persistent().get(&pending_key).unwrap_or(0);
+    env.storage().persistent().set(&pending_key, &(pending + amount));
+    tvl::adjust_reserved(env, token, amount);
+
+    env.events().publish(
+        (symbol_short!("pending"), claimant.clone(), token.clone()),
+        amount,
+    );
+}
+
+/// Helper function delivering a claim to `destination` after taking the protocol fee, unless the token or the
+/// claimant is exempt.
+fn deliver_claim(env: &Env, token: &Address, claimant: &Address, destination: &Address, amount: i128) {
+    let mut fee = 0;
+    if let Some((fee_collector, claim_fee)) = protocol_fee(env, token, claimant, amount) {
+        fee = claim_fee;
+        if fee > 0 {
+            token::Client::new(env, token).transfer(
+                &env.current_contract_address(),
+                &fee_collector,
+                &fee,
+            );
+            env.events()
+                .publish((symbol_short!("fee"), token.clone(), fee_collector), fee);
+        }
+    }
+
+    deliver(env, token, destination, amount - fee);
+}
+
+/// Helper function computing the protocol fee on a claim and who collects it; `None` without a fee collector or
+/// for exempt tokens and claimants.
+fn protocol_fee(env: &Env, token: &Address, claimant: &Address, amount: i128) -> Option<(Address, i128)> {
+    let config = read_config(env);
+    let fee_collector = config.fee_collector?;
+    if config.fee_exempt.contains(token) || config.fee_exempt.contains(claimant) {
+        return None;
+    }
+    Some((fee_collector, math::checked(env, math::bps_of(amount, config.fee_bps))))
+}
+
+/// Helper function returning the unclaimed remainder of a balance to its refund address.
+fn refund_balance(env: &Env, balance_id: u64, claimable_balance: &ClaimableBalance) -> i128 {
+    let remaining = return_balance(env, balance_id, claimable_balance, &claimable_balance.refund_to);
+
+    events::publish_cancel(env, balance_id, claimable_balance, remaining);
+
+    remaining
+}
+
+/// Helper function closing a balance that was not (fully) claimed and sending what is left of it to the recipient.
+fn return_balance(env: &Env, balance_id: u64, claimable_balance: &ClaimableBalance, recipient: &Address) -> i128 {
+    escrow::check_not_disputed(claimable_balance);
+    milestones::check_no_pending_rejection(claimable_balance);
+    freeze::check_not_frozen(claimable_balance);
+
+    let remaining = claimable_balance.amount - claimable_balance.claimed;
+
+    // Balances returned before they expired were cancelled
+    let status = if is_expired(env, &claimable_balance.time_bound) {
+        BalanceStatus::Expired
+    } else {
+        BalanceStatus::Cancelled
+    };
+    close_balance(env, balance_id, claimable_balance, status);
+
+    // Return the remainder only once the balance is gone
+    basket::refund_basket(env, claimable_balance, recipient);
+    token::Client::new(env, &claimable_balance.token).transfer(
+        &env.current_contract_address(),
+        recipient,
+        &remaining,
+    );
+
+    remaining
+}
+
+/// Helper function refunding an expired balance to its depositor and paying its bounty to the keeper.
+fn refund_expired_balance(env: &Env, keeper: Address, balance_id: u64) -> i128 {
+    let mut claimable_balance = read_balance(env, balance_id);
+    if !is_expired(env, &claimable_balance.time_bound) {
+        panic!("balance has not expired");
+    }
+
+    // Funds can only go back to the depositor, so anyone may trigger the refund
+    let bounty = core::mem::take(&mut claimable_balance.bounty);
+    let refunded = refund_balance(env, balance_id, &claimable_balance);
+    pay_bounty(env, balance_id, &claimable_balance.token, keeper, bounty);
+    refunded
+}
+
+/// Helper function pushing the remaining per-claimant shares of a balance and paying its bounty to the keeper.
+fn distribute_balance(env: &Env, keeper: Address, balance_id: u64) -> i128 {
+    let mut claimable_balance = read_balance(env, balance_id);
+
+    // Only balances with per-claimant shares can be pushed without picking a winner
+    if !has_claimant_shares(&claimable_balance) {
+        panic!("balance has no per-claimant shares to distribute");
+    }
+    if !check_time_bound(env, &claimable_balance) {
+        panic!("time predicate is not fulfilled");
+    }
+
+    // Reward the keeper before the balance gets closed by the last payout
+    let bounty = core::mem::take(&mut claimable_balance.bounty);
+    write_balance(env, balance_id, &claimable_balance);
+    pay_bounty(env, balance_id, &claimable_balance.token, keeper, bounty);
+
+    // Pay every claimant that did not claim its share itself, or only the winner of a lottery
+    let recipients = match claimable_balance.distribution {
+        DistributionMode::Lottery(_) => {
+            let winner = lottery::read_winner(env, balance_id).expect("lottery has not been drawn");
+            Vec::from_array(env, [winner])
+        }
+        _ => claimable_balance.claimants.clone(),
+    };
+    let mut distributed = 0;
+    for claimant in recipients.iter() {
+        let claimed_key = DataKey::Claimed(balance_id, claimant.clone());
+        if env.storage().persistent().has(&claimed_key) {
+            continue;
+        }
+        distributed += pay_full_claim(env, balance_id, claimant.clone(), claimant, None);
+    }
+
+    distributed
+}
+
+/// Helper function checking whether a balance pays each claimant a share of its own rather than the first to claim.
+fn has_claimant_shares(claimable_balance: &ClaimableBalance) -> bool {
+    !claimable_balance.allocations.is_empty()
+        || !matches!(claimable_balance.distribution, DistributionMode::FirstComeFirstServed)
+}
+
+/// Helper function paying a balance's bounty to the keeper that pushed its payout or refund.
+fn pay_bounty(env: &Env, balance_id: u64, token: &Address, keeper: Address, bounty: i128) {
+    if bounty == 0 {
+        return;
+    }
+
+    tvl::adjust_reserved(env, token, -bounty);
+    token::Client::new(env, token).transfer(&env.current_contract_address(), &keeper, &bounty);
+
+    env.events()
+        .publish((symbol_short!("bounty"), balance_id, keeper), bounty);
+}
+
+/// Helper function reading a stored balance; panics if it is not a pending gift.
+fn read_gift(env: &Env, balance_id: u64) -> ClaimableBalance {
+    let claimable_balance = load_balance(env, balance_id).expect("no pending gift");
+    if claimable_balance.gift_message.is_none() {
+        panic!("balance is not a gift");
+    }
+    claimable_balance
+}
+
+/// Helper function checking whether a time bound can no longer be satisfied in the future.
+fn is_expired(env: &Env, time_bound: &TimeBound) -> bool {
+    timelock_core::time_bound_expired(time_bound, current_time(env))
+}
+
+/// Helper function enforcing the time bound, claimant membership, hashlock and gate of a claim. The compliance hook
+/// is consulted once the payout is known.
+fn check_claim(
+    env: &Env,
+    balance_id: u64,
+    claimable_balance: &ClaimableBalance,
+    claimant: &Address,
+    preimage: Option<&Bytes>,
+) {
+    freeze::check_not_frozen(claimable_balance);
+    strategy::check_not_deployed(env, balance_id);
+
+    // Check if current time satisfies the time condition, telling a refusing oracle apart
+    if !check_time_bound(env, claimable_balance) {
+        if is_held_by_oracle(env, claimable_balance) {
+            env.panic_with_error(Error::from(ClaimError::OracleConditionNotMet));
+        }
+        panic!("time predicate is not fulfilled");
+    }
+
+    // Check if the claimant is among the allowed addresses
+    if !index::is_claimant(env, balance_id, claimant) {
+        panic!("claimant is not allowed to claim this balance");
+    }
+    if !check_claimant_predicate(env, claimable_balance, claimant) {
+        panic!("claimant predicate is not fulfilled");
+    }
+
+    // Check the revealed secret against the hashlock, and publish it so the counterparty can use it
+    if let Hashlock::Sha256(hash) = &claimable_balance.hashlock {
+        let preimage = preimage.expect("balance is hashlocked, use claim_with_preimage");
+        if env.crypto().sha256(preimage).to_bytes() != *hash {
+            panic!("preimage does not match the hashlock");
+        }
+        env.events()
+            .publish((symbol_short!("preimage"), hash.clone()), preimage.clone());
+    }
+
+    check_gate(env, balance_id, claimable_balance, claimant);
+}
+
+/// Helper function telling whether the balance would be unlocked if every oracle price condition of its predicate held.
+fn is_held_by_oracle(env: &Env, claimable_balance: &ClaimableBalance) -> bool {
+    let now = current_time(env);
+    timelock_core::time_bound_holds(&claimable_balance.time_bound, now)
+        && timelock_core::evaluate_predicate(&claimable_balance.predicate, now, &|_| true)
+}
+
+/// Helper function telling whether a plain claim of the balance by the claimant would pay out something right now.
+fn is_ready_to_claim(env: &Env, balance_id: u64, claimable_balance: &ClaimableBalance, claimant: &Address) -> bool {
+    // Balances that need extra input or another party are left to their dedicated entrypoints
+    if !matches!(claimable_balance.hashlock, Hashlock::None)
+        || !matches!(claimable_balance.multisig, Multisig::None)
+        || claimable_balance.escrow
+        || claimable_balance.frozen
+        || strategy::is_deployed(env, balance_id)
+    {
+        return false;
+    }
+    if !index::is_claimant(env, balance_id, claimant)
+        || !check_time_bound(env, claimable_balance)
+        || !check_claimant_predicate(env, claimable_balance, claimant)
+        || !gate_allows(env, balance_id, claimable_balance, claimant)
+        || current_time(env) < cooldown_end(claimable_balance)
+    {
+        return false;
+    }
+
+    let (payout, within_cap) = due_payout(env, balance_id, claimable_balance, claimant);
+    payout > 0 && within_cap && claim_hook_denial(env, claimant, &claimable_balance.token, payout).is_none()
+}
+
+/// Helper function working out what a plain claim of the balance by the claimant would pay right now, mirroring
+/// `pay_full_claim` and `pay_vested_claim`. Vested payouts are clipped to the withdrawal cap, which full payouts
+/// must fit in; returns the payout and whether the cap allows it.
+fn due_payout(env: &Env, balance_id: u64, claimable_balance: &ClaimableBalance, claimant: &Address) -> (i128, bool) {
+    let already_claimed = env
+        .storage()
+        .persistent()
+        .has(&DataKey::Claimed(balance_id, claimant.clone()));
+    let payout = match &claimable_balance.vesting {
+        Vesting::Linear(vesting) => {
+            vested_amount(env, vesting, claimable_balance.amount) - claimable_balance.claimed
+        }
+        Vesting::Tranches(_) | Vesting::Recurring(_) => {
+            matured_steps(env, claimable_balance, claimable_balance.tranches_paid).0
+        }
+        Vesting::Milestones(schedule) => milestones::approved_amount(env, schedule, true),
+        Vesting::None => match claimable_balance.allocations.get(claimant.clone()) {
+            Some(_) if already_claimed => 0,
+            Some(share) => share,
+            None => match &claimable_balance.distribution {
+                DistributionMode::FirstComeFirstServed => claimable_balance.amount - claimable_balance.claimed,
+                DistributionMode::EqualSplit | DistributionMode::Weighted(_) if already_claimed => 0,
+                DistributionMode::EqualSplit => math::checked(env, timelock_core::equal_share(claimable_balance)),
+                DistributionMode::Weighted(weights) => {
+                    math::checked(env, timelock_core::weighted_share(claimable_balance.amount, weights, claimant))
+                }
+                DistributionMode::Lottery(_) if lottery::read_winner(env, balance_id).as_ref() == Some(claimant) => {
+                    claimable_balance.amount
+                }
+                DistributionMode::Lottery(_) => 0,
+            },
+        },
+    };
+
+    // Vested claims are clipped to the withdrawal cap, full claims must fit in it
+    let headroom = withdrawal_headroom(env, balance_id, &claimable_balance.withdrawal_cap, claimant);
+    match claimable_balance.vesting {
+        Vesting::Linear(_) => (payout.min(headroom.max(0)), headroom > 0),
+        Vesting::None | Vesting::Tranches(_) | Vesting::Recurring(_) | Vesting::Milestones(_) => {
+            (payout, payout <= headroom)
+        }
+    }
+}
+
+/// Helper function panicking if deposits have been paused; any pause blocks new deposits.
+fn check_deposits_not_paused(env: &Env) {
+    if env.storage().instance().has(&DataKey::Paused) {
+        panic!("deposits are paused");
+    }
+}
+
+/// Helper function panicking if claims have been paused along with deposits.
+fn check_claims_not_paused(env: &Env) {
+    if claims_paused(env) {
+        panic!("claims are paused");
+    }
+}
+
+/// Helper function telling whether claims have been paused along with deposits.
+fn claims_paused(env: &Env) -> bool {
+    let claims_paused: Option<bool> = env.storage().instance().get(&DataKey::Paused);
+    claims_paused == Some(true)
+}
+
+/// Helper function asking the configured compliance hook, if any, and surfacing its reason code, offset by
+/// `HOOK_DENIAL_BASE`, on denial.
+fn check_claim_hook(env: &Env, claimant: &Address, token: &Address, amount: i128) {
+    if let Some(reason) = claim_hook_denial(env, claimant, token, amount) {
+        env.panic_with_error(Error::from_contract_error(HOOK_DENIAL_BASE.saturating_add(reason.code)));
+    }
+}
+
+/// Helper function returning why the configured compliance hook, if any, denies the claim.
+fn claim_hook_denial(env: &Env, claimant: &Address, token: &Address, amount: i128) -> Option<DenialReason> {
+    let hook = read_config(env).claim_hook?;
+    ClaimHookClient::new(env, &hook).check_claim(claimant, token, &amount)
+}
+
+/// Helper function panicking if the gate of the balance, if any, refuses the claimant.
+fn check_gate(env: &Env, balance_id: u64, claimable_balance: &ClaimableBalance, claimant: &Address) {
+    if !gate_allows(env, balance_id, claimable_balance, claimant) {
+        panic!("claim refused by the gate");
+    }
+}
+
+/// Helper function asking the gate of the balance, if any, whether the claimant may claim it.
+fn gate_allows(env: &Env, balance_id: u64, claimable_balance: &ClaimableBalance, claimant: &Address) -> bool {
+    match &claimable_balance.gate {
+        Some(gate) => ClaimGateClient::new(env, gate).check(claimant, &balance_id),
+        None => true,
+    }
+}
+
+/// Helper function hashing an airdrop leaf as sha256(address xdr || amount big-endian).
+fn merkle_leaf(env: &Env, claimant: &Address, amount: i128) -> BytesN<32> {
+    let mut data = claimant.clone().to_xdr(env);
+    data.extend_from_array(&amount.to_be_bytes());
+    env.crypto().sha256(&data).to_bytes()
+}
+
+/// Helper function checking a Merkle proof, hashing each pair in sorted order.
+fn verify_merkle_proof(env: &Env, leaf: &BytesN<32>, proof: &Vec<BytesN<32>>, root: &BytesN<32>) -> bool {
+    let mut computed = leaf.clone();
+    for sibling in proof.iter() {
+        let (first, second) = if computed <= sibling {
+            (computed, sibling)
+        } else {
+            (sibling, computed)
+        };
+        let mut pair = Bytes::from(first);
+        pair.append(&Bytes::from(second));
+        computed = env.crypto().sha256(&pair).to_bytes();
+    }
+    computed == *root
+}
+
+/// Helper function computing when the next portion of a balance unlocks, or `None` once it expired.
+fn next_unlock_time(env: &Env, claimable_balance: &ClaimableBalance) -> Option<u64> {
+    if is_expired(env, &claimable_balance.time_bound) {
+        return None;
+    }
+
+    let time_bound_start = match claimable_balance.time_bound.kind {
+        TimeBoundKind::Before => claimable_balance.created_at,
+        TimeBoundKind::After | TimeBoundKind::Between(_) => claimable_balance.time_bound.timestamp,
+    };
+    let schedule_start = match &claimable_balance.vesting {
+        Vesting::None => 0,
+        Vesting::Linear(vesting) => vesting.cliff,
+        Vesting::Tranches(tranches) => tranches
+            .get(claimable_balance.tranches_paid)
+            .map_or(0, |tranche| tranche.timestamp),
+        Vesting::Recurring(schedule) => {
+            schedule.first_unlock + claimable_balance.tranches_paid as u64 * schedule.interval
+        }
+        Vesting::Milestones(schedule) if milestones::approved_amount(env, schedule, true) == 0 => u64::MAX,
+        Vesting::Milestones(_) => 0,
+    };
+    Some(time_bound_start.max(schedule_start).max(cooldown_end(claimable_balance)))
+}
+
+/// Helper function computing the total amount of a vesting balance released so far; panics if it does not vest.
+fn vested_total(env: &Env, claimable_balance: &ClaimableBalance) -> i128 {
+    match &claimable_balance.vesting {
+        Vesting::None => panic!("balance has no vesting schedule"),
+        Vesting::Linear(vesting) => vested_amount(env, vesting, claimable_balance.amount),
+        Vesting::Tranches(_) | Vesting::Recurring(_) => matured_steps(env, claimable_balance, 0).0,
+        Vesting::Milestones(schedule) => milestones::approved_amount(env, schedule, false),
+    }
+}
+
+/// Helper function summing the tranches or recurring installments matured at the current time that follow the
+/// `paid` leading ones. Returns the amount and the number of steps paid once it is paid out.
+fn matured_steps(env: &Env, claimable_balance: &ClaimableBalance, paid: u32) -> (i128, u32) {
+    let now = current_time(env);
+    let matured = match &claimable_balance.vesting {
+        Vesting::Tranches(tranches) => timelock_core::matured_tranches(tranches, paid, now),
+        Vesting::Recurring(schedule) => {
+            timelock_core::matured_installments(schedule, claimable_balance.amount, paid, now)
+        }
+        Vesting::None | Vesting::Linear(_) | Vesting::Milestones(_) => Ok((0, paid)),
+    };
+    math::checked(env, matured)
+}
+
+/// Helper function computing the amount vested at the current ledger time.
+fn vested_amount(env: &Env, vesting: &VestingSchedule, amount: i128) -> i128 {
+    math::checked(env, timelock_core::linear_vested(vesting, amount, current_time(env)))
+}
+
+/// Helper function removing a fully paid out balance and refunding any leftover rent budget.
+fn close_balance(env: &Env, balance_id: u64, claimable_balance: &ClaimableBalance, status: BalanceStatus) {
+    // Deployed funds have to be back before anything is paid out of the balance
+    strategy::check_not_deployed(env, balance_id);
+
+    // The claimants are needed to clean up after them, even when closing from a claim
+    let claimable_balance = &with_metadata(env, balance_id, claimable_balance.clone());
+    env.storage().persistent().remove(&DataKey::Balance(balance_id));
+    env.storage().persistent().remove(&DataKey::BalanceMetadata(balance_id));
+    env.storage().persistent().remove(&DataKey::LastRentBump(balance_id));
+    env.storage().persistent().remove(&DataKey::Approvals(balance_id));
+    env.storage().persistent().remove(&DataKey::BalanceWithdrawals(balance_id));
+    escrow::clear_confirmations(env, balance_id, claimable_balance);
+    rotation::clear_rotations(env, balance_id, claimable_balance);
+    env.storage().persistent().remove(&DataKey::PendingExtension(balance_id));
+    env.storage().persistent().remove(&DataKey::PendingFreezeAction(balance_id));
+    index::unindex_balance(env, balance_id, claimable_balance);
+    status::record_status(env, balance_id, status);
+
+    // Whatever was not paid out through claims leaves the contract with the closing payout or refund
+    tvl::adjust_total_locked(
+        env,
+        &claimable_balance.token,
+        claimable_balance.claimed - claimable_balance.amount,
+    );
+
+    // A bounty no keeper earned goes back to the depositor
+    if claimable_balance.bounty > 0 {
+        tvl::adjust_reserved(env, &claimable_balance.token, -claimable_balance.bounty);
+        token::Client::new(env, &claimable_balance.token).transfer(
+            &env.current_contract_address(),
+            &claimable_balance.depositor,
+            &claimable_balance.bounty,
+        );
+    }
+
+    // Return whatever is left of the rent budget to the depositor
+    let rent_budget = read_rent_budget(env, balance_id);
+    if rent_budget > 0 {
+        env.storage().persistent().remove(&DataKey::RentBudget(balance_id));
+        let native_token = read_native_token(env);
+        tvl::adjust_reserved(env, &native_token, -rent_budget);
+        token::Client::new(env, &native_token).transfer(
+            &env.current_contract_address(),
+            &claimable_balance.depositor,
+            &rent_budget,
+        );
+    }
+}
+
+/// Helper function to read a stored balance; panics if it does not exist or has been settled.
+fn read_balance(env: &Env, balance_id: u64) -> ClaimableBalance {
+    load_balance(env, balance_id).expect("balance does not exist")
+}
+
+/// Helper function to read a balance together with its metadata, or `None` if it does not exist.
+fn load_balance(env: &Env, balance_id: u64) -> Option<ClaimableBalance> {
+    let claimable_balance = env.storage().persistent().get(&DataKey::Balance(balance_id))?;
+    Some(with_metadata(env, balance_id, claimable_balance))
+}
+
+/// Helper function to read only the claim-relevant record of a balance; claimants, gift message and memo are left
+/// empty. Panics if the balance does not exist.
+fn read_balance_hot(env: &Env, balance_id: u64) -> ClaimableBalance {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Balance(balance_id))
+        .expect("balance does not exist")
+}
+
+/// Helper function filling in the metadata of a balance read on its own. Records stored before the metadata was
+/// split off still carry it and are returned unchanged.
+fn with_metadata(env: &Env, balance_id: u64, mut claimable_balance: ClaimableBalance) -> ClaimableBalance {
+    let metadata: Option<BalanceMetadata> = env
+        .storage()
+        .persistent()
+        .get(&DataKey::BalanceMetadata(balance_id));
+    if let Some(metadata) = metadata {
+        claimable_balance.claimants = metadata.claimants;
+        claimable_balance.gift_message = metadata.gift_message;
+        claimable_balance.memo = metadata.memo;
+    }
+    claimable_balance
+}
+
+/// Helper function telling whether a balance was read with its metadata, rather than through `read_balance_hot`.
+fn has_metadata(claimable_balance: &ClaimableBalance) -> bool {
+    !claimable_balance.claimants.is_empty()
+        || claimable_balance.gift_message.is_some()
+        || claimable_balance.memo.is_some()
+}
+
+/// Helper function to store a balance under its id, extending its TTL on every deposit and claim.
+fn write_balance(env: &Env, balance_id: u64, claimable_balance: &ClaimableBalance) {
+    // Balances read with their metadata store it apart; claims leave it untouched
+    if has_metadata(claimable_balance) {
+        let metadata = BalanceMetadata {
+            claimants: claimable_balance.claimants.clone(),
+            gift_message: claimable_balance.gift_message.clone(),
+            memo: claimable_balance.memo.clone(),
+        };
+        env.storage()
+            .persistent()
+            .set(&DataKey::BalanceMetadata(balance_id), &metadata);
+    }
+    let record = ClaimableBalance {
+        claimants: Vec::new(env),
+        gift_message: None,
+        memo: None,
+        ..claimable_balance.clone()
+    };
+    env.storage()
+        .persistent()
+        .set(&DataKey::Balance(balance_id), &record);
+    status::record_open_status(env, balance_id, claimable_balance);
+    extend_balance_ttl(env, balance_id, claimable_balance, RENT_BUMP_EXTEND_TO);
+}
+
+/// Helper function to read the id the next deposit will be stored under.
+fn read_next_balance_id(env: &Env) -> u64 {
+    env.storage()
+        .instance()
+        .get(&DataKey::NextBalanceId)
+        .unwrap_or(0)
+}
+
+/// Helper function assigning the next sequential balance id.
+fn take_next_balance_id(env: &Env) -> u64 {
+    let balance_id = read_next_balance_id(env);
+    env.storage()
+        .instance()
+        .set(&DataKey::NextBalanceId, &(balance_id + 1));
+    balance_id
+}
+
+/// Helper function to read the admin address; panics if the contract is not configured.
+fn read_admin(env: &Env) -> Address {
+    env.storage()
+        .instance()
+        .get(&DataKey::Admin)
+        .expect("contract is not configured")
+}
+
+/// Helper function to read the rent budget escrowed for a balance, defaulting to zero.
+fn read_rent_budget(env: &Env, balance_id: u64) -> i128 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::RentBudget(balance_id))
+        .unwrap_or(0)
+}
+
+/// Helper function to read the configured native asset contract; panics if it is not set.
+fn read_native_token(env: &Env) -> Address {
+    read_config(env)
+        .native_token
+        .expect("native token is not configured")
+}
+
+/// Helper function to read the configuration; panics if the contract is not configured.
+fn read_config(env: &Env) -> Config {
+    env.storage()
+        .instance()
+        .get(&DataKey::Config)
+        .expect("contract is not configured")
+}
+
+// Governance call scheduler built on the same contract.
+mod scheduler;
+pub use scheduler::{Call, Operation, OperationState, OperationStatus};
+
+// Timelocked upgrades of the contract code.
+mod upgrade;
+pub use upgrade::PendingUpgrade;
+
+// Storage migrations run after upgrades.
+mod migration;
+
+// Recovery of tokens sent to the contract outside of deposits.
+mod rescue;
+
+// Releases gated by milestone approvals.
+mod milestones;
+pub use milestones::{Milestone, MilestoneSchedule};
+
+// Dry runs of claims for front-ends.
+mod preview;
+pub use preview::{ClaimBlocker, ClaimPreview};
+
+// Deposits of the native asset without passing its contract address.
+mod native;
+
+// Admin-managed list of the tokens accepted by deposits.
+mod allowlist;
+
+// Per-token minimum deposit amounts against dust balances.
+mod min_deposit;
+
+// Sweeps of long-expired balances to the protocol treasury.
+mod sweep;
+
+// Random selection of the claimant receiving a lottery balance.
+mod lottery;
+
+// Token holdings required of claimants by predicates, e.g. membership NFTs.
+mod holding;
+pub use holding::HoldingCondition;
+
+// Claims paid out in another asset through an AMM router.
+mod swap;
+pub use swap::{SwapRouter, SwapRouterClient};
+
+// Admin deployment of idle locked funds to whitelisted yield strategies.
+mod strategy;
+pub use strategy::{Deployment, Strategy, StrategyClient, YieldRecipient};
+
+// Claims consented to by contract claimants that cannot authorize calls.
+mod contract_claimant;
+pub use contract_claimant::{ClaimantContract, ClaimantContractClient};
+
+// Sablier-style continuous payment streams.
+mod stream;
+pub use stream::Stream;
+
+// k-of-n claimant approvals for releasing a balance.
+mod multisig;
+pub use multisig::ClaimApproval;
+
+// Two-party escrow released by mutual confirmation.
+mod escrow;
+
+// Per-account indexes of the open balances.
+mod index;
+
+// Per-token total value locked statistics.
+mod tvl;
+
+// On-chain history of the payouts of each balance.
+mod history;
+pub use history::ClaimRecord;
+
+// Deposit validation with dedicated error codes.
+mod validation;
+pub use validation::DepositError;
+
+// Balances holding several tokens at once.
+mod basket;
+
+// Claims signed off-chain and submitted by relayers.
+mod signed_claim;
+pub use signed_claim::SignedClaim;
+
+// Replay protection shared by signed and relayed payloads.
+mod nonce;
+
+// Oracle price conditions for predicates.
+mod oracle;
+pub use oracle::{Comparator, OracleAsset, PriceCondition, PriceData, PriceOracle, PriceOracleClient};
+
+// Depositor-approved replacement of claimant addresses.
+mod rotation;
+
+// Claimant list edits by the depositor or admin before unlock.
+mod claimants;
+
+// Balance ids derived from the depositor and a salt.
+mod salted;
+
+// Claims before the unlock time against a penalty.
+mod early_claim;
+pub use early_claim::{EarlyClaim, EarlyClaimTerms};
+
+// Lock extensions chosen by a claimant or agreed between depositor and claimants.
+mod relock;
+pub use relock::LockExtension;
+
+// Admin freezes of individual balances.
+mod freeze;
+pub use freeze::{FreezeAction, PendingFreezeAction};
+
+// Lifecycle state of each balance.
+mod status;
+pub use status::BalanceStatus;
+
+// Role-based access control for the call scheduler.
+mod rbac;
+pub use rbac::Role;
+
+// Keeper-facing interface over the contract's time-driven maintenance tasks.
+mod schedulable;
+pub use schedulable::{Schedulable, SchedulableClient, Task};
+
+// Versioned schema of the deposit, claim, refund and schedule events.
+mod events;
+pub use events::{
+    CancelEvent, ClaimEvent, DepositEvent, EarlyClaimEvent, ResolveEvent, RevokeEvent, ScheduleEvent, StreamCancelEvent,
+    StreamWithdrawEvent, SwapEvent, SweepEvent, TopUpEvent, EVENT_VERSION,
+};
+
+// Checked arithmetic with typed errors.
+pub mod math;
+pub use math::MathError;
+
+// Storage-free timelock semantics reusable by other contracts.
+pub mod timelock_core;
+
+// Published interface for cross-contract integration.
+pub mod interface;
+
+// Fixtures for integration tests of this and dependent contracts.
+#[cfg(any(test, feature = "testutils"))]
+pub mod testutils;
 
 // Test module is defined in a separate file.
 mod test;
@@ -0,0 +1,92 @@
+use soroban_sdk::{contractimpl, symbol_short, Address, BytesN, Env};
+
+use crate::{
+    check_claims_not_paused, check_time_bound, freeze, read_balance, Airdrop, ClaimableBalance,
+    ClaimableBalanceContract, ClaimableBalanceContractArgs, ClaimableBalanceContractClient, DataKey,
+    DistributionMode, Vesting,
+};
+
+#[contractimpl]
+impl ClaimableBalanceContract {
+    /// Draws the winner of a lottery balance among its claimants, once the time bound is satisfied, by revealing
+    /// the seed whose SHA-256 the depositor committed at deposit; only the winner can then claim the whole amount.
+    /// Only callable by the depositor, once; returns the winner.
+    ///
+    /// The outcome is fixed by the committed seed, so nobody can retry the draw until they win, e.g. from a
+    /// contract reverting unless `lottery_winner` is its own address. The depositor knows the winner from the
+    /// start and can withhold the seed until the balance expires and refund itself, so lotteries suit raffles and
+    /// incentives run by their sponsor, not high stakes.
+    pub fn draw(env: Env, balance_id: u64, seed: BytesN<32>) -> Address {
+        check_claims_not_paused(&env);
+
+        let claimable_balance = read_balance(&env, balance_id);
+
+        // Require that the depositor authorizes the draw
+        claimable_balance.depositor.require_auth();
+
+        freeze::check_not_frozen(&claimable_balance);
+        let DistributionMode::Lottery(commitment) = &claimable_balance.distribution else {
+            panic!("balance is not a lottery");
+        };
+        if env.crypto().sha256(&seed.clone().into()).to_bytes() != *commitment {
+            panic!("seed does not match the commitment");
+        }
+        if !check_time_bound(&env, &claimable_balance) {
+            panic!("time predicate is not fulfilled");
+        }
+        if read_winner(&env, balance_id).is_some() {
+            panic!("lottery has already been drawn");
+        }
+
+        // Pick one claimant uniformly at random from the seed and keep the result for the claim
+        env.prng().seed(seed.into());
+        let index = env.prng().gen_range::<u64>(0..claimable_balance.claimants.len() as u64);
+        let winner = claimable_balance.claimants.get_unchecked(index as u32);
+        write_winner(&env, balance_id, &winner);
+
+        env.events()
+            .publish((symbol_short!("draw"), balance_id), winner.clone());
+
+        winner
+    }
+
+    /// Returns the drawn winner of a lottery balance, if the draw took place.
+    pub fn lottery_winner(env: Env, balance_id: u64) -> Option<Address> {
+        read_winner(&env, balance_id)
+    }
+}
+
+/// Helper function rejecting lotteries combined with features that pay more than one claimant or no listed one.
+pub(crate) fn validate_lottery(claimable_balance: &ClaimableBalance) {
+    if !matches!(claimable_balance.distribution, DistributionMode::Lottery(_)) {
+        return;
+    }
+    if !matches!(claimable_balance.vesting, Vesting::None)
+        || !claimable_balance.allocations.is_empty()
+        || !matches!(claimable_balance.airdrop, Airdrop::None)
+        || !claimable_balance.claim_windows.is_empty()
+    {
+        panic!("lotteries cannot vest, be allocated, be airdropped or have claim windows");
+    }
+}
+
+/// Helper function panicking unless the lottery of the balance was drawn and the claimant won it.
+pub(crate) fn check_winner(env: &Env, balance_id: u64, claimant: &Address) {
+    match read_winner(env, balance_id) {
+        Some(winner) if winner == *claimant => {}
+        Some(_) => panic!("claimant did not win the lottery"),
+        None => panic!("lottery has not been drawn"),
+    }
+}
+
+/// Helper function to read the drawn winner of a lottery balance.
+pub(crate) fn read_winner(env: &Env, balance_id: u64) -> Option<Address> {
+    env.storage().persistent().get(&DataKey::LotteryWinner(balance_id))
+}
+
+/// Helper function to record the winner of a lottery balance, e.g. after drawing it or rotating its address.
+pub(crate) fn write_winner(env: &Env, balance_id: u64, winner: &Address) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::LotteryWinner(balance_id), winner);
+}
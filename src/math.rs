@@ -0,0 +1,56 @@
+use soroban_sdk::{contracterror, Env, Error};
+
+/// Enum listing why an amount computation failed, surfaced as the contract error code of the failed call.
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum MathError {
+    Overflow = 100,        // The result does not fit in an i128
+    DivisionByZero = 101,  // The divisor is zero
+}
+
+/// Returns `a + b`, or an error instead of wrapping.
+pub fn add(a: i128, b: i128) -> Result<i128, MathError> {
+    a.checked_add(b).ok_or(MathError::Overflow)
+}
+
+/// Returns `a - b`, or an error instead of wrapping.
+pub fn sub(a: i128, b: i128) -> Result<i128, MathError> {
+    a.checked_sub(b).ok_or(MathError::Overflow)
+}
+
+/// Returns the timestamp `a + b`, or an error instead of wrapping.
+pub fn add_time(a: u64, b: u64) -> Result<u64, MathError> {
+    a.checked_add(b).ok_or(MathError::Overflow)
+}
+
+/// Returns `a * b`, or an error instead of wrapping.
+pub fn mul(a: i128, b: i128) -> Result<i128, MathError> {
+    a.checked_mul(b).ok_or(MathError::Overflow)
+}
+
+/// Returns `a / b` rounded towards zero, or an error on a zero divisor.
+pub fn div(a: i128, b: i128) -> Result<i128, MathError> {
+    if b == 0 {
+        return Err(MathError::DivisionByZero);
+    }
+    a.checked_div(b).ok_or(MathError::Overflow)
+}
+
+/// Returns `value * numerator / denominator` rounded towards zero, e.g. the vested part of an amount.
+pub fn mul_div(value: i128, numerator: i128, denominator: i128) -> Result<i128, MathError> {
+    div(mul(value, numerator)?, denominator)
+}
+
+/// Returns the share of `amount` given in basis points.
+pub fn bps_of(amount: i128, bps: u32) -> Result<i128, MathError> {
+    mul_div(amount, bps as i128, crate::BPS_DENOMINATOR)
+}
+
+/// Helper function unwrapping a computation, panicking with its error code if it failed.
+pub(crate) fn checked<T>(env: &Env, result: Result<T, MathError>) -> T {
+    match result {
+        Ok(value) => value,
+        Err(error) => env.panic_with_error(Error::from(error)),
+    }
+}
@@ -0,0 +1,83 @@
+use soroban_sdk::{contractimpl, contracttype, symbol_short, Address, Env, Symbol, Vec};
+
+use crate::{
+    read_admin, store_balance, take_next_balance_id, ClaimableBalance, ClaimableBalanceContract,
+    ClaimableBalanceContractArgs, ClaimableBalanceContractClient, DataKey, TimeBound, CONTRACT_VERSION,
+};
+
+/// Struct with the layout of the single balance stored by the original one-shot deployment.
+#[derive(Clone)]
+#[contracttype]
+struct LegacyBalance {
+    token: Address,           // Address of the token contract
+    amount: i128,             // Amount of tokens to claim
+    claimants: Vec<Address>,  // List of addresses allowed to claim
+    time_bound: TimeBound,    // Time-bound condition for claiming
+}
+
+#[contractimpl]
+impl ClaimableBalanceContract {
+    /// Rewrites the stored data into the layout of the installed code, once per upgrade that changes it.
+    /// Only callable by the admin; returns the storage version migrated to.
+    pub fn migrate(env: Env) -> u32 {
+        // Require that the admin authorizes the migration
+        let admin = read_admin(&env);
+        admin.require_auth();
+
+        let version = read_storage_version(&env);
+        if version >= CONTRACT_VERSION {
+            panic!("storage is already up to date");
+        }
+
+        // Version 0 kept a single balance in instance storage; it moves to the keyed persistent layout
+        if version < 1 {
+            migrate_legacy_balance(&env, admin);
+        }
+
+        env.storage()
+            .instance()
+            .set(&DataKey::StorageVersion, &CONTRACT_VERSION);
+
+        env.events()
+            .publish((symbol_short!("migrate"),), (version, CONTRACT_VERSION));
+
+        CONTRACT_VERSION
+    }
+
+    /// Returns the version of the layout the stored data is in; 0 for deployments that predate versioning.
+    pub fn storage_version(env: Env) -> u32 {
+        read_storage_version(&env)
+    }
+}
+
+/// Helper function to read the storage version, defaulting to 0 for deployments that predate versioning.
+fn read_storage_version(env: &Env) -> u32 {
+    env.storage()
+        .instance()
+        .get(&DataKey::StorageVersion)
+        .unwrap_or(0)
+}
+
+/// Helper function moving the single balance of the original deployment, if any is left, under a balance id.
+/// The original layout did not record a depositor, so refunds of the migrated balance go to the admin.
+fn migrate_legacy_balance(env: &Env, admin: Address) {
+    // The original `DataKey::Balance` and `DataKey::Init` unit variants are stored as one-symbol vectors
+    let balance_key = (Symbol::new(env, "Balance"),);
+    let init_key = (Symbol::new(env, "Init"),);
+
+    let legacy_balance: Option<LegacyBalance> = env.storage().instance().get(&balance_key);
+    env.storage().instance().remove(&balance_key);
+    env.storage().instance().remove(&init_key);
+
+    if let Some(legacy_balance) = legacy_balance {
+        let claimable_balance = ClaimableBalance::new(
+            env,
+            admin,
+            legacy_balance.token,
+            legacy_balance.amount,
+            legacy_balance.claimants,
+            legacy_balance.time_bound,
+        );
+        store_balance(env, take_next_balance_id(env), claimable_balance);
+    }
+}
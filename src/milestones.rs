@@ -0,0 +1,208 @@
+use soroban_sdk::{contractimpl, contracttype, symbol_short, token, Address, Env, Vec};
+
+use crate::{
+    math, read_balance, receive, tvl, write_balance, ClaimableBalance, ClaimableBalanceContract,
+    ClaimableBalanceContractArgs, ClaimableBalanceContractClient, Vesting, MAX_AMOUNT,
+};
+
+/// Struct describing one deliverable of a milestone schedule and its payment.
+#[derive(Clone)]
+#[contracttype]
+pub struct Milestone {
+    pub amount: i128,    // Amount released once the milestone is approved
+    pub approved: bool,  // Whether the depositor or the reviewer approved the milestone
+    pub rejected: bool,  // Whether a bonded rejection of the milestone awaits the arbiter
+    pub paid: bool,      // Whether the approved amount has been claimed
+}
+
+/// Struct describing a release in milestones, e.g. for grants or contract work: a milestone can only be claimed
+/// once the depositor, or the reviewer if one is set, approved it. With an arbiter, the approver can instead
+/// reject a milestone by posting a bond, which goes to the claimant if the arbiter overturns the rejection.
+#[derive(Clone)]
+#[contracttype]
+pub struct MilestoneSchedule {
+    pub milestones: Vec<Milestone>,  // Deliverables adding up to the amount, approvable in any order
+    pub reviewer: Option<Address>,   // Approver of the milestones in place of the depositor
+    pub arbiter: Option<Address>,    // Optional address ruling on rejected milestones
+    pub rejection_bond: i128,        // Amount of the balance's token the approver posts with each rejection
+}
+
+#[contractimpl]
+impl ClaimableBalanceContract {
+    /// Approves a milestone of a balance, letting its claimant claim the milestone's amount. Only callable by the
+    /// reviewer of the schedule, or the depositor if it has none. Returns the amount released.
+    pub fn approve_milestone(env: Env, balance_id: u64, index: u32) -> i128 {
+        let mut claimable_balance = read_balance(&env, balance_id);
+        let Vesting::Milestones(mut schedule) = claimable_balance.vesting.clone() else {
+            panic!("balance has no milestones");
+        };
+
+        // Require that the reviewer, or the depositor, authorizes the approval
+        let approver = schedule.reviewer.clone().unwrap_or(claimable_balance.depositor.clone());
+        approver.require_auth();
+
+        let mut milestone = schedule.milestones.get(index).expect("milestone does not exist");
+        if milestone.approved {
+            panic!("milestone is already approved");
+        }
+        if milestone.rejected {
+            panic!("milestone rejection awaits the arbiter");
+        }
+        milestone.approved = true;
+        schedule.milestones.set(index, milestone.clone());
+        claimable_balance.vesting = Vesting::Milestones(schedule);
+        write_balance(&env, balance_id, &claimable_balance);
+
+        env.events().publish(
+            (symbol_short!("milestone"), balance_id, approver),
+            (index, milestone.amount),
+        );
+
+        milestone.amount
+    }
+
+    /// Rejects a milestone of a balance whose schedule has an arbiter, posting the schedule's rejection bond.
+    /// Only callable by the reviewer of the schedule, or the depositor if it has none.
+    pub fn reject_milestone(env: Env, balance_id: u64, index: u32) {
+        let mut claimable_balance = read_balance(&env, balance_id);
+        let Vesting::Milestones(mut schedule) = claimable_balance.vesting.clone() else {
+            panic!("balance has no milestones");
+        };
+        if schedule.arbiter.is_none() {
+            panic!("milestones without an arbiter cannot be rejected");
+        }
+
+        // Require that the reviewer, or the depositor, authorizes the rejection
+        let approver = schedule.reviewer.clone().unwrap_or(claimable_balance.depositor.clone());
+        approver.require_auth();
+
+        let mut milestone = schedule.milestones.get(index).expect("milestone does not exist");
+        if milestone.approved || milestone.rejected {
+            panic!("milestone is already approved or rejected");
+        }
+
+        // Hold the bond until the arbiter rules
+        if receive(&env, &claimable_balance.token, &approver, schedule.rejection_bond) != schedule.rejection_bond {
+            panic!("token took a fee on transfer; bonds need the exact amount");
+        }
+        tvl::adjust_reserved(&env, &claimable_balance.token, schedule.rejection_bond);
+
+        milestone.rejected = true;
+        schedule.milestones.set(index, milestone);
+        let rejection_bond = schedule.rejection_bond;
+        claimable_balance.vesting = Vesting::Milestones(schedule);
+        write_balance(&env, balance_id, &claimable_balance);
+
+        env.events().publish(
+            (symbol_short!("ms_reject"), balance_id, approver),
+            (index, rejection_bond),
+        );
+    }
+
+    /// Rules on a rejected milestone. Overturning the rejection approves the milestone and pays the bond to the
+    /// claimant; upholding it returns the bond to the approver and leaves the milestone open for another delivery.
+    /// Only callable by the arbiter of the schedule.
+    pub fn resolve_milestone(env: Env, balance_id: u64, index: u32, overturn: bool) {
+        let mut claimable_balance = read_balance(&env, balance_id);
+        let Vesting::Milestones(mut schedule) = claimable_balance.vesting.clone() else {
+            panic!("balance has no milestones");
+        };
+        let arbiter = schedule.arbiter.clone().expect("milestones have no arbiter");
+
+        // Require that the arbiter authorizes the ruling
+        arbiter.require_auth();
+
+        let mut milestone = schedule.milestones.get(index).expect("milestone does not exist");
+        if !milestone.rejected {
+            panic!("milestone has not been rejected");
+        }
+        milestone.rejected = false;
+        milestone.approved = overturn;
+        schedule.milestones.set(index, milestone);
+
+        // The bond goes to the claimant if the rejection was wrong, and back to the approver otherwise
+        let bond_recipient = if overturn {
+            claimable_balance.claimants.get_unchecked(0)
+        } else {
+            schedule.reviewer.clone().unwrap_or(claimable_balance.depositor.clone())
+        };
+        let rejection_bond = schedule.rejection_bond;
+        claimable_balance.vesting = Vesting::Milestones(schedule);
+        write_balance(&env, balance_id, &claimable_balance);
+        tvl::adjust_reserved(&env, &claimable_balance.token, -rejection_bond);
+
+        if rejection_bond > 0 {
+            token::Client::new(&env, &claimable_balance.token).transfer(
+                &env.current_contract_address(),
+                &bond_recipient,
+                &rejection_bond,
+            );
+        }
+
+        env.events().publish(
+            (symbol_short!("ms_ruling"), balance_id, arbiter),
+            (index, overturn, bond_recipient),
+        );
+    }
+}
+
+/// Helper function rejecting milestone schedules that are empty, pre-approved or do not add up to the amount.
+pub(crate) fn validate_milestones(env: &Env, claimable_balance: &ClaimableBalance) {
+    let Vesting::Milestones(schedule) = &claimable_balance.vesting else {
+        return;
+    };
+    if schedule.milestones.is_empty() {
+        panic!("milestones must not be empty");
+    }
+    if schedule.arbiter.is_some() && (schedule.rejection_bond <= 0 || claimable_balance.claimants.len() != 1) {
+        panic!("arbitrated milestones need a positive rejection bond and a single claimant");
+    }
+    if schedule.rejection_bond < 0 || schedule.rejection_bond > MAX_AMOUNT {
+        panic!("rejection bond must be between 0 and MAX_AMOUNT");
+    }
+
+    let mut total = 0;
+    for milestone in schedule.milestones.iter() {
+        if milestone.amount <= 0 {
+            panic!("milestone amount must be positive");
+        }
+        if milestone.approved || milestone.rejected || milestone.paid {
+            panic!("milestones must start unapproved");
+        }
+        total = math::checked(env, math::add(total, milestone.amount));
+    }
+    if total != claimable_balance.amount {
+        panic!("milestones must add up to the amount");
+    }
+}
+
+/// Helper function panicking while a milestone rejection awaits the arbiter, whose bond must not be stranded.
+pub(crate) fn check_no_pending_rejection(claimable_balance: &ClaimableBalance) {
+    if let Vesting::Milestones(schedule) = &claimable_balance.vesting {
+        if schedule.milestones.iter().any(|milestone| milestone.rejected) {
+            panic!("milestone rejection awaits the arbiter");
+        }
+    }
+}
+
+/// Helper function summing the milestones that are approved, optionally only those not claimed yet.
+pub(crate) fn approved_amount(env: &Env, schedule: &MilestoneSchedule, unpaid_only: bool) -> i128 {
+    let approved = schedule
+        .milestones
+        .iter()
+        .filter(|milestone| milestone.approved && !(unpaid_only && milestone.paid))
+        .try_fold(0, |total, milestone| math::add(total, milestone.amount));
+    math::checked(env, approved)
+}
+
+/// Helper function marking every approved milestone as paid, returning the amount not claimed before.
+pub(crate) fn release_approved(env: &Env, schedule: &mut MilestoneSchedule) -> i128 {
+    let payout = approved_amount(env, schedule, true);
+    let mut milestones = Vec::new(env);
+    for mut milestone in schedule.milestones.iter() {
+        milestone.paid |= milestone.approved;
+        milestones.push_back(milestone);
+    }
+    schedule.milestones = milestones;
+    payout
+}
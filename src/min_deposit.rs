@@ -0,0 +1,48 @@
+use soroban_sdk::{contractimpl, symbol_short, Address, Env, Error};
+
+use crate::{
+    read_admin, ClaimableBalance, ClaimableBalanceContract, ClaimableBalanceContractArgs,
+    ClaimableBalanceContractClient, DataKey, DepositError, MAX_AMOUNT,
+};
+
+#[contractimpl]
+impl ClaimableBalanceContract {
+    /// Sets the smallest amount of a token a deposit can lock, so dust balances do not cost more rent than they are
+    /// worth; zero removes the minimum. Only callable by the admin.
+    pub fn set_min_deposit(env: Env, token: Address, min_amount: i128) {
+        // Require that the admin authorizes the change
+        read_admin(&env).require_auth();
+
+        if !(0..=MAX_AMOUNT).contains(&min_amount) {
+            panic!("minimum deposit must be between 0 and MAX_AMOUNT");
+        }
+        let key = DataKey::MinDeposit(token.clone());
+        if min_amount == 0 {
+            env.storage().instance().remove(&key);
+        } else {
+            env.storage().instance().set(&key, &min_amount);
+        }
+
+        env.events().publish((symbol_short!("min_dep"), token), min_amount);
+    }
+
+    /// Returns the smallest amount of the token a deposit can lock; zero when no minimum is set.
+    pub fn min_deposit(env: Env, token: Address) -> i128 {
+        read_min_deposit(&env, &token)
+    }
+}
+
+/// Helper function rejecting a deposit locking less than the minimum set for its token.
+pub(crate) fn check_min_deposit(env: &Env, claimable_balance: &ClaimableBalance) {
+    if claimable_balance.amount < read_min_deposit(env, &claimable_balance.token) {
+        env.panic_with_error(Error::from(DepositError::BelowMinDeposit));
+    }
+}
+
+/// Helper function to read the minimum deposit of a token, defaulting to zero.
+fn read_min_deposit(env: &Env, token: &Address) -> i128 {
+    env.storage()
+        .instance()
+        .get(&DataKey::MinDeposit(token.clone()))
+        .unwrap_or(0)
+}
@@ -0,0 +1,112 @@
+use soroban_sdk::{contractimpl, contracttype, symbol_short, Address, Env, Vec};
+
+use crate::{
+    index, pay_full_claim, read_balance, ClaimableBalance, ClaimableBalanceContract,
+    ClaimableBalanceContractArgs, ClaimableBalanceContractClient, DataKey, Multisig,
+};
+
+/// Struct collecting the claimant approvals gathered so far for releasing a balance to one destination.
+#[derive(Clone)]
+#[contracttype]
+pub struct ClaimApproval {
+    pub destination: Address,     // Address the approved claim pays out to
+    pub approvers: Vec<Address>,  // Claimants that approved releasing the balance to the destination
+}
+
+#[contractimpl]
+impl ClaimableBalanceContract {
+    /// Approves releasing a k-of-n balance to `destination`. Approving another destination than the pending
+    /// one discards the approvals gathered so far. Returns the number of approvals for the destination.
+    pub fn approve_claim(env: Env, claimant: Address, balance_id: u64, destination: Address) -> u32 {
+        // Require that claimant authorizes the approval
+        claimant.require_auth();
+
+        let claimable_balance = read_balance(&env, balance_id);
+        if let Multisig::None = claimable_balance.multisig {
+            panic!("balance does not require approvals");
+        }
+        if !index::is_claimant(&env, balance_id, &claimant) {
+            panic!("claimant is not allowed to claim this balance");
+        }
+
+        // Start over whenever the destination changes
+        let key = DataKey::Approvals(balance_id);
+        let mut approval = match env.storage().persistent().get::<_, ClaimApproval>(&key) {
+            Some(approval) if approval.destination == destination => approval,
+            _ => ClaimApproval {
+                destination: destination.clone(),
+                approvers: Vec::new(&env),
+            },
+        };
+        if approval.approvers.contains(&claimant) {
+            panic!("claimant has already approved");
+        }
+        approval.approvers.push_back(claimant.clone());
+        env.storage().persistent().set(&key, &approval);
+
+        // Publish the approval and the destination it is for
+        env.events().publish(
+            (symbol_short!("approve"), balance_id, claimant),
+            destination,
+        );
+
+        approval.approvers.len()
+    }
+
+    /// Releases a k-of-n balance to the destination approved by enough claimants; anyone can execute it.
+    pub fn execute_claim(env: Env, balance_id: u64, destination: Address) -> i128 {
+        let approval: ClaimApproval = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Approvals(balance_id))
+            .expect("claim has not been approved");
+
+        // The first approver stands for the claimants in the claim checks and event
+        let claimant = approval.approvers.get_unchecked(0);
+        pay_full_claim(&env, balance_id, claimant, destination, None)
+    }
+
+    /// Returns the approvals gathered so far for a k-of-n balance, if any.
+    pub fn claim_approval(env: Env, balance_id: u64) -> Option<ClaimApproval> {
+        env.storage().persistent().get(&DataKey::Approvals(balance_id))
+    }
+}
+
+/// Helper function telling whether enough claimants approved releasing a k-of-n balance to `destination`.
+pub(crate) fn is_approved(
+    env: &Env,
+    balance_id: u64,
+    claimable_balance: &ClaimableBalance,
+    destination: &Address,
+) -> bool {
+    let Multisig::Threshold(threshold) = claimable_balance.multisig else {
+        return true;
+    };
+
+    let approval: Option<ClaimApproval> =
+        env.storage().persistent().get(&DataKey::Approvals(balance_id));
+    approval.is_some_and(|approval| approval.destination == *destination && approval.approvers.len() >= threshold)
+}
+
+/// Helper function panicking unless enough claimants approved releasing a k-of-n balance to `destination`.
+pub(crate) fn check_approvals(
+    env: &Env,
+    balance_id: u64,
+    claimable_balance: &ClaimableBalance,
+    destination: &Address,
+) {
+    let Multisig::Threshold(threshold) = claimable_balance.multisig else {
+        return;
+    };
+
+    let approval: Option<ClaimApproval> =
+        env.storage().persistent().get(&DataKey::Approvals(balance_id));
+    match approval {
+        Some(approval) if approval.destination == *destination => {
+            if approval.approvers.len() < threshold {
+                panic!("not enough claimants approved the claim");
+            }
+        }
+        _ => panic!("claim has not been approved for this destination"),
+    }
+}
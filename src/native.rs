@@ -0,0 +1,42 @@
+use soroban_sdk::{contractimpl, Address, Env, Vec};
+
+use crate::{
+    read_config, read_native_token, ClaimableBalanceContract, ClaimableBalanceContractArgs,
+    ClaimableBalanceContractClient, DepositOptions, TimeBound,
+};
+
+#[contractimpl]
+impl ClaimableBalanceContract {
+    /// Deposits a balance of the native asset like `deposit`, resolving its Stellar Asset Contract from the
+    /// configuration. Returns the id of the new balance.
+    pub fn deposit_native(
+        env: Env,
+        from: Address,              // Address sending the native asset
+        amount: i128,               // Amount of the native asset to deposit, in stroops
+        claimants: Vec<Address>,    // Allowed claimants
+        time_bound: TimeBound,      // Time-bound constraint
+    ) -> u64 {
+        let native_token = read_native_token(&env);
+        Self::deposit(env, from, native_token, amount, claimants, time_bound)
+    }
+
+    /// Deposits a balance of the native asset like `deposit_with_options`, resolving its Stellar Asset Contract
+    /// from the configuration. Returns the id of the new balance.
+    pub fn deposit_native_with_options(
+        env: Env,
+        from: Address,              // Address sending the native asset
+        amount: i128,               // Amount of the native asset to deposit, in stroops
+        claimants: Vec<Address>,    // Allowed claimants
+        time_bound: TimeBound,      // Time-bound constraint
+        options: DepositOptions,    // Optional features such as vesting
+    ) -> u64 {
+        let native_token = read_native_token(&env);
+        Self::deposit_with_options(env, from, native_token, amount, claimants, time_bound, options)
+    }
+
+    /// Returns the configured Stellar Asset Contract of the native asset, if any.
+    pub fn native_token(env: Env) -> Option<Address> {
+        read_config(&env).native_token
+    }
+}
+
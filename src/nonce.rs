@@ -0,0 +1,35 @@
+use soroban_sdk::{contractimpl, symbol_short, Address, Env};
+
+use crate::{ClaimableBalanceContract, ClaimableBalanceContractArgs, ClaimableBalanceContractClient, DataKey};
+
+#[contractimpl]
+impl ClaimableBalanceContract {
+    /// Uses up a nonce of the authorizing address, e.g. to cancel a signed payload handed to a relayer before it is
+    /// submitted.
+    pub fn consume_nonce(env: Env, address: Address, nonce: u64) {
+        // Require that the address authorizes giving up its nonce
+        address.require_auth();
+
+        consume_nonce(&env, &address, nonce);
+
+        env.events()
+            .publish((symbol_short!("nonce"), address), nonce);
+    }
+
+    /// Returns whether a nonce of the address has been used by a signed payload or consumed explicitly.
+    pub fn is_nonce_used(env: Env, address: Address, nonce: u64) -> bool {
+        env.storage()
+            .persistent()
+            .has(&DataKey::UsedNonce(address, nonce))
+    }
+}
+
+/// Helper function marking a nonce of the address as used, panicking if it already was. Every signature- or
+/// relayer-based entrypoint draws from this one registry, so a payload cannot be replayed through another one.
+pub(crate) fn consume_nonce(env: &Env, address: &Address, nonce: u64) {
+    let nonce_key = DataKey::UsedNonce(address.clone(), nonce);
+    if env.storage().persistent().has(&nonce_key) {
+        panic!("nonce has already been used");
+    }
+    env.storage().persistent().set(&nonce_key, &());
+}
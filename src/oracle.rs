@@ -0,0 +1,53 @@
+use soroban_sdk::{contractclient, contracttype, Address, Env, Symbol};
+
+/// Enum identifying an asset priced by a Reflector-compatible oracle.
+#[derive(Clone)]
+#[contracttype]
+pub enum OracleAsset {
+    Stellar(Address),  // Stellar asset, by its token contract address
+    Other(Symbol),     // Off-chain asset, by its ticker
+}
+
+/// Struct holding a price reported by the oracle.
+#[derive(Clone)]
+#[contracttype]
+pub struct PriceData {
+    pub price: i128,     // Price, scaled by the oracle's decimals
+    pub timestamp: u64,  // UNIX timestamp of the price
+}
+
+/// Interface of Reflector-compatible price oracles.
+#[contractclient(name = "PriceOracleClient")]
+pub trait PriceOracle {
+    /// Returns the most recent price of the asset, if the oracle has one.
+    fn lastprice(env: Env, asset: OracleAsset) -> Option<PriceData>;
+}
+
+/// Enum selecting on which side of the target the oracle price must be.
+#[derive(Clone)]
+#[contracttype]
+pub enum Comparator {
+    Above,  // Satisfied when the price is strictly above the target
+    Below,  // Satisfied when the price is strictly below the target
+}
+
+/// Struct describing a claim condition on the price of an asset.
+#[derive(Clone)]
+#[contracttype]
+pub struct PriceCondition {
+    pub oracle: Address,         // Reflector-compatible oracle contract
+    pub asset: OracleAsset,      // Asset whose price is checked
+    pub comparator: Comparator,  // Side of the target the price must be on
+    pub price: i128,             // Target price, in the oracle's scale
+}
+
+/// Helper function asking the oracle whether the price condition holds; it does not while the oracle has no price.
+pub(crate) fn price_condition_holds(env: &Env, condition: &PriceCondition) -> bool {
+    let Some(last) = PriceOracleClient::new(env, &condition.oracle).lastprice(&condition.asset) else {
+        return false;
+    };
+    match condition.comparator {
+        Comparator::Above => last.price > condition.price,
+        Comparator::Below => last.price < condition.price,
+    }
+}
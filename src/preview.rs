@@ -0,0 +1,97 @@
+use soroban_sdk::{contractimpl, contracttype, Address, Env};
+
+use crate::{
+    check_claimant_predicate, check_time_bound, claim_hook_denial, claims_paused, cooldown_end, current_time,
+    due_payout, escrow, gate_allows, index, multisig, protocol_fee, read_balance, strategy, Airdrop, ClaimableBalanceContract,
+    ClaimableBalanceContractArgs, ClaimableBalanceContractClient, Hashlock,
+};
+
+/// Enum naming the first check a claim of the balance would fail right now.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum ClaimBlocker {
+    None,               // The claim would go through
+    Paused,             // Claims are paused
+    Frozen,             // The admin froze the balance
+    Deployed,           // Part of the balance is deployed to a yield strategy and has to be recalled first
+    Locked,             // The time bound or the balance predicate does not hold
+    NotClaimant,        // The address is not a claimant of the balance
+    ClaimantCondition,  // The claimant's own predicate, lock or claim window does not hold
+    NeedsPreimage,      // The balance is hashlocked and can only be claimed with `claim_with_preimage`
+    NeedsProof,         // The balance is a Merkle airdrop and can only be claimed with `claim_with_proof`
+    Denied(u32),        // The compliance hook denied the claim with this reason code
+    Gate,               // The gate of the balance refused the claimant
+    NotApproved,        // Not enough claimants approved releasing the balance to the claimant
+    NotConfirmed,       // The buyer has not confirmed the escrow yet
+    Disputed,           // The escrow awaits the arbiter
+    NothingDue,         // Nothing vested or matured since the last claim, or the claimant's share was paid
+    Cooldown,           // The claim cooldown since the last partial claim is still running
+    WithdrawalCap,      // The rolling withdrawal cap has no room for the payout
+}
+
+/// Struct describing what a claim of a balance would pay the claimant right now.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct ClaimPreview {
+    pub payout: i128,           // Amount of the balance's token the claim would release, before the protocol fee
+    pub fee: i128,              // Protocol fee taken from the payout
+    pub net: i128,              // Amount the claimant would receive
+    pub blocker: ClaimBlocker,  // First check the claim would fail; `None` when it would go through
+}
+
+#[contractimpl]
+impl ClaimableBalanceContract {
+    /// Dry-runs a claim of the balance by the claimant: returns what it would pay right now, net of the protocol
+    /// fee, and the first check it would fail. Amounts are worked out even when a check fails, so front-ends can
+    /// show what the claim is worth; basket assets are not included.
+    pub fn preview_claim(env: Env, balance_id: u64, claimant: Address) -> ClaimPreview {
+        let claimable_balance = read_balance(&env, balance_id);
+
+        let (payout, within_cap) = due_payout(&env, balance_id, &claimable_balance, &claimant);
+        let fee = protocol_fee(&env, &claimable_balance.token, &claimant, payout).map_or(0, |(_, fee)| fee);
+
+        // Run the checks in the order the claim entrypoints do
+        let blocker = if claims_paused(&env) {
+            ClaimBlocker::Paused
+        } else if claimable_balance.frozen {
+            ClaimBlocker::Frozen
+        } else if strategy::is_deployed(&env, balance_id) {
+            ClaimBlocker::Deployed
+        } else if !check_time_bound(&env, &claimable_balance) {
+            ClaimBlocker::Locked
+        } else if let Airdrop::MerkleRoot(_) = claimable_balance.airdrop {
+            ClaimBlocker::NeedsProof
+        } else if !index::is_claimant(&env, balance_id, &claimant) {
+            ClaimBlocker::NotClaimant
+        } else if !check_claimant_predicate(&env, &claimable_balance, &claimant) {
+            ClaimBlocker::ClaimantCondition
+        } else if let Hashlock::Sha256(_) = claimable_balance.hashlock {
+            ClaimBlocker::NeedsPreimage
+        } else if !gate_allows(&env, balance_id, &claimable_balance, &claimant) {
+            ClaimBlocker::Gate
+        } else if !multisig::is_approved(&env, balance_id, &claimable_balance, &claimant) {
+            ClaimBlocker::NotApproved
+        } else if !escrow::is_buyer_confirmed(&env, balance_id, &claimable_balance) {
+            ClaimBlocker::NotConfirmed
+        } else if claimable_balance.disputed {
+            ClaimBlocker::Disputed
+        } else if payout <= 0 {
+            ClaimBlocker::NothingDue
+        } else if let Some(reason) = claim_hook_denial(&env, &claimant, &claimable_balance.token, payout) {
+            ClaimBlocker::Denied(reason.code)
+        } else if current_time(&env) < cooldown_end(&claimable_balance) {
+            ClaimBlocker::Cooldown
+        } else if !within_cap {
+            ClaimBlocker::WithdrawalCap
+        } else {
+            ClaimBlocker::None
+        };
+
+        ClaimPreview {
+            payout,
+            fee,
+            net: payout - fee,
+            blocker,
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use soroban_sdk::{contractimpl, contracttype, symbol_short, Address, Env};
+
+use crate::{
+    read_admin, read_config, ClaimableBalanceContract, ClaimableBalanceContractArgs,
+    ClaimableBalanceContractClient, DataKey,
+};
+
+/// Enum representing the roles of the call scheduler, mirroring OpenZeppelin's TimelockController.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum Role {
+    Admin,      // Can grant and revoke roles; always held by the contract admin
+    Proposer,   // Can schedule operations
+    Executor,   // Can execute ready operations
+    Canceller,  // Can cancel pending operations
+    Guardian,   // Emergency brake: can only cancel pending operations and announced freeze actions
+}
+
+#[contractimpl]
+impl ClaimableBalanceContract {
+    /// Grants a role to an account, effective once the scheduler's `min_delay` has passed, so a role holder cannot
+    /// grant itself the right to schedule and execute calls faster than the timelock allows. Only callable by
+    /// holders of the `Admin` role.
+    pub fn grant_role(env: Env, caller: Address, role: Role, account: Address) {
+        require_role(&env, &caller, Role::Admin);
+
+        // Leave cancellers and guardians time to react before the role can be used
+        let effective_at = env.ledger().timestamp().saturating_add(read_config(&env).min_delay);
+        env.storage()
+            .persistent()
+            .set(&DataKey::Role(role.clone(), account.clone()), &effective_at);
+
+        env.events()
+            .publish((symbol_short!("grant"), role, account), (caller, effective_at));
+    }
+
+    /// Revokes a role from an account. Only callable by holders of the `Admin` role.
+    pub fn revoke_role(env: Env, caller: Address, role: Role, account: Address) {
+        require_role(&env, &caller, Role::Admin);
+
+        env.storage()
+            .persistent()
+            .remove(&DataKey::Role(role.clone(), account.clone()));
+
+        env.events()
+            .publish((symbol_short!("revoke"), role, account), caller);
+    }
+
+    /// Returns whether the account holds the role, and its grant has taken effect.
+    pub fn has_role(env: Env, role: Role, account: Address) -> bool {
+        has_role(&env, role, &account)
+    }
+}
+
+/// Helper function checking whether an account holds a role whose grant has taken effect; the contract admin always
+/// holds `Admin`.
+pub(crate) fn has_role(env: &Env, role: Role, account: &Address) -> bool {
+    if role == Role::Admin && *account == read_admin(env) {
+        return true;
+    }
+
+    env.storage()
+        .persistent()
+        .get::<_, u64>(&DataKey::Role(role, account.clone()))
+        .is_some_and(|effective_at| env.ledger().timestamp() >= effective_at)
+}
+
+/// Helper function requiring the caller's authorization and the given role; panics otherwise.
+pub(crate) fn require_role(env: &Env, caller: &Address, role: Role) {
+    caller.require_auth();
+
+    if !has_role(env, role, caller) {
+        panic!("caller is missing the required role");
+    }
+}
+
+/// Helper function requiring the caller's authorization and at least one of the given roles; panics otherwise.
+pub(crate) fn require_any_role(env: &Env, caller: &Address, roles: &[Role]) {
+    caller.require_auth();
+
+    if !roles.iter().any(|role| has_role(env, role.clone(), caller)) {
+        panic!("caller is missing the required role");
+    }
+}
@@ -0,0 +1,119 @@
+use soroban_sdk::{contractimpl, contracttype, symbol_short, Address, Env, Vec};
+
+use crate::{
+    index, read_balance, write_balance, ClaimableBalance, ClaimableBalanceContract, ClaimableBalanceContractArgs,
+    ClaimableBalanceContractClient, DataKey, TimeBoundKind,
+};
+
+/// Struct describing a later unlock time proposed by the depositor, which takes effect once every claimant accepted.
+#[derive(Clone)]
+#[contracttype]
+pub struct LockExtension {
+    pub new_timestamp: u64,      // Proposed UNIX timestamp from which the balance can be claimed
+    pub accepted: Vec<Address>,  // Claimants that accepted the proposal so far
+}
+
+#[contractimpl]
+impl ClaimableBalanceContract {
+    /// Pushes back the time from which the claimant can claim, e.g. to commit to a longer lock for staking rewards.
+    /// Only affects the calling claimant and can only move its unlock time forward.
+    pub fn extend_my_lock(env: Env, claimant: Address, balance_id: u64, new_timestamp: u64) {
+        // Require that claimant authorizes the extension
+        claimant.require_auth();
+
+        let mut claimable_balance = read_balance(&env, balance_id);
+        if !index::is_claimant(&env, balance_id, &claimant) {
+            panic!("claimant is not allowed to claim this balance");
+        }
+        check_relockable(&claimable_balance);
+
+        // The new unlock time must lie beyond the current one
+        let current = claimable_balance
+            .claimant_locks
+            .get(claimant.clone())
+            .unwrap_or(claimable_balance.time_bound.timestamp);
+        if new_timestamp <= current {
+            panic!("lock can only be extended");
+        }
+
+        claimable_balance.claimant_locks.set(claimant.clone(), new_timestamp);
+        write_balance(&env, balance_id, &claimable_balance);
+
+        env.events()
+            .publish((symbol_short!("relock"), balance_id, claimant), new_timestamp);
+    }
+
+    /// Proposes to push back the unlock time of a balance. The extension only takes effect once every claimant
+    /// accepted it; a new proposal replaces the previous one and its acceptances. Only callable by the depositor.
+    pub fn propose_extension(env: Env, balance_id: u64, new_timestamp: u64) {
+        let claimable_balance = read_balance(&env, balance_id);
+
+        // Require that the depositor authorizes the proposal
+        claimable_balance.depositor.require_auth();
+
+        check_relockable(&claimable_balance);
+        if new_timestamp <= claimable_balance.time_bound.timestamp {
+            panic!("lock can only be extended");
+        }
+
+        let extension = LockExtension {
+            new_timestamp,
+            accepted: Vec::new(&env),
+        };
+        env.storage()
+            .persistent()
+            .set(&DataKey::PendingExtension(balance_id), &extension);
+        env.events()
+            .publish((symbol_short!("ext_prop"), balance_id), new_timestamp);
+    }
+
+    /// Accepts the proposed extension. The claimant names the unlock time it agrees to so a later proposal cannot be
+    /// slipped in. The last acceptance moves the unlock time of the balance.
+    pub fn accept_extension(env: Env, claimant: Address, balance_id: u64, new_timestamp: u64) {
+        // Require that claimant authorizes the acceptance
+        claimant.require_auth();
+
+        let mut claimable_balance = read_balance(&env, balance_id);
+        if !index::is_claimant(&env, balance_id, &claimant) {
+            panic!("claimant is not allowed to claim this balance");
+        }
+        let pending_key = DataKey::PendingExtension(balance_id);
+        let mut extension: LockExtension = env
+            .storage()
+            .persistent()
+            .get(&pending_key)
+            .expect("no extension proposed");
+        if extension.new_timestamp != new_timestamp {
+            panic!("extension proposal has changed");
+        }
+        if !extension.accepted.contains(&claimant) {
+            extension.accepted.push_back(claimant.clone());
+        }
+        env.events()
+            .publish((symbol_short!("ext_acc"), balance_id, claimant), new_timestamp);
+
+        // Keep waiting until every claimant agreed
+        if !claimable_balance.claimants.iter().all(|c| extension.accepted.contains(&c)) {
+            env.storage().persistent().set(&pending_key, &extension);
+            return;
+        }
+
+        env.storage().persistent().remove(&pending_key);
+        claimable_balance.time_bound.timestamp = new_timestamp;
+        write_balance(&env, balance_id, &claimable_balance);
+        env.events()
+            .publish((symbol_short!("extended"), balance_id), new_timestamp);
+    }
+
+    /// Returns the extension awaiting the claimants' acceptance, if any.
+    pub fn pending_extension(env: Env, balance_id: u64) -> Option<LockExtension> {
+        env.storage().persistent().get(&DataKey::PendingExtension(balance_id))
+    }
+}
+
+/// Helper function panicking unless the balance unlocks after a time, the only kind of lock that can be extended.
+fn check_relockable(claimable_balance: &ClaimableBalance) {
+    if !matches!(claimable_balance.time_bound.kind, TimeBoundKind::After) {
+        panic!("only balances unlocking after a time can be relocked");
+    }
+}
@@ -0,0 +1,40 @@
+use soroban_sdk::{contractimpl, symbol_short, token, Address, Env};
+
+use crate::{
+    read_admin, tvl, ClaimableBalanceContract, ClaimableBalanceContractArgs, ClaimableBalanceContractClient,
+};
+
+#[contractimpl]
+impl ClaimableBalanceContract {
+    /// Sends tokens that reached the contract outside of a deposit, e.g. by a direct transfer, to `to`.
+    /// Only callable by the admin, and only up to `rescuable(token)`, so funds owed to anyone stay put.
+    pub fn rescue(env: Env, token: Address, amount: i128, to: Address) {
+        // Require that the admin authorizes the rescue
+        read_admin(&env).require_auth();
+
+        if amount <= 0 {
+            panic!("amount must be positive");
+        }
+        if amount > rescuable_amount(&env, &token) {
+            panic!("amount exceeds the untracked balance");
+        }
+
+        token::Client::new(&env, &token).transfer(&env.current_contract_address(), &to, &amount);
+
+        env.events()
+            .publish((symbol_short!("rescue"), token, to), amount);
+    }
+
+    /// Returns the amount of a token the contract holds beyond its open balances, bounties, rent budgets, streams
+    /// and pending deliveries.
+    pub fn rescuable(env: Env, token: Address) -> i128 {
+        rescuable_amount(&env, &token)
+    }
+}
+
+/// Helper function computing the held amount of a token that nobody is owed; never negative.
+fn rescuable_amount(env: &Env, token: &Address) -> i128 {
+    let held = token::Client::new(env, token).balance(&env.current_contract_address());
+    let owed = tvl::read_total_locked(env, token) + tvl::read_reserved(env, token);
+    (held - owed).max(0)
+}
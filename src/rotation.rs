@@ -0,0 +1,152 @@
+use soroban_sdk::{contractimpl, symbol_short, Address, Env, Vec};
+
+use crate::{
+    index, lottery, read_balance, write_balance, ClaimWindow, ClaimableBalance, ClaimableBalanceContract,
+    ClaimableBalanceContractArgs, ClaimableBalanceContractClient, DataKey, DistributionMode, Multisig,
+};
+
+#[contractimpl]
+impl ClaimableBalanceContract {
+    /// Proposes to replace the claimant's address on a balance, e.g. after its key was compromised.
+    /// The depositor has to approve the rotation before it takes effect; a new proposal replaces the previous one.
+    pub fn propose_rotation(env: Env, claimant: Address, balance_id: u64, new_claimant: Address) {
+        // Require that the current claimant authorizes the proposal
+        claimant.require_auth();
+
+        let claimable_balance = read_balance(&env, balance_id);
+        check_rotatable(&claimable_balance);
+        if !index::is_claimant(&env, balance_id, &claimant) {
+            panic!("claimant is not allowed to claim this balance");
+        }
+        if index::is_claimant(&env, balance_id, &new_claimant) {
+            panic!("new claimant is already a claimant");
+        }
+
+        env.storage().persistent().set(
+            &DataKey::PendingRotation(balance_id, claimant.clone()),
+            &new_claimant,
+        );
+        env.events().publish(
+            (symbol_short!("rot_prop"), balance_id, claimant),
+            new_claimant,
+        );
+    }
+
+    /// Approves a proposed rotation, moving the claimant's shares, conditions and claim progress to the new address.
+    /// The depositor names the address it approves so a later proposal cannot be slipped in. Only callable by the
+    /// depositor.
+    pub fn approve_rotation(env: Env, balance_id: u64, claimant: Address, new_claimant: Address) {
+        let mut claimable_balance = read_balance(&env, balance_id);
+
+        // Require that the depositor authorizes the rotation
+        claimable_balance.depositor.require_auth();
+
+        let pending_key = DataKey::PendingRotation(balance_id, claimant.clone());
+        let proposed: Address = env
+            .storage()
+            .persistent()
+            .get(&pending_key)
+            .expect("no rotation proposed");
+        if proposed != new_claimant {
+            panic!("rotation proposal has changed");
+        }
+        env.storage().persistent().remove(&pending_key);
+
+        replace_claimant(&env, balance_id, &mut claimable_balance, &claimant, &new_claimant);
+        write_balance(&env, balance_id, &claimable_balance);
+
+        env.events().publish(
+            (symbol_short!("rotated"), balance_id, claimant),
+            new_claimant,
+        );
+    }
+}
+
+/// Helper function panicking if the claimants of a balance cannot be swapped, because approvals or
+/// confirmations are bound to their identities.
+pub(crate) fn check_rotatable(claimable_balance: &ClaimableBalance) {
+    if !matches!(claimable_balance.multisig, Multisig::None) || claimable_balance.escrow {
+        panic!("claimants of multisig and escrow balances cannot be replaced");
+    }
+}
+
+/// Helper function moving everything a balance keeps about a claimant to a new address, including its index entry.
+pub(crate) fn replace_claimant(
+    env: &Env,
+    balance_id: u64,
+    claimable_balance: &mut ClaimableBalance,
+    claimant: &Address,
+    new_claimant: &Address,
+) {
+    let position = claimable_balance
+        .claimants
+        .first_index_of(claimant)
+        .expect("claimant is not allowed to claim this balance");
+    if index::is_claimant(env, balance_id, new_claimant) {
+        panic!("new claimant is already a claimant");
+    }
+    claimable_balance.claimants.set(position, new_claimant.clone());
+
+    // Carry over the claimant's share and conditions
+    if let Some(share) = claimable_balance.allocations.get(claimant.clone()) {
+        claimable_balance.allocations.remove(claimant.clone());
+        claimable_balance.allocations.set(new_claimant.clone(), share);
+    }
+    if let Some(predicate) = claimable_balance.claimant_predicates.get(claimant.clone()) {
+        claimable_balance.claimant_predicates.remove(claimant.clone());
+        claimable_balance.claimant_predicates.set(new_claimant.clone(), predicate);
+    }
+    if let DistributionMode::Weighted(weights) = &claimable_balance.distribution {
+        let mut rotated = Vec::new(env);
+        for (address, weight) in weights.iter() {
+            let address = if address == *claimant { new_claimant.clone() } else { address };
+            rotated.push_back((address, weight));
+        }
+        claimable_balance.distribution = DistributionMode::Weighted(rotated);
+    }
+    if let Some(lock) = claimable_balance.claimant_locks.get(claimant.clone()) {
+        claimable_balance.claimant_locks.remove(claimant.clone());
+        claimable_balance.claimant_locks.set(new_claimant.clone(), lock);
+    }
+    let mut windows = Vec::new(env);
+    for window in claimable_balance.claim_windows.iter() {
+        if window.claimant == *claimant {
+            windows.push_back(ClaimWindow {
+                claimant: new_claimant.clone(),
+                ..window
+            });
+        } else {
+            windows.push_back(window);
+        }
+    }
+    claimable_balance.claim_windows = windows;
+
+    // Carry over what the claimant already claimed and withdrew
+    let storage = env.storage().persistent();
+    let claimed_key = DataKey::Claimed(balance_id, claimant.clone());
+    if storage.has(&claimed_key) {
+        storage.remove(&claimed_key);
+        storage.set(&DataKey::Claimed(balance_id, new_claimant.clone()), &());
+    }
+    let withdrawals_key = DataKey::Withdrawals(balance_id, claimant.clone());
+    if let Some(withdrawals) = storage.get::<_, Vec<(u64, i128)>>(&withdrawals_key) {
+        storage.remove(&withdrawals_key);
+        storage.set(&DataKey::Withdrawals(balance_id, new_claimant.clone()), &withdrawals);
+    }
+
+    if lottery::read_winner(env, balance_id).as_ref() == Some(claimant) {
+        lottery::write_winner(env, balance_id, new_claimant);
+    }
+
+    index::remove_claimant_entry(env, balance_id, claimant);
+    index::add_claimant_entry(env, balance_id, new_claimant);
+}
+
+/// Helper function dropping the rotation proposals left on a balance that is being closed.
+pub(crate) fn clear_rotations(env: &Env, balance_id: u64, claimable_balance: &ClaimableBalance) {
+    for claimant in claimable_balance.claimants.iter() {
+        env.storage()
+            .persistent()
+            .remove(&DataKey::PendingRotation(balance_id, claimant));
+    }
+}
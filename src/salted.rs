@@ -0,0 +1,54 @@
+use soroban_sdk::{contractimpl, xdr::ToXdr, Address, BytesN, Env, Vec};
+
+use crate::{
+    create_balance_as, ClaimableBalance, ClaimableBalanceContract, ClaimableBalanceContractArgs,
+    ClaimableBalanceContractClient, DataKey, Funding, TimeBound,
+};
+
+/// Bit set on every salted balance id, keeping them apart from the sequential ones.
+const SALTED_ID_FLAG: u64 = 1 << 63;
+
+#[contractimpl]
+impl ClaimableBalanceContract {
+    /// Deposits a balance like `deposit`, under the id `salted_balance_id(from, salt)` that off-chain systems can
+    /// compute before submitting. Each salt can only fund one balance per depositor, so a logical payout cannot be
+    /// funded twice. Returns the id of the new balance.
+    pub fn deposit_with_salt(
+        env: Env,
+        from: Address,              // Address sending the tokens
+        token: Address,             // Token contract address
+        amount: i128,               // Amount of tokens to deposit
+        claimants: Vec<Address>,    // Allowed claimants
+        time_bound: TimeBound,      // Time-bound constraint
+        salt: BytesN<32>,           // Depositor-chosen value identifying the payout
+    ) -> u64 {
+        let balance_id = salted_id(&env, &from, &salt);
+
+        // Salted ids are never reused, even after their balance is settled
+        let salt_key = DataKey::SaltUsed(balance_id);
+        if env.storage().persistent().has(&salt_key) {
+            panic!("salt has already been used");
+        }
+        env.storage().persistent().set(&salt_key, &());
+
+        let claimable_balance = ClaimableBalance::new(&env, from, token, amount, claimants, time_bound);
+        create_balance_as(&env, balance_id, claimable_balance, Funding::Transfer)
+    }
+
+    /// Returns the id `deposit_with_salt` assigns for the depositor and salt.
+    pub fn salted_balance_id(env: Env, depositor: Address, salt: BytesN<32>) -> u64 {
+        salted_id(&env, &depositor, &salt)
+    }
+}
+
+/// Helper function deriving a balance id from the first 8 bytes of sha256(depositor xdr || salt), with the salted
+/// id flag set.
+fn salted_id(env: &Env, depositor: &Address, salt: &BytesN<32>) -> u64 {
+    let mut data = depositor.clone().to_xdr(env);
+    data.append(&salt.clone().into());
+    let hash = env.crypto().sha256(&data).to_array();
+
+    let mut id_bytes = [0u8; 8];
+    id_bytes.copy_from_slice(&hash[..8]);
+    u64::from_be_bytes(id_bytes) | SALTED_ID_FLAG
+}
@@ -0,0 +1,83 @@
+use soroban_sdk::{contractclient, contractimpl, contracttype, Address, Env};
+
+use crate::rbac::{require_role, Role};
+use crate::scheduler::{execute_operation, is_expired, is_predecessor_executed, read_operation, OperationStatus};
+use crate::{
+    bump_rent_for, next_rent_bump_sequence, read_config, read_rent_budget,
+    refund_expired_balance, ClaimableBalance, ClaimableBalanceContract,
+    ClaimableBalanceContractArgs, ClaimableBalanceContractClient, DataKey, TimeBoundKind,
+};
+
+/// Approximate duration of a ledger in seconds, used to translate ledger sequences into timestamps.
+const LEDGER_DURATION: u64 = 5;
+
+/// Enum identifying a time-driven maintenance task that keepers can perform.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum Task {
+    RentBump(u64),     // Extend the TTL of a balance, paid from its rent budget
+    ExpirySweep(u64),  // Return an expired balance to its depositor
+    Operation(u64),    // Execute a ready scheduled operation
+}
+
+/// Interface allowing generic keeper networks to drive time-based actions without bespoke integration.
+#[contractclient(name = "SchedulableClient")]
+pub trait Schedulable {
+    /// Returns the UNIX timestamp from which the task can be performed, or `None` if there is nothing to do.
+    fn next_action_time(env: Env, task: Task) -> Option<u64>;
+
+    /// Performs the task on behalf of the keeper, which receives any keeper reward.
+    fn perform(env: Env, keeper: Address, task: Task);
+}
+
+#[contractimpl]
+impl Schedulable for ClaimableBalanceContract {
+    fn next_action_time(env: Env, task: Task) -> Option<u64> {
+        let now = env.ledger().timestamp();
+
+        match task {
+            Task::RentBump(balance_id) => {
+                // Nothing to keep alive, or no budget left to pay for the bump
+                if !env.storage().persistent().has(&DataKey::Balance(balance_id))
+                    || read_rent_budget(&env, balance_id) < read_config(&env).rent_bump_fee
+                {
+                    return None;
+                }
+                let ledgers_left = next_rent_bump_sequence(&env, balance_id)
+                    .saturating_sub(env.ledger().sequence());
+                Some(now + ledgers_left as u64 * LEDGER_DURATION)
+            }
+            Task::ExpirySweep(balance_id) => {
+                let claimable_balance: ClaimableBalance =
+                    env.storage().persistent().get(&DataKey::Balance(balance_id))?;
+                match claimable_balance.time_bound.kind {
+                    TimeBoundKind::Before => Some(claimable_balance.time_bound.timestamp + 1),
+                    TimeBoundKind::After => None,
+                    TimeBoundKind::Between(end) => Some(end + 1),
+                }
+            }
+            Task::Operation(op_id) => {
+                // Operations waiting on a predecessor are not due yet, whatever their eta, and expired ones never
+                let operation = read_operation(&env, op_id);
+                (operation.status == OperationStatus::Pending
+                    && is_predecessor_executed(&env, &operation)
+                    && !is_expired(&env, &operation))
+                    .then_some(operation.eta)
+            }
+        }
+    }
+
+    fn perform(env: Env, keeper: Address, task: Task) {
+        match task {
+            Task::RentBump(balance_id) => bump_rent_for(&env, keeper, balance_id),
+            Task::ExpirySweep(balance_id) => {
+                refund_expired_balance(&env, keeper, balance_id);
+            }
+            Task::Operation(op_id) => {
+                // Scheduled calls stay restricted to executors, even when driven by a keeper network
+                require_role(&env, &keeper, Role::Executor);
+                execute_operation(&env, op_id);
+            }
+        }
+    }
+}
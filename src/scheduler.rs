@@ -0,0 +1,296 @@
+use soroban_sdk::{
+    contractimpl, contracttype, symbol_short, vec, Address, Env, IntoVal, Symbol, TryFromVal, Val, Vec,
+};
+
+use crate::rbac::{require_any_role, require_role, Role};
+use crate::{
+    events, read_config, tvl, ClaimableBalanceContract, ClaimableBalanceContractArgs,
+    ClaimableBalanceContractClient, DataKey,
+};
+
+/// Enum representing the lifecycle of a scheduled operation.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum OperationStatus {
+    Pending,    // Waiting for its eta, or ready to be executed
+    Executed,   // The call has been performed
+    Cancelled,  // The call was cancelled before execution
+}
+
+/// Enum representing the state of an operation id as shown to governance interfaces.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum OperationState {
+    Unset,      // No operation was scheduled under the id
+    Pending,    // Waiting for its eta or its predecessor
+    Ready,      // Can be executed now
+    Expired,    // Was not executed within the grace period and can no longer run
+    Done,       // The calls have been performed
+    Cancelled,  // The calls were cancelled before execution
+}
+
+/// Struct representing one cross-contract call of a scheduled operation.
+#[derive(Clone)]
+#[contracttype]
+pub struct Call {
+    pub target: Address,   // Contract to call
+    pub function: Symbol,  // Function to invoke on the target
+    pub args: Vec<Val>,    // Arguments passed to the function
+}
+
+/// Struct representing cross-contract calls that can only be executed, together, after a minimum delay.
+#[derive(Clone)]
+#[contracttype]
+pub struct Operation {
+    pub calls: Vec<Call>,          // Calls performed in order by a single execution
+    pub eta: u64,                  // UNIX timestamp from which the calls can be executed
+    pub predecessor: Option<u64>,  // Operation that has to be executed first, if any
+    pub status: OperationStatus,   // Current lifecycle state
+}
+
+#[contractimpl]
+impl ClaimableBalanceContract {
+    /// Schedules a call to be executed no earlier than `eta`, which must respect the configured minimum delay, and
+    /// only after the `predecessor` operation, if any, was executed. Only callable by proposers; returns the
+    /// operation id.
+    pub fn schedule(
+        env: Env,
+        proposer: Address,
+        target: Address,
+        function: Symbol,
+        args: Vec<Val>,
+        eta: u64,
+        predecessor: Option<u64>,
+    ) -> u64 {
+        // Require that a proposer authorizes the scheduling
+        require_role(&env, &proposer, Role::Proposer);
+
+        let call = Call {
+            target,
+            function,
+            args,
+        };
+        store_operation(&env, vec![&env, call], eta, predecessor)
+    }
+
+    /// Schedules several calls as a single operation, whose execution performs all of them in order or none at
+    /// all. The same delay and predecessor rules as for `schedule` apply. Only callable by proposers; returns the
+    /// operation id.
+    pub fn schedule_batch(
+        env: Env,
+        proposer: Address,
+        calls: Vec<Call>,
+        eta: u64,
+        predecessor: Option<u64>,
+    ) -> u64 {
+        // Require that a proposer authorizes the scheduling
+        require_role(&env, &proposer, Role::Proposer);
+
+        if calls.is_empty() {
+            panic!("batch must not be empty");
+        }
+        store_operation(&env, calls, eta, predecessor)
+    }
+
+    /// Executes a pending operation once its eta has passed. Returns the results of its calls in order, also for a
+    /// single call. Only callable by executors.
+    pub fn execute(env: Env, executor: Address, op_id: u64) -> Vec<Val> {
+        // Require that an executor authorizes the execution
+        require_role(&env, &executor, Role::Executor);
+
+        execute_operation(&env, op_id)
+    }
+
+    /// Returns the state of an operation, telling apart pending operations from those ready to be executed.
+    pub fn operation_state(env: Env, op_id: u64) -> OperationState {
+        let Some(operation) = env
+            .storage()
+            .persistent()
+            .get::<_, Operation>(&DataKey::Operation(op_id))
+        else {
+            return OperationState::Unset;
+        };
+        match operation.status {
+            OperationStatus::Executed => OperationState::Done,
+            OperationStatus::Cancelled => OperationState::Cancelled,
+            OperationStatus::Pending => {
+                if is_expired(&env, &operation) {
+                    OperationState::Expired
+                } else if env.ledger().timestamp() >= operation.eta && is_predecessor_executed(&env, &operation) {
+                    OperationState::Ready
+                } else {
+                    OperationState::Pending
+                }
+            }
+        }
+    }
+
+    /// Returns a scheduled operation with its calls and eta, if any was scheduled under the id.
+    pub fn get_operation(env: Env, op_id: u64) -> Option<Operation> {
+        env.storage().persistent().get(&DataKey::Operation(op_id))
+    }
+
+    /// Cancels a pending operation. Only callable by cancellers and guardians.
+    pub fn cancel(env: Env, canceller: Address, op_id: u64) {
+        // Require that a canceller or a guardian authorizes the cancellation
+        require_any_role(&env, &canceller, &[Role::Canceller, Role::Guardian]);
+
+        let mut operation = read_operation(&env, op_id);
+        if operation.status != OperationStatus::Pending {
+            panic!("operation is not pending");
+        }
+
+        operation.status = OperationStatus::Cancelled;
+        env.storage()
+            .persistent()
+            .set(&DataKey::Operation(op_id), &operation);
+
+        env.events().publish((symbol_short!("cancel"), op_id), ());
+    }
+}
+
+/// Helper function executing a pending operation once its eta has passed.
+pub(crate) fn execute_operation(env: &Env, op_id: u64) -> Vec<Val> {
+    let mut operation = read_operation(env, op_id);
+
+    if operation.status != OperationStatus::Pending {
+        panic!("operation is not pending");
+    }
+    if env.ledger().timestamp() < operation.eta {
+        panic!("operation is not ready");
+    }
+    if is_expired(env, &operation) {
+        panic!("operation has expired");
+    }
+    if !is_predecessor_executed(env, &operation) {
+        panic!("predecessor has not been executed");
+    }
+
+    // Mark the operation as executed before calling out, so it cannot be re-entered
+    operation.status = OperationStatus::Executed;
+    env.storage()
+        .persistent()
+        .set(&DataKey::Operation(op_id), &operation);
+
+    // A failing call reverts the whole invocation, so a batch never executes partially
+    let mut results: Vec<Val> = Vec::new(env);
+    for call in operation.calls.iter() {
+        let result = if call.target == env.current_contract_address() {
+            execute_self_call(env, &call)
+        } else {
+            // Deposits made since scheduling may have put the target in custody
+            check_call_target(env, &call);
+            env.invoke_contract(&call.target, &call.function, call.args)
+        };
+        results.push_back(result);
+    }
+
+    env.events().publish((symbol_short!("execute"), op_id), ());
+
+    results
+}
+
+/// Helper function performing a call an operation makes on the timelock itself, which Soroban does not allow to
+/// be invoked re-entrantly. Only `update_delay(min_delay)`, `update_upgrade_delay(upgrade_delay)` and
+/// `update_freeze_delay(freeze_delay)` are accepted; they have no public entrypoint, so no delay can be shortened
+/// without waiting out the scheduler delay.
+fn execute_self_call(env: &Env, call: &Call) -> Val {
+    if call.args.len() != 1 {
+        panic!("operations can only update the delays of the timelock itself");
+    }
+    let delay = u64::try_from_val(env, &call.args.get_unchecked(0)).expect("delay must be a u64");
+
+    let mut config = read_config(env);
+    let old_delay = if call.function == Symbol::new(env, "update_delay") {
+        core::mem::replace(&mut config.min_delay, delay)
+    } else if call.function == Symbol::new(env, "update_upgrade_delay") {
+        core::mem::replace(&mut config.upgrade_delay, delay)
+    } else if call.function == Symbol::new(env, "update_freeze_delay") {
+        core::mem::replace(&mut config.freeze_delay, delay)
+    } else {
+        panic!("operations can only update the delays of the timelock itself");
+    };
+    config.validate();
+    env.storage().instance().set(&DataKey::Config, &config);
+
+    env.events()
+        .publish((symbol_short!("delay"), call.function.clone()), (old_delay, delay));
+    ().into_val(env)
+}
+
+/// Helper function validating the eta and predecessor of a new operation and storing it under the next id.
+fn store_operation(env: &Env, calls: Vec<Call>, eta: u64, predecessor: Option<u64>) -> u64 {
+    // Enforce the minimum delay between scheduling and execution
+    if eta < env.ledger().timestamp().saturating_add(read_config(env).min_delay) {
+        panic!("eta is before the minimum delay");
+    }
+
+    // Operations run as the timelock, so they must not reach the funds it holds for others
+    for call in calls.iter() {
+        if call.target != env.current_contract_address() {
+            check_call_target(env, &call);
+        }
+    }
+
+    // Chaining behind a cancelled operation would leave this one unexecutable
+    if let Some(predecessor) = predecessor {
+        if read_operation(env, predecessor).status == OperationStatus::Cancelled {
+            panic!("predecessor is cancelled");
+        }
+    }
+
+    let op_id: u64 = env
+        .storage()
+        .instance()
+        .get(&DataKey::NextOperationId)
+        .unwrap_or(0);
+    env.storage()
+        .instance()
+        .set(&DataKey::NextOperationId, &(op_id + 1));
+
+    env.storage().persistent().set(
+        &DataKey::Operation(op_id),
+        &Operation {
+            calls: calls.clone(),
+            eta,
+            predecessor,
+            status: OperationStatus::Pending,
+        },
+    );
+    events::publish_schedule(env, op_id, calls, eta, predecessor);
+
+    op_id
+}
+
+/// Helper function rejecting calls to a token the contract owes to anyone, or to a strategy holding deployed funds:
+/// the timelock authorizes every call it makes directly, so such a call could move escrowed tokens.
+fn check_call_target(env: &Env, call: &Call) {
+    if tvl::read_total_locked(env, &call.target) > 0 || tvl::read_reserved(env, &call.target) > 0 {
+        panic!("operations cannot call a token held in custody");
+    }
+    if read_config(env).strategies.contains(&call.target) {
+        panic!("operations cannot call a yield strategy");
+    }
+}
+
+/// Helper function checking whether a pending operation missed the grace period after its eta.
+pub(crate) fn is_expired(env: &Env, operation: &Operation) -> bool {
+    let grace_period = read_config(env).grace_period;
+    grace_period > 0 && env.ledger().timestamp() > operation.eta.saturating_add(grace_period)
+}
+
+/// Helper function checking that the operation has no predecessor, or that its predecessor was executed.
+pub(crate) fn is_predecessor_executed(env: &Env, operation: &Operation) -> bool {
+    match operation.predecessor {
+        Some(predecessor) => read_operation(env, predecessor).status == OperationStatus::Executed,
+        None => true,
+    }
+}
+
+/// Helper function to read a scheduled operation; panics if it does not exist.
+pub(crate) fn read_operation(env: &Env, op_id: u64) -> Operation {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Operation(op_id))
+        .expect("operation does not exist")
+}
@@ -0,0 +1,62 @@
+use soroban_sdk::{contractimpl, contracttype, xdr::ToXdr, Address, Bytes, BytesN, Env};
+
+use crate::{
+    current_time, nonce, pay_full_claim, ClaimableBalanceContract, ClaimableBalanceContractArgs,
+    ClaimableBalanceContractClient,
+};
+
+/// Struct holding the claim a claimant signs off-chain for a relayer to submit.
+#[derive(Clone)]
+#[contracttype]
+pub struct SignedClaim {
+    pub balance_id: u64,       // Balance to claim
+    pub destination: Address,  // Address receiving the payout
+    pub expiry: u64,           // UNIX timestamp after which the signature is no longer accepted
+    pub nonce: u64,            // Claimant-chosen number that makes the signature single-use
+}
+
+#[contractimpl]
+impl ClaimableBalanceContract {
+    /// Claims on behalf of a claimant account that signed the claim with its ed25519 key, so any relayer can pay
+    /// the fees. Only full claims are supported. Returns the amount paid out.
+    pub fn claim_with_signature(
+        env: Env,
+        claimant: Address,        // Account whose key signed the claim
+        public_key: BytesN<32>,   // Ed25519 public key of the claimant account
+        claim: SignedClaim,       // Signed claim
+        signature: BytesN<64>,    // Signature of `claim_message(claim)`
+    ) -> i128 {
+        if !is_account_key(&env, &claimant, &public_key) {
+            panic!("public key does not belong to the claimant");
+        }
+        if current_time(&env) > claim.expiry {
+            panic!("signature has expired");
+        }
+
+        // Each nonce can be used once per claimant, across every signed or relayed entrypoint
+        env.crypto()
+            .ed25519_verify(&public_key, &claim_message(&env, &claim), &signature);
+        nonce::consume_nonce(&env, &claimant, claim.nonce);
+
+        pay_full_claim(&env, claim.balance_id, claimant, claim.destination, None)
+    }
+
+    /// Returns the message a claimant has to sign for `claim_with_signature`.
+    pub fn claim_message(env: Env, claim: SignedClaim) -> Bytes {
+        claim_message(&env, &claim)
+    }
+}
+
+/// Helper function serializing a signed claim together with this contract's address, so the signature cannot be
+/// replayed against another deployment.
+fn claim_message(env: &Env, claim: &SignedClaim) -> Bytes {
+    (env.current_contract_address(), claim.clone()).to_xdr(env)
+}
+
+/// Helper function checking that an address is the Stellar account of the given ed25519 public key.
+fn is_account_key(env: &Env, address: &Address, public_key: &BytesN<32>) -> bool {
+    // XDR of an account address: value type, address type and key type tags, then the 32-byte key
+    let xdr = address.clone().to_xdr(env);
+    let account_prefix = Bytes::from_array(env, &[0, 0, 0, 18, 0, 0, 0, 0, 0, 0, 0, 0]);
+    xdr.len() == 44 && xdr.slice(..12) == account_prefix && xdr.slice(12..) == Bytes::from(public_key)
+}
@@ -0,0 +1,72 @@
+use soroban_sdk::{contractimpl, contracttype, Env};
+
+use crate::{
+    check_time_bound, is_expired, ClaimableBalance, ClaimableBalanceContract, ClaimableBalanceContractArgs,
+    ClaimableBalanceContractClient, DataKey,
+};
+
+/// Enum representing the lifecycle state of a balance. The stored state follows every write and closure of the
+/// balance; `Claimable` and `Expired` are derived from the time bound when the status is read.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum BalanceStatus {
+    Pending,           // Locked, nothing paid out yet
+    Claimable,         // Unlocked, nothing paid out yet
+    PartiallyClaimed,  // Part of the amount has been paid out
+    Claimed,           // Fully paid out to the claimants
+    Cancelled,         // Returned to the depositor before it expired, e.g. revoked, declined or redirected
+    Expired,           // Can no longer be claimed, or was refunded because of that
+    Frozen,            // Claims are blocked by the admin
+}
+
+#[contractimpl]
+impl ClaimableBalanceContract {
+    /// Returns the lifecycle state of a balance, including balances that have already been closed.
+    pub fn status(env: Env, balance_id: u64) -> BalanceStatus {
+        let stored: Option<BalanceStatus> = env.storage().persistent().get(&DataKey::Status(balance_id));
+        if let Some(status) = stored {
+            if !matches!(status, BalanceStatus::Pending | BalanceStatus::PartiallyClaimed) {
+                return status;
+            }
+        }
+
+        // Open balances move with time without being written; balances from before statuses were stored have none
+        let claimable_balance: ClaimableBalance = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Balance(balance_id))
+            .expect("balance does not exist");
+        match stored.unwrap_or(open_status(&claimable_balance)) {
+            BalanceStatus::Pending | BalanceStatus::PartiallyClaimed
+                if is_expired(&env, &claimable_balance.time_bound) =>
+            {
+                BalanceStatus::Expired
+            }
+            BalanceStatus::Pending if check_time_bound(&env, &claimable_balance) => BalanceStatus::Claimable,
+            status => status,
+        }
+    }
+}
+
+/// Helper function storing the state of an open balance, as derived from its record.
+pub(crate) fn record_open_status(env: &Env, balance_id: u64, claimable_balance: &ClaimableBalance) {
+    record_status(env, balance_id, open_status(claimable_balance));
+}
+
+/// Helper function deriving the stored state of an open balance from its record.
+fn open_status(claimable_balance: &ClaimableBalance) -> BalanceStatus {
+    if claimable_balance.frozen {
+        BalanceStatus::Frozen
+    } else if claimable_balance.claimed > 0 {
+        BalanceStatus::PartiallyClaimed
+    } else {
+        BalanceStatus::Pending
+    }
+}
+
+/// Helper function storing the state of a balance; the state outlives the balance itself.
+pub(crate) fn record_status(env: &Env, balance_id: u64, status: BalanceStatus) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::Status(balance_id), &status);
+}
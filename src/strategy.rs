@@ -0,0 +1,181 @@
+use soroban_sdk::{contractclient, contractimpl, contracttype, symbol_short, token, Address, Env};
+
+use crate::{
+    check_time_bound, is_expired, math, read_admin, read_balance, read_config, tvl, write_balance,
+    ClaimableBalanceContract, ClaimableBalanceContractArgs, ClaimableBalanceContractClient, DataKey,
+    DistributionMode, Vesting,
+};
+
+/// Interface that yield strategies must implement to hold idle locked tokens.
+#[contractclient(name = "StrategyClient")]
+pub trait Strategy {
+    /// Invests `amount` of `token`, transferred to the strategy beforehand, for `owner`. Returns the shares credited.
+    fn invest(env: Env, owner: Address, token: Address, amount: i128) -> i128;
+
+    /// Redeems `shares` of the authorizing `owner`'s position in `token`, sending what they are worth to the owner.
+    /// Returns the amount sent.
+    fn redeem(env: Env, owner: Address, token: Address, shares: i128) -> i128;
+}
+
+/// Enum representing who receives the yield earned on a balance's deployed funds.
+#[derive(Clone, Default)]
+#[contracttype]
+pub enum YieldRecipient {
+    #[default]
+    Depositor,  // The depositor of the balance
+    Claimant,   // The first claimant of the balance, or the depositor of an airdrop
+    Treasury,   // The configured treasury
+}
+
+/// Struct describing the part of a balance deployed to a yield strategy.
+#[derive(Clone)]
+#[contracttype]
+pub struct Deployment {
+    pub strategy: Address,  // Whitelisted strategy holding the funds
+    pub principal: i128,    // Amount of the balance's token deployed, owed back in full
+    pub shares: i128,       // Shares the strategy credited for the principal
+}
+
+#[contractimpl]
+impl ClaimableBalanceContract {
+    /// Deploys part of a locked balance to a whitelisted yield strategy. The funds have to be recalled before the
+    /// balance can be claimed, refunded or swept. Only callable by the admin.
+    pub fn deploy_funds(env: Env, balance_id: u64, strategy: Address, amount: i128) {
+        // Require that the admin authorizes the deployment
+        read_admin(&env).require_auth();
+
+        if !read_config(&env).strategies.contains(&strategy) {
+            panic!("strategy is not whitelisted");
+        }
+        let claimable_balance = read_balance(&env, balance_id);
+        if check_time_bound(&env, &claimable_balance) || is_expired(&env, &claimable_balance.time_bound) {
+            panic!("only locked balances can be deployed");
+        }
+        if read_deployment(&env, balance_id).is_some() {
+            panic!("balance is already deployed");
+        }
+        if amount <= 0 || amount > claimable_balance.amount - claimable_balance.claimed {
+            panic!("amount must be positive and at most what is left of the balance");
+        }
+
+        // A loss shrinks the amount, so only balances whose payouts scale with it can be deployed
+        if !matches!(claimable_balance.vesting, Vesting::None | Vesting::Linear(_))
+            || !claimable_balance.allocations.is_empty()
+            || matches!(claimable_balance.distribution, DistributionMode::EqualSplit)
+        {
+            panic!("balances with fixed shares or steps cannot be deployed");
+        }
+
+        // Hand the funds to the strategy and keep track of the principal owed back
+        let contract = env.current_contract_address();
+        token::Client::new(&env, &claimable_balance.token).transfer(&contract, &strategy, &amount);
+        let shares = StrategyClient::new(&env, &strategy).invest(&contract, &claimable_balance.token, &amount);
+        let deployment = Deployment {
+            strategy: strategy.clone(),
+            principal: amount,
+            shares,
+        };
+        env.storage()
+            .persistent()
+            .set(&DataKey::Deployment(balance_id), &deployment);
+
+        env.events()
+            .publish((symbol_short!("deploy"), balance_id, strategy), (amount, shares));
+    }
+
+    /// Recalls the deployed funds of a balance, keeping the principal locked and paying the yield to the configured
+    /// recipient. If the strategy lost part of the principal, the balance settles with what came back and the
+    /// shortfall is recorded. Callable by the admin, and by anyone once the balance unlocked or expired so claims and
+    /// refunds cannot be held up. Returns the yield paid.
+    pub fn recall_funds(env: Env, balance_id: u64) -> i128 {
+        let mut claimable_balance = read_balance(&env, balance_id);
+        let deployment = read_deployment(&env, balance_id).expect("balance is not deployed");
+
+        // Require that the admin authorizes the recall while the balance is still locked
+        if !check_time_bound(&env, &claimable_balance) && !is_expired(&env, &claimable_balance.time_bound) {
+            read_admin(&env).require_auth();
+        }
+
+        // Redeem the shares and count what actually came back
+        let contract = env.current_contract_address();
+        let token_client = token::Client::new(&env, &claimable_balance.token);
+        let balance_before = token_client.balance(&contract);
+        StrategyClient::new(&env, &deployment.strategy).redeem(
+            &contract,
+            &claimable_balance.token,
+            &deployment.shares,
+        );
+        let returned = token_client.balance(&contract) - balance_before;
+        env.storage()
+            .persistent()
+            .remove(&DataKey::Deployment(balance_id));
+
+        // A loss comes out of the balance, which keeps only what the strategy returned
+        let shortfall = (deployment.principal - returned).max(0);
+        if shortfall > 0 {
+            claimable_balance.amount -= shortfall;
+            tvl::adjust_total_locked(&env, &claimable_balance.token, -shortfall);
+            write_balance(&env, balance_id, &claimable_balance);
+            let total_shortfall = math::checked(&env, math::add(read_shortfall(&env, balance_id), shortfall));
+            env.storage()
+                .persistent()
+                .set(&DataKey::Shortfall(balance_id), &total_shortfall);
+        }
+
+        // Everything beyond the principal is yield; airdrops list no claimants, so their yield goes to the depositor
+        let earned = (returned - deployment.principal).max(0);
+        let recipient = match read_config(&env).yield_recipient {
+            YieldRecipient::Depositor => claimable_balance.depositor.clone(),
+            YieldRecipient::Claimant => claimable_balance
+                .claimants
+                .first()
+                .unwrap_or(claimable_balance.depositor.clone()),
+            YieldRecipient::Treasury => read_config(&env).treasury.expect("no treasury is configured"),
+        };
+        if earned > 0 {
+            token_client.transfer(&contract, &recipient, &earned);
+        }
+
+        env.events().publish(
+            (symbol_short!("recall"), balance_id, deployment.strategy),
+            (deployment.principal, earned, shortfall, recipient),
+        );
+
+        earned
+    }
+
+    /// Returns the part of a balance currently deployed to a yield strategy, if any.
+    pub fn deployment(env: Env, balance_id: u64) -> Option<Deployment> {
+        read_deployment(&env, balance_id)
+    }
+
+    /// Returns how much of a balance's deployed principal its strategies failed to return; kept after it settles.
+    pub fn shortfall(env: Env, balance_id: u64) -> i128 {
+        read_shortfall(&env, balance_id)
+    }
+}
+
+/// Helper function to read the principal a balance lost to its strategies.
+fn read_shortfall(env: &Env, balance_id: u64) -> i128 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Shortfall(balance_id))
+        .unwrap_or(0)
+}
+
+/// Helper function to read the deployment of a balance, if any.
+fn read_deployment(env: &Env, balance_id: u64) -> Option<Deployment> {
+    env.storage().persistent().get(&DataKey::Deployment(balance_id))
+}
+
+/// Helper function checking whether part of the balance is deployed to a yield strategy.
+pub(crate) fn is_deployed(env: &Env, balance_id: u64) -> bool {
+    env.storage().persistent().has(&DataKey::Deployment(balance_id))
+}
+
+/// Helper function panicking while part of the balance is deployed to a yield strategy.
+pub(crate) fn check_not_deployed(env: &Env, balance_id: u64) {
+    if is_deployed(env, balance_id) {
+        panic!("balance funds are deployed to a yield strategy");
+    }
+}
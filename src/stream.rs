@@ -0,0 +1,188 @@
+use soroban_sdk::{contractimpl, contracttype, symbol_short, token, Address, Env};
+
+use crate::{
+    allowlist, check_claims_not_paused, check_deposits_not_paused, current_time, deliver, events, math, tvl,
+    ClaimableBalanceContract, ClaimableBalanceContractArgs, ClaimableBalanceContractClient,
+    DataKey, MAX_AMOUNT,
+};
+
+/// Struct representing a continuous payment accruing to the recipient every second between `start` and `stop`.
+#[derive(Clone)]
+#[contracttype]
+pub struct Stream {
+    pub sender: Address,     // Address that funded the stream
+    pub recipient: Address,  // Address the tokens stream to
+    pub token: Address,      // Address of the token contract
+    pub deposit: i128,       // Total amount streamed between start and stop
+    pub start: u64,          // UNIX timestamp at which tokens start accruing
+    pub stop: u64,           // UNIX timestamp at which the whole deposit has accrued
+    pub withdrawn: i128,     // Amount already withdrawn by the recipient
+}
+
+#[contractimpl]
+impl ClaimableBalanceContract {
+    /// Creates a stream paying `deposit` tokens to the recipient linearly between `start` and `stop`.
+    /// Returns the stream id.
+    pub fn create_stream(
+        env: Env,
+        sender: Address,     // Address funding the stream
+        recipient: Address,  // Address receiving the stream
+        token: Address,      // Token contract address
+        deposit: i128,       // Amount of tokens to stream
+        start: u64,          // Stream start timestamp
+        stop: u64,           // Stream stop timestamp
+    ) -> u64 {
+        // Require that the sender authorizes the stream
+        sender.require_auth();
+
+        check_deposits_not_paused(&env);
+
+        if deposit <= 0 || deposit > MAX_AMOUNT {
+            panic!("deposit must be positive and at most MAX_AMOUNT");
+        }
+        if start >= stop {
+            panic!("stream start must be before its stop");
+        }
+        allowlist::check_token_allowed(&env, &token);
+        if stop <= current_time(&env) {
+            panic!("stream stop must be in the future");
+        }
+
+        // Transfer the whole deposit from the sender to this contract
+        token::Client::new(&env, &token).transfer(
+            &sender,
+            &env.current_contract_address(),
+            &deposit,
+        );
+        tvl::adjust_reserved(&env, &token, deposit);
+
+        let stream_id: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::NextStreamId)
+            .unwrap_or(0);
+        env.storage()
+            .instance()
+            .set(&DataKey::NextStreamId, &(stream_id + 1));
+
+        let stream = Stream {
+            sender: sender.clone(),
+            recipient: recipient.clone(),
+            token,
+            deposit,
+            start,
+            stop,
+            withdrawn: 0,
+        };
+        env.storage()
+            .persistent()
+            .set(&DataKey::Stream(stream_id), &stream);
+
+        env.events().publish(
+            (symbol_short!("stream"), stream_id, sender, recipient),
+            (stream.token, deposit, start, stop),
+        );
+
+        stream_id
+    }
+
+    /// Withdraws part of the streamed tokens to the recipient. Only callable by the recipient.
+    pub fn withdraw_from_stream(env: Env, stream_id: u64, amount: i128) {
+        let mut stream = read_stream(&env, stream_id);
+
+        // Require that the recipient authorizes the withdrawal
+        stream.recipient.require_auth();
+
+        check_claims_not_paused(&env);
+
+        if amount <= 0 {
+            panic!("amount must be positive");
+        }
+        let withdrawable = math::checked(&env, math::sub(streamed_amount(&env, &stream), stream.withdrawn));
+        if amount > withdrawable {
+            panic!("amount exceeds the withdrawable balance");
+        }
+
+        // Remove the stream once everything has been withdrawn
+        stream.withdrawn = math::checked(&env, math::add(stream.withdrawn, amount));
+        tvl::adjust_reserved(&env, &stream.token, -amount);
+        if stream.withdrawn == stream.deposit {
+            env.storage().persistent().remove(&DataKey::Stream(stream_id));
+        } else {
+            env.storage()
+                .persistent()
+                .set(&DataKey::Stream(stream_id), &stream);
+        }
+
+        token::Client::new(&env, &stream.token).transfer(
+            &env.current_contract_address(),
+            &stream.recipient,
+            &amount,
+        );
+
+        events::publish_stream_withdraw(&env, stream_id, stream.recipient, stream.token, amount);
+    }
+
+    /// Cancels a stream, paying the recipient what has streamed so far and returning the rest to the sender.
+    /// Only callable by the sender.
+    pub fn cancel_stream(env: Env, stream_id: u64) {
+        let stream = read_stream(&env, stream_id);
+
+        // Require that the sender authorizes the cancellation
+        stream.sender.require_auth();
+
+        // Split the deposit pro rata at the current time
+        let streamed = streamed_amount(&env, &stream);
+        let recipient_amount = math::checked(&env, math::sub(streamed, stream.withdrawn));
+        let sender_amount = math::checked(&env, math::sub(stream.deposit, streamed));
+
+        env.storage().persistent().remove(&DataKey::Stream(stream_id));
+        tvl::adjust_reserved(&env, &stream.token, -(stream.deposit - stream.withdrawn));
+
+        if recipient_amount > 0 {
+            deliver(&env, &stream.token, &stream.recipient, recipient_amount);
+        }
+        if sender_amount > 0 {
+            token::Client::new(&env, &stream.token).transfer(
+                &env.current_contract_address(),
+                &stream.sender,
+                &sender_amount,
+            );
+        }
+
+        events::publish_stream_cancel(&env, stream_id, stream.sender, stream.token, recipient_amount, sender_amount);
+    }
+
+    /// Returns a stream.
+    pub fn get_stream(env: Env, stream_id: u64) -> Stream {
+        read_stream(&env, stream_id)
+    }
+
+    /// Returns the amount the recipient can currently withdraw from a stream.
+    pub fn stream_withdrawable(env: Env, stream_id: u64) -> i128 {
+        let stream = read_stream(&env, stream_id);
+        streamed_amount(&env, &stream) - stream.withdrawn
+    }
+}
+
+/// Helper function computing the amount of a stream accrued at the current time, withdrawn or not.
+fn streamed_amount(env: &Env, stream: &Stream) -> i128 {
+    let now = current_time(env);
+
+    if now <= stream.start {
+        0
+    } else if now >= stream.stop {
+        stream.deposit
+    } else {
+        let elapsed = (now - stream.start) as i128;
+        math::checked(env, math::mul_div(stream.deposit, elapsed, (stream.stop - stream.start) as i128))
+    }
+}
+
+/// Helper function to read a stream; panics if it does not exist or has ended.
+fn read_stream(env: &Env, stream_id: u64) -> Stream {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Stream(stream_id))
+        .expect("stream does not exist")
+}
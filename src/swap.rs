@@ -0,0 +1,69 @@
+use soroban_sdk::{contractclient, contractimpl, token, Address, Env};
+
+use crate::{
+    events, pay_full_claim, protocol_fee, read_balance_hot, read_config, ClaimableBalanceContract,
+    ClaimableBalanceContractArgs, ClaimableBalanceContractClient,
+};
+
+/// Interface that AMM routers must implement to swap claims into another asset.
+#[contractclient(name = "SwapRouterClient")]
+pub trait SwapRouter {
+    /// Swaps `amount_in` of `token_in`, transferred to the router beforehand, into at least `min_out` of
+    /// `token_out` sent to `to`. Returns the amount sent.
+    fn swap(env: Env, token_in: Address, token_out: Address, amount_in: i128, min_out: i128, to: Address) -> i128;
+}
+
+#[contractimpl]
+impl ClaimableBalanceContract {
+    /// Claims a balance like `claim`, but pays the claimant in `payout_token` by swapping the claim, net of the
+    /// protocol fee, through the configured router. Fails unless at least `min_out` arrives; returns the amount
+    /// received.
+    pub fn claim_swapped(
+        env: Env,
+        claimant: Address,
+        balance_id: u64,
+        payout_token: Address,
+        min_out: i128,
+    ) -> i128 {
+        // Require that claimant authorizes the claim and the slippage bound
+        claimant.require_auth();
+
+        let router = read_config(&env).swap_router.expect("no swap router is configured");
+        let claimable_balance = read_balance_hot(&env, balance_id);
+        if payout_token == claimable_balance.token {
+            panic!("payout token must differ from the locked token");
+        }
+        if !claimable_balance.basket.is_empty() {
+            panic!("basket balances cannot be swapped");
+        }
+        if min_out <= 0 {
+            panic!("minimum out must be positive");
+        }
+
+        // Claim into this contract, which keeps what is left after the protocol fee
+        let contract = env.current_contract_address();
+        let payout = pay_full_claim(&env, balance_id, claimant.clone(), contract.clone(), None);
+        let fee = protocol_fee(&env, &claimable_balance.token, &claimant, payout).map_or(0, |(_, fee)| fee);
+        let amount_in = payout - fee;
+
+        // Hand the claim to the router and check what actually reached the claimant
+        let payout_client = token::Client::new(&env, &payout_token);
+        let balance_before = payout_client.balance(&claimant);
+        token::Client::new(&env, &claimable_balance.token).transfer(&contract, &router, &amount_in);
+        SwapRouterClient::new(&env, &router).swap(
+            &claimable_balance.token,
+            &payout_token,
+            &amount_in,
+            &min_out,
+            &claimant,
+        );
+        let received = payout_client.balance(&claimant) - balance_before;
+        if received < min_out {
+            panic!("swap returned less than the minimum out");
+        }
+
+        events::publish_swap(&env, balance_id, claimant, claimable_balance.token, amount_in, payout_token, received);
+
+        received
+    }
+}
@@ -0,0 +1,39 @@
+use soroban_sdk::{contractimpl, Env};
+
+use crate::{
+    current_time, events, read_balance, read_config, return_balance, timelock_core, ClaimableBalance,
+    ClaimableBalanceContract, ClaimableBalanceContractArgs, ClaimableBalanceContractClient,
+};
+
+#[contractimpl]
+impl ClaimableBalanceContract {
+    /// Sends what is left of a balance to the configured treasury once its claim window closed more than
+    /// `sweep_delay` seconds ago; until then the depositor can still have it refunded. Callable by anyone;
+    /// returns the amount swept.
+    pub fn sweep_expired(env: Env, balance_id: u64) -> i128 {
+        let treasury = read_config(&env).treasury.expect("no treasury is configured");
+        let claimable_balance = read_balance(&env, balance_id);
+        if current_time(&env) <= sweepable_after(&env, &claimable_balance) {
+            panic!("balance cannot be swept yet");
+        }
+
+        // Funds can only go to the treasury, so anyone may trigger the sweep
+        let swept = return_balance(&env, balance_id, &claimable_balance, &treasury);
+
+        events::publish_sweep(&env, balance_id, treasury, claimable_balance.token, swept);
+
+        swept
+    }
+
+    /// Returns the timestamp after which a balance can be swept to the treasury; `u64::MAX` for balances whose
+    /// claim window never closes.
+    pub fn sweepable_after(env: Env, balance_id: u64) -> u64 {
+        sweepable_after(&env, &read_balance(&env, balance_id))
+    }
+}
+
+/// Helper function computing when the claim window of a balance closed, plus the configured sweep delay.
+fn sweepable_after(env: &Env, claimable_balance: &ClaimableBalance) -> u64 {
+    timelock_core::time_bound_end(&claimable_balance.time_bound)
+        .map_or(u64::MAX, |end| end.saturating_add(read_config(env).sweep_delay))
+}
@@ -2,31 +2,312 @@
 extern crate std;
 
 use super::*;
-use soroban_sdk::testutils::{Address as _, AuthorizedFunction, AuthorizedInvocation, Ledger};
-use soroban_sdk::{symbol_short, token, vec, Address, Env, IntoVal};
+use soroban_sdk::testutils::{
+    Address as _, AuthorizedFunction, AuthorizedInvocation, Events, IssuerFlags, Ledger,
+};
+use soroban_sdk::auth::{Context, CustomAccountInterface};
+use soroban_sdk::crypto::Hash;
+use soroban_sdk::xdr::{
+    InvokeContractArgs, SorobanAddressCredentials, SorobanAuthorizationEntry, SorobanAuthorizedFunction,
+    SorobanAuthorizedInvocation, SorobanCredentials, VecM,
+};
+use soroban_sdk::{BytesN, TryFromVal};
+use soroban_sdk::{
+    contract, contractimpl, contracttype, symbol_short, token, vec, Address, Bytes, Env, Error, IntoVal,
+    String, Symbol, Val,
+};
+use testutils::{create_claimable_balance_contract, create_token_contract, TimeTravel};
 use token::Client as TokenClient;
 use token::StellarAssetClient as TokenAdminClient;
 
-/// Utility function to create a token contract and return both its client and admin interfaces.
-fn create_token_contract<'a>(e: &Env, admin: &Address) -> (TokenClient<'a>, TokenAdminClient<'a>) {
-    let sac = e.register_stellar_asset_contract_v2(admin.clone());
-    (
-        token::Client::new(e, &sac.address()),
-        token::StellarAssetClient::new(e, &sac.address()),
-    )
+/// Mock compliance hook denying every claim with a stored reason code (0 allows the claim), and claims above a
+/// stored maximum amount with code 99.
+#[contract]
+struct MockClaimHook;
+
+#[contractimpl]
+impl MockClaimHook {
+    pub fn set_code(env: Env, code: u32) {
+        env.storage().instance().set(&symbol_short!("code"), &code);
+    }
+
+    pub fn set_max_amount(env: Env, max_amount: i128) {
+        env.storage().instance().set(&symbol_short!("max"), &max_amount);
+    }
+
+    pub fn check_claim(
+        env: Env,
+        _claimant: Address,
+        _token: Address,
+        amount: i128,
+    ) -> Option<DenialReason> {
+        let code: u32 = env.storage().instance().get(&symbol_short!("code")).unwrap_or(0);
+        let max_amount: i128 = env.storage().instance().get(&symbol_short!("max")).unwrap_or(i128::MAX);
+        if amount > max_amount {
+            Some(DenialReason { code: 99 })
+        } else if code == 0 {
+            None
+        } else {
+            Some(DenialReason { code })
+        }
+    }
+}
+
+/// Mock time-attestation contract returning a stored timestamp.
+#[contract]
+struct MockTimeSource;
+
+#[contractimpl]
+impl MockTimeSource {
+    pub fn set_now(env: Env, now: u64) {
+        env.storage().instance().set(&symbol_short!("now"), &now);
+    }
+
+    pub fn now(env: Env) -> u64 {
+        env.storage().instance().get(&symbol_short!("now")).unwrap()
+    }
+}
+
+/// Mock contract used as the target of scheduled operations.
+#[contract]
+struct MockTarget;
+
+#[contractimpl]
+impl MockTarget {
+    pub fn set_value(env: Env, value: u32) -> u32 {
+        env.storage().instance().set(&symbol_short!("value"), &value);
+        value
+    }
+
+    pub fn value(env: Env) -> u32 {
+        env.storage().instance().get(&symbol_short!("value")).unwrap_or(0)
+    }
+}
+
+/// Mock AMM router paying out twice the amount it received, from liquidity minted to it beforehand.
+#[contract]
+struct MockRouter;
+
+#[contractimpl]
+impl SwapRouter for MockRouter {
+    fn swap(env: Env, _token_in: Address, token_out: Address, amount_in: i128, min_out: i128, to: Address) -> i128 {
+        let amount_out = amount_in * 2;
+        if amount_out < min_out {
+            panic!("insufficient output amount");
+        }
+        TokenClient::new(&env, &token_out).transfer(&env.current_contract_address(), &to, &amount_out);
+        amount_out
+    }
+}
+
+/// Mock yield strategy crediting one share per token and redeeming each share for 1.1 tokens, or the set return,
+/// from liquidity minted to it beforehand.
+#[contract]
+struct MockStrategy;
+
+#[contractimpl]
+impl MockStrategy {
+    pub fn set_return_bps(env: Env, return_bps: i128) {
+        env.storage().instance().set(&symbol_short!("bps"), &return_bps);
+    }
+}
+
+#[contractimpl]
+impl Strategy for MockStrategy {
+    fn invest(_env: Env, _owner: Address, _token: Address, amount: i128) -> i128 {
+        amount
+    }
+
+    fn redeem(env: Env, owner: Address, token: Address, shares: i128) -> i128 {
+        owner.require_auth();
+        let return_bps: i128 = env.storage().instance().get(&symbol_short!("bps")).unwrap_or(11_000);
+        let amount = shares * return_bps / 10_000;
+        TokenClient::new(&env, &token).transfer(&env.current_contract_address(), &owner, &amount);
+        amount
+    }
+}
+
+/// Mock smart wallet authorizing calls signed with its PIN, and consenting to claims while it accepts them.
+#[contract]
+struct MockWallet;
+
+#[contractimpl]
+impl MockWallet {
+    pub fn __constructor(env: Env, pin: u32, accepts: bool) {
+        env.storage().instance().set(&symbol_short!("pin"), &pin);
+        env.storage().instance().set(&symbol_short!("accepts"), &accepts);
+    }
+}
+
+#[contractimpl]
+impl CustomAccountInterface for MockWallet {
+    type Signature = u32;
+    type Error = Error;
+
+    fn __check_auth(env: Env, _payload: Hash<32>, pin: u32, _contexts: Vec<Context>) -> Result<(), Error> {
+        if pin != env.storage().instance().get::<_, u32>(&symbol_short!("pin")).unwrap() {
+            return Err(Error::from_contract_error(1));
+        }
+        Ok(())
+    }
+}
+
+#[contractimpl]
+impl ClaimantContract for MockWallet {
+    fn accept_claim(env: Env, _balance_id: u64, _token: Address) -> bool {
+        env.storage().instance().get(&symbol_short!("accepts")).unwrap()
+    }
+}
+
+/// Mock keeper network driving any `Schedulable` contract.
+#[contract]
+struct MockScheduler;
+
+#[contractimpl]
+impl MockScheduler {
+    /// Performs the task if it is due, returning whether it was performed.
+    pub fn tick(env: Env, target: Address, task: Task) -> bool {
+        let schedulable = SchedulableClient::new(&env, &target);
+        match schedulable.next_action_time(&task) {
+            Some(time) if time <= env.ledger().timestamp() => {
+                schedulable.perform(&env.current_contract_address(), &task);
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Mock token burning 1% of every transfer.
+#[contract]
+struct MockFeeToken;
+
+#[contractimpl]
+impl MockFeeToken {
+    pub fn mint(env: Env, to: Address, amount: i128) {
+        let balance = Self::balance(env.clone(), to.clone());
+        env.storage().persistent().set(&to, &(balance + amount));
+    }
+
+    pub fn balance(env: Env, id: Address) -> i128 {
+        env.storage().persistent().get(&id).unwrap_or(0)
+    }
+
+    pub fn transfer(env: Env, from: Address, to: Address, amount: i128) {
+        from.require_auth();
+        let from_balance = Self::balance(env.clone(), from.clone());
+        env.storage().persistent().set(&from, &(from_balance - amount));
+        Self::mint(env, to, amount - amount / 100);
+    }
+}
+
+/// Mock gate contract admitting an allowlist of claimants.
+#[contract]
+struct MockGate;
+
+#[contractimpl]
+impl MockGate {
+    pub fn allow(env: Env, claimant: Address) {
+        env.storage().instance().set(&claimant, &());
+    }
+
+    pub fn check(env: Env, claimant: Address, _balance_id: u64) -> bool {
+        env.storage().instance().has(&claimant)
+    }
+}
+
+/// Mock Reflector-style oracle reporting a stored price for every asset.
+#[contract]
+struct MockOracle;
+
+#[contractimpl]
+impl MockOracle {
+    pub fn set_price(env: Env, price: i128) {
+        env.storage().instance().set(&symbol_short!("price"), &price);
+    }
+
+    pub fn lastprice(env: Env, _asset: OracleAsset) -> Option<PriceData> {
+        let price: i128 = env.storage().instance().get(&symbol_short!("price"))?;
+        Some(PriceData {
+            price,
+            timestamp: env.ledger().timestamp(),
+        })
+    }
+}
+
+/// Storage keys of the original single-balance deployment, used to seed a legacy layout.
+#[contracttype]
+enum LegacyDataKey {
+    Init,
+    Balance,
+}
+
+/// Balance layout of the original single-balance deployment.
+#[contracttype]
+struct LegacyClaimableBalance {
+    token: Address,
+    amount: i128,
+    claimants: soroban_sdk::Vec<Address>,
+    time_bound: TimeBound,
+}
+
+/// Utility function returning a valid configuration used by default in tests.
+fn default_config(env: &Env) -> Config {
+    Config {
+        max_claimants: 10,
+        max_active_balances: 0,
+        min_lock_duration: 0,
+        max_lock_duration: 365 * 24 * 60 * 60,
+        claim_hook: None,
+        native_token: None,
+        rent_bump_fee: 0,
+        time_source: None,
+        max_time_divergence: 0,
+        treasury: None,
+        sweep_delay: 0,
+        min_delay: MIN_DELAY,
+        grace_period: 0,
+        upgrade_delay: 0,
+        freeze_delay: 0,
+        fee_bps: 0,
+        fee_collector: None,
+        fee_exempt: Vec::new(env),
+        swap_router: None,
+        strategies: Vec::new(env),
+        yield_recipient: YieldRecipient::Depositor,
+    }
+}
+
+/// Utility function granting roles to an account and waiting out the delay before they take effect.
+fn grant_roles(test: &ClaimableBalanceTest, roles: &[Role], account: &Address) {
+    for role in roles {
+        test.contract.grant_role(&test.admin, role, account);
+    }
+    test.env.ledger().set_timestamp(test.env.ledger().timestamp() + MIN_DELAY);
+}
+
+/// Utility function returning the last event published during the latest invocation.
+fn last_event(e: &Env) -> soroban_sdk::Vec<(Address, soroban_sdk::Vec<Val>, Val)> {
+    let events = e.events().all();
+    events.slice(events.len() - 1..)
 }
 
-/// Utility function to register the claimable balance contract and return its client.
-fn create_claimable_balance_contract<'a>(e: &Env) -> ClaimableBalanceContractClient<'a> {
-    ClaimableBalanceContractClient::new(e, &e.register(ClaimableBalanceContract, ()))
+/// Utility function returning the data of the first event of the latest invocation with the given name.
+fn find_event_data(e: &Env, name: Symbol) -> Option<Val> {
+    e.events().all().iter().find_map(|(_, topics, data)| {
+        let first = Symbol::try_from_val(e, &topics.first()?).ok()?;
+        (first == name).then_some(data)
+    })
 }
 
 /// Struct to encapsulate and organize all resources used in tests.
 struct ClaimableBalanceTest<'a> {
     env: Env,
+    admin: Address,
     deposit_address: Address,
     claim_addresses: [Address; 3],
     token: TokenClient<'a>,
+    native_token: TokenClient<'a>,
     contract: ClaimableBalanceContractClient<'a>,
 }
 
@@ -55,12 +336,27 @@ impl<'a> ClaimableBalanceTest<'a> {
         let (token, token_admin_client) = create_token_contract(&env, &token_admin);
         token_admin_client.mint(&deposit_address, &1000);
 
-        let contract = create_claimable_balance_contract(&env);
+        // Stand-in for the native asset contract used for rent budgets
+        let (native_token, native_token_admin_client) = create_token_contract(&env, &token_admin);
+        native_token_admin_client.mint(&deposit_address, &1000);
+
+        let admin = Address::generate(&env);
+        let contract = create_claimable_balance_contract(
+            &env,
+            &admin,
+            Config {
+                native_token: Some(native_token.address.clone()),
+                ..default_config(&env)
+            },
+        );
+
         ClaimableBalanceTest {
             env,
+            admin,
             deposit_address,
             claim_addresses,
             token,
+            native_token,
             contract,
         }
     }
@@ -71,7 +367,7 @@ fn test_deposit_and_claim() {
     let test = ClaimableBalanceTest::setup();
 
     // Deposit 800 tokens with time bound "before 12346"
-    test.contract.deposit(
+    let balance_id = test.contract.deposit(
         &test.deposit_address,
         &test.token.address,
         &800,
@@ -134,7 +430,7 @@ fn test_deposit_and_claim() {
     assert_eq!(test.token.balance(&test.claim_addresses[1]), 0);
 
     // Perform claim
-    test.contract.claim(&test.claim_addresses[1]);
+    test.contract.claim(&test.claim_addresses[1], &balance_id);
 
     // Verify authorization and final balances
     assert_eq!(
@@ -145,7 +441,7 @@ fn test_deposit_and_claim() {
                 function: AuthorizedFunction::Contract((
                     test.contract.address.clone(),
                     symbol_short!("claim"),
-                    (test.claim_addresses[1].clone(),).into_val(&test.env),
+                    (test.claim_addresses[1].clone(), balance_id).into_val(&test.env),
                 )),
                 sub_invocations: std::vec![]
             }
@@ -158,33 +454,42 @@ fn test_deposit_and_claim() {
 }
 
 #[test]
-#[should_panic(expected = "contract has been already initialized")]
-fn test_double_deposit_not_possible() {
+fn test_deposits_are_held_as_separate_balances() {
     let test = ClaimableBalanceTest::setup();
 
-    // First deposit works
-    test.contract.deposit(
+    // Each deposit gets its own id
+    let first_id = test.contract.deposit(
         &test.deposit_address,
         &test.token.address,
-        &1,
+        &100,
         &vec![&test.env, test.claim_addresses[0].clone()],
         &TimeBound {
             kind: TimeBoundKind::Before,
             timestamp: 12346,
         },
     );
-
-    // Second deposit should panic (already initialized)
-    test.contract.deposit(
+    let second_id = test.contract.deposit(
         &test.deposit_address,
         &test.token.address,
-        &1,
-        &vec![&test.env, test.claim_addresses[0].clone()],
+        &300,
+        &vec![&test.env, test.claim_addresses[1].clone()],
         &TimeBound {
             kind: TimeBoundKind::Before,
             timestamp: 12346,
         },
     );
+    assert_eq!((first_id, second_id), (0, 1));
+    assert_eq!(test.token.balance(&test.contract.address), 400);
+
+    // Claimants can only claim their own balance
+    assert!(test
+        .contract
+        .try_claim(&test.claim_addresses[0], &second_id)
+        .is_err());
+    test.contract.claim(&test.claim_addresses[1], &second_id);
+    test.contract.claim(&test.claim_addresses[0], &first_id);
+    assert_eq!(test.token.balance(&test.claim_addresses[0]), 100);
+    assert_eq!(test.token.balance(&test.claim_addresses[1]), 300);
 }
 
 #[test]
@@ -193,7 +498,7 @@ fn test_unauthorized_claim_not_possible() {
     let test = ClaimableBalanceTest::setup();
 
     // Setup with claimants 0 and 1
-    test.contract.deposit(
+    let balance_id = test.contract.deposit(
         &test.deposit_address,
         &test.token.address,
         &800,
@@ -209,7 +514,7 @@ fn test_unauthorized_claim_not_possible() {
     );
 
     // Claim attempt by address 2 should panic
-    test.contract.claim(&test.claim_addresses[2]);
+    test.contract.claim(&test.claim_addresses[2], &balance_id);
 }
 
 #[test]
@@ -218,7 +523,7 @@ fn test_out_of_time_bound_claim_not_possible() {
     let test = ClaimableBalanceTest::setup();
 
     // Deposit requires AFTER 12346, but ledger timestamp is 12345 -> should fail
-    test.contract.deposit(
+    let balance_id = test.contract.deposit(
         &test.deposit_address,
         &test.token.address,
         &800,
@@ -229,7 +534,7 @@ fn test_out_of_time_bound_claim_not_possible() {
         },
     );
 
-    test.contract.claim(&test.claim_addresses[0]); // Should panic due to time condition
+    test.contract.claim(&test.claim_addresses[0], &balance_id); // Should panic due to time condition
 }
 
 #[test]
@@ -238,7 +543,7 @@ fn test_double_claim_not_possible() {
     let test = ClaimableBalanceTest::setup();
 
     // Valid deposit and claim
-    test.contract.deposit(
+    let balance_id = test.contract.deposit(
         &test.deposit_address,
         &test.token.address,
         &800,
@@ -249,42 +554,5751 @@ fn test_double_claim_not_possible() {
         },
     );
 
-    test.contract.claim(&test.claim_addresses[0]);
+    test.contract.claim(&test.claim_addresses[0], &balance_id);
     assert_eq!(test.token.balance(&test.claim_addresses[0]), 800);
 
     // Second claim should panic as balance was already claimed
-    test.contract.claim(&test.claim_addresses[0]);
+    test.contract.claim(&test.claim_addresses[0], &balance_id);
 }
 
 #[test]
-#[should_panic(expected = "contract has been already initialized")]
-fn test_deposit_after_claim_not_possible() {
+#[should_panic(expected = "balance does not exist")]
+fn test_claim_of_settled_balance_not_possible() {
     let test = ClaimableBalanceTest::setup();
 
     // Deposit and valid claim
-    test.contract.deposit(
+    let balance_id = test.contract.deposit(
         &test.deposit_address,
         &test.token.address,
         &800,
         &vec![&test.env, test.claim_addresses[0].clone()],
         &TimeBound {
             kind: TimeBoundKind::After,
-            timestamp: 12344,
+            timestamp: 12345,
         },
     );
 
-    test.contract.claim(&test.claim_addresses[0]);
+    test.contract.claim(&test.claim_addresses[0], &balance_id);
     assert_eq!(test.token.balance(&test.claim_addresses[0]), 800);
 
-    // Re-deposit attempt should panic due to one-time init guard
-    test.contract.deposit(
+    // A new deposit gets a fresh id; the settled balance stays gone
+    let new_balance_id = test.contract.deposit(
         &test.deposit_address,
         &test.token.address,
         &200,
         &vec![&test.env, test.claim_addresses[0].clone()],
         &TimeBound {
             kind: TimeBoundKind::After,
-            timestamp: 12344,
+            timestamp: 12345,
+        },
+    );
+    assert_ne!(new_balance_id, balance_id);
+
+    test.contract.claim(&test.claim_addresses[0], &balance_id);
+}
+
+#[test]
+fn test_update_config() {
+    let test = ClaimableBalanceTest::setup();
+
+    let config = Config {
+        max_claimants: 1,
+        min_lock_duration: 10,
+        max_lock_duration: 100,
+        ..test.contract.get_config()
+    };
+    test.contract.update_config(&config);
+
+    // Only the admin had to authorize the update
+    assert_eq!(
+        test.env.auths(),
+        [(
+            test.admin.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    test.contract.address.clone(),
+                    Symbol::new(&test.env, "update_config"),
+                    (config.clone(),).into_val(&test.env),
+                )),
+                sub_invocations: std::vec![]
+            }
+        ),]
+    );
+
+    let stored = test.contract.get_config();
+    assert_eq!(stored.max_claimants, 1);
+    assert_eq!(stored.min_lock_duration, 10);
+    assert_eq!(stored.max_lock_duration, 100);
+}
+
+#[test]
+#[should_panic(expected = "min lock duration must be lower than max lock duration")]
+fn test_update_config_rejects_inconsistent_durations() {
+    let test = ClaimableBalanceTest::setup();
+
+    test.contract.update_config(&Config {
+        min_lock_duration: 100,
+        max_lock_duration: 100,
+        ..test.contract.get_config()
+    });
+}
+
+#[test]
+#[should_panic(expected = "max claimants must be positive")]
+fn test_constructor_rejects_invalid_config() {
+    let env = Env::default();
+
+    create_claimable_balance_contract(
+        &env,
+        &Address::generate(&env),
+        Config {
+            max_claimants: 0,
+            ..default_config(&env)
         },
     );
 }
+
+#[test]
+fn test_deposit_respects_max_lock_duration() {
+    let test = ClaimableBalanceTest::setup();
+
+    test.contract.update_config(&Config {
+        max_lock_duration: 100,
+        ..test.contract.get_config()
+    });
+
+    // Unlock lies 101 seconds in the future, beyond the configured maximum
+    assert_eq!(
+        test.contract.try_deposit(
+            &test.deposit_address,
+            &test.token.address,
+            &800,
+            &vec![&test.env, test.claim_addresses[0].clone()],
+            &TimeBound {
+                kind: TimeBoundKind::After,
+                timestamp: 12446,
+            },
+        ),
+        Err(Ok(DepositError::LockDurationOutOfBounds.into()))
+    );
+}
+
+#[test]
+fn test_deposit_and_claim_emit_events() {
+    let test = ClaimableBalanceTest::setup();
+
+    let balance_id = test.contract.deposit(
+        &test.deposit_address,
+        &test.token.address,
+        &800,
+        &vec![&test.env, test.claim_addresses[0].clone()],
+        &TimeBound {
+            kind: TimeBoundKind::Before,
+            timestamp: 12346,
+        },
+    );
+
+    // Deposit event carries the schema version, balance id, depositor and token as topics, a `DepositEvent` as data
+    assert_eq!(
+        last_event(&test.env),
+        vec![
+            &test.env,
+            (
+                test.contract.address.clone(),
+                (
+                    symbol_short!("deposit"),
+                    EVENT_VERSION,
+                    balance_id,
+                    test.deposit_address.clone(),
+                    test.token.address.clone(),
+                )
+                    .into_val(&test.env),
+                DepositEvent {
+                    amount: 800,
+                    unlock_time: 12346,
+                    timestamp: 12345,
+                }
+                .into_val(&test.env),
+            )
+        ]
+    );
+
+    test.contract.claim(&test.claim_addresses[0], &balance_id);
+
+    // Claim event carries the schema version, balance id, claimant and token as topics, a `ClaimEvent` as data
+    assert_eq!(
+        last_event(&test.env),
+        vec![
+            &test.env,
+            (
+                test.contract.address.clone(),
+                (
+                    symbol_short!("claim"),
+                    EVENT_VERSION,
+                    balance_id,
+                    test.claim_addresses[0].clone(),
+                    test.token.address.clone(),
+                )
+                    .into_val(&test.env),
+                ClaimEvent {
+                    amount: 800,
+                    timestamp: 12345,
+                    destination: test.claim_addresses[0].clone(),
+                }
+                .into_val(&test.env),
+            )
+        ]
+    );
+}
+
+#[test]
+fn test_claim_hook_denial_surfaces_reason_code() {
+    let test = ClaimableBalanceTest::setup();
+
+    let hook = MockClaimHookClient::new(&test.env, &test.env.register(MockClaimHook, ()));
+    hook.set_code(&42);
+    test.contract.update_config(&Config {
+        claim_hook: Some(hook.address.clone()),
+        ..test.contract.get_config()
+    });
+
+    let balance_id = test.contract.deposit(
+        &test.deposit_address,
+        &test.token.address,
+        &800,
+        &vec![&test.env, test.claim_addresses[0].clone()],
+        &TimeBound {
+            kind: TimeBoundKind::Before,
+            timestamp: 12346,
+        },
+    );
+
+    // The hook's reason code is returned as the contract error code, offset into its own range
+    assert_eq!(
+        test.contract.try_claim(&test.claim_addresses[0], &balance_id),
+        Err(Ok(Error::from_contract_error(HOOK_DENIAL_BASE + 42)))
+    );
+
+    // Codes a hook shares with the contract's own errors stay distinguishable
+    hook.set_code(&(DepositError::InvalidAmount as u32));
+    let result = test.contract.try_claim(&test.claim_addresses[0], &balance_id);
+    assert_ne!(result, Err(Ok(DepositError::InvalidAmount.into())));
+    assert_eq!(result, Err(Ok(Error::from_contract_error(HOOK_DENIAL_BASE + 1))));
+    hook.set_code(&(MathError::Overflow as u32));
+    assert_ne!(
+        test.contract.try_claim(&test.claim_addresses[0], &balance_id),
+        Err(Ok(MathError::Overflow.into()))
+    );
+    assert_eq!(test.token.balance(&test.contract.address), 800);
+
+    // Once the hook allows the claim, it goes through
+    hook.set_code(&0);
+    test.contract.claim(&test.claim_addresses[0], &balance_id);
+    assert_eq!(test.token.balance(&test.claim_addresses[0]), 800);
+}
+
+#[test]
+fn test_claim_within_time_window() {
+    let test = ClaimableBalanceTest::setup();
+
+    // Claimable from 12346 to 12350 inclusive
+    let balance_id = test.contract.deposit(
+        &test.deposit_address,
+        &test.token.address,
+        &800,
+        &vec![&test.env, test.claim_addresses[0].clone()],
+        &TimeBound {
+            kind: TimeBoundKind::Between(12350),
+            timestamp: 12346,
+        },
+    );
+
+    // Too early
+    assert!(test.contract.try_claim(&test.claim_addresses[0], &balance_id).is_err());
+
+    // Window end is inclusive
+    test.env.ledger().with_mut(|li| li.timestamp = 12350);
+    test.contract.claim(&test.claim_addresses[0], &balance_id);
+    assert_eq!(test.token.balance(&test.claim_addresses[0]), 800);
+}
+
+#[test]
+#[should_panic(expected = "time predicate is not fulfilled")]
+fn test_claim_after_time_window_not_possible() {
+    let test = ClaimableBalanceTest::setup();
+
+    let balance_id = test.contract.deposit(
+        &test.deposit_address,
+        &test.token.address,
+        &800,
+        &vec![&test.env, test.claim_addresses[0].clone()],
+        &TimeBound {
+            kind: TimeBoundKind::Between(12350),
+            timestamp: 12346,
+        },
+    );
+
+    test.env.ledger().with_mut(|li| li.timestamp = 12351);
+    test.contract.claim(&test.claim_addresses[0], &balance_id);
+}
+
+#[test]
+#[should_panic(expected = "time window start must be before its end")]
+fn test_deposit_with_inverted_time_window_not_possible() {
+    let test = ClaimableBalanceTest::setup();
+
+    test.contract.deposit(
+        &test.deposit_address,
+        &test.token.address,
+        &800,
+        &vec![&test.env, test.claim_addresses[0].clone()],
+        &TimeBound {
+            kind: TimeBoundKind::Between(12346),
+            timestamp: 12346,
+        },
+    );
+}
+
+#[test]
+fn test_rent_budget_pays_keepers_and_is_refunded() {
+    let test = ClaimableBalanceTest::setup();
+    test.contract.update_config(&Config {
+        rent_bump_fee: 10,
+        ..test.contract.get_config()
+    });
+
+    let balance_id = test.contract.deposit(
+        &test.deposit_address,
+        &test.token.address,
+        &800,
+        &vec![&test.env, test.claim_addresses[0].clone()],
+        &TimeBound {
+            kind: TimeBoundKind::Before,
+            timestamp: 12346,
+        },
+    );
+
+    test.contract.fund_rent(&test.deposit_address, &balance_id, &100);
+    assert_eq!(test.contract.rent_budget(&balance_id), 100);
+    assert_eq!(test.native_token.balance(&test.deposit_address), 900);
+
+    // A keeper is paid for bumping the TTL
+    let keeper = Address::generate(&test.env);
+    test.contract.bump_rent(&keeper, &balance_id);
+    assert_eq!(test.native_token.balance(&keeper), 10);
+    assert_eq!(test.contract.rent_budget(&balance_id), 90);
+
+    // Bumps are rate-limited
+    assert!(test.contract.try_bump_rent(&keeper, &balance_id).is_err());
+    test.env.ledger().with_mut(|li| li.sequence_number += 7 * 17280);
+    test.contract.bump_rent(&keeper, &balance_id);
+    assert_eq!(test.native_token.balance(&keeper), 20);
+
+    // Leftover budget goes back to the depositor on claim
+    test.contract.claim(&test.claim_addresses[0], &balance_id);
+    assert_eq!(test.contract.rent_budget(&balance_id), 0);
+    assert_eq!(test.native_token.balance(&test.deposit_address), 980);
+}
+
+#[test]
+#[should_panic(expected = "only the depositor can fund rent")]
+fn test_fund_rent_by_non_depositor_not_possible() {
+    let test = ClaimableBalanceTest::setup();
+
+    let balance_id = test.contract.deposit(
+        &test.deposit_address,
+        &test.token.address,
+        &800,
+        &vec![&test.env, test.claim_addresses[0].clone()],
+        &TimeBound {
+            kind: TimeBoundKind::Before,
+            timestamp: 12346,
+        },
+    );
+
+    test.contract.fund_rent(&test.claim_addresses[0], &balance_id, &100);
+}
+
+#[test]
+fn test_linear_vesting_partial_claims() {
+    let test = ClaimableBalanceTest::setup();
+
+    // Vests from 12345 to 12445 with a cliff at 12365
+    let balance_id = test.contract.deposit_with_options(
+        &test.deposit_address,
+        &test.token.address,
+        &800,
+        &vec![&test.env, test.claim_addresses[0].clone()],
+        &TimeBound {
+            kind: TimeBoundKind::After,
+            timestamp: 12345,
+        },
+        &DepositOptions {
+            vesting: Vesting::Linear(VestingSchedule {
+                start: 12345,
+                cliff: 12365,
+                end: 12445,
+            }),
+            ..Default::default()
+        },
+    );
+
+    // Nothing can be claimed before the cliff
+    test.env.ledger().with_mut(|li| li.timestamp = 12364);
+    assert_eq!(test.contract.vested_amount(&balance_id), 0);
+    assert!(test.contract.try_claim_vested(&test.claim_addresses[0], &balance_id).is_err());
+
+    // A quarter of the schedule has elapsed
+    test.env.ledger().with_mut(|li| li.timestamp = 12370);
+    assert_eq!(test.contract.vested_amount(&balance_id), 200);
+    assert_eq!(test.contract.claim_vested(&test.claim_addresses[0], &balance_id), 200);
+
+    // Only the newly vested part is paid on the next claim
+    test.env.ledger().with_mut(|li| li.timestamp = 12395);
+    assert_eq!(test.contract.claim_vested(&test.claim_addresses[0], &balance_id), 200);
+    assert_eq!(test.token.balance(&test.claim_addresses[0]), 400);
+
+    // The remainder is paid once fully vested
+    test.env.ledger().with_mut(|li| li.timestamp = 12500);
+    assert_eq!(test.contract.claim_vested(&test.claim_addresses[0], &balance_id), 400);
+    assert_eq!(test.token.balance(&test.claim_addresses[0]), 800);
+    assert_eq!(test.token.balance(&test.contract.address), 0);
+}
+
+#[test]
+#[should_panic(expected = "balance is vesting, use claim_vested")]
+fn test_full_claim_of_vesting_balance_not_possible() {
+    let test = ClaimableBalanceTest::setup();
+
+    let balance_id = test.contract.deposit_with_options(
+        &test.deposit_address,
+        &test.token.address,
+        &800,
+        &vec![&test.env, test.claim_addresses[0].clone()],
+        &TimeBound {
+            kind: TimeBoundKind::After,
+            timestamp: 12345,
+        },
+        &DepositOptions {
+            vesting: Vesting::Linear(VestingSchedule {
+                start: 12345,
+                cliff: 12345,
+                end: 12445,
+            }),
+            ..Default::default()
+        },
+    );
+
+    test.contract.claim(&test.claim_addresses[0], &balance_id);
+}
+
+#[test]
+fn test_settle_claims_for_eligible_claimant() {
+    let test = ClaimableBalanceTest::setup();
+
+    let balance_id = test.contract.deposit(
+        &test.deposit_address,
+        &test.token.address,
+        &800,
+        &vec![&test.env, test.claim_addresses[0].clone()],
+        &TimeBound {
+            kind: TimeBoundKind::Before,
+            timestamp: 12346,
+        },
+    );
+
+    // The depositor cannot settle while the balance is still claimable
+    assert!(test.contract.try_settle(&test.deposit_address, &balance_id).is_err());
+
+    assert_eq!(
+        test.contract.settle(&test.claim_addresses[0], &balance_id),
+        Settlement::Claimed(800)
+    );
+    assert_eq!(test.token.balance(&test.claim_addresses[0]), 800);
+}
+
+#[test]
+fn test_settle_refunds_expired_balance_to_depositor() {
+    let test = ClaimableBalanceTest::setup();
+
+    let balance_id = test.contract.deposit(
+        &test.deposit_address,
+        &test.token.address,
+        &800,
+        &vec![&test.env, test.claim_addresses[0].clone()],
+        &TimeBound {
+            kind: TimeBoundKind::Before,
+            timestamp: 12346,
+        },
+    );
+
+    // Claim window has closed
+    test.env.ledger().with_mut(|li| li.timestamp = 12347);
+    assert!(test.contract.try_settle(&test.claim_addresses[0], &balance_id).is_err());
+
+    assert_eq!(
+        test.contract.settle(&test.deposit_address, &balance_id),
+        Settlement::Refunded(800)
+    );
+    assert_eq!(test.token.balance(&test.deposit_address), 1000);
+    assert_eq!(test.token.balance(&test.contract.address), 0);
+}
+
+#[test]
+fn test_allocated_claims_pay_each_share() {
+    let test = ClaimableBalanceTest::setup();
+
+    let balance_id = test.contract.deposit_allocated(
+        &test.deposit_address,
+        &test.token.address,
+        &vec![
+            &test.env,
+            (test.claim_addresses[0].clone(), 500_i128),
+            (test.claim_addresses[1].clone(), 300_i128),
+        ],
+        &TimeBound {
+            kind: TimeBoundKind::Before,
+            timestamp: 12346,
+        },
+    );
+    assert_eq!(test.token.balance(&test.contract.address), 800);
+
+    // Each claimant receives exactly its allocation
+    test.contract.claim(&test.claim_addresses[1], &balance_id);
+    assert_eq!(test.token.balance(&test.claim_addresses[1]), 300);
+
+    // A second claim by the same claimant does not touch the other share
+    assert!(test.contract.try_claim(&test.claim_addresses[1], &balance_id).is_err());
+    assert_eq!(test.token.balance(&test.contract.address), 500);
+
+    test.contract.claim(&test.claim_addresses[0], &balance_id);
+    assert_eq!(test.token.balance(&test.claim_addresses[0]), 500);
+    assert_eq!(test.token.balance(&test.contract.address), 0);
+}
+
+#[test]
+#[should_panic(expected = "duplicate claimant")]
+fn test_allocated_deposit_with_duplicate_claimant_not_possible() {
+    let test = ClaimableBalanceTest::setup();
+
+    test.contract.deposit_allocated(
+        &test.deposit_address,
+        &test.token.address,
+        &vec![
+            &test.env,
+            (test.claim_addresses[0].clone(), 500_i128),
+            (test.claim_addresses[0].clone(), 300_i128),
+        ],
+        &TimeBound {
+            kind: TimeBoundKind::Before,
+            timestamp: 12346,
+        },
+    );
+}
+
+#[test]
+fn test_attested_time_within_divergence_is_used() {
+    let test = ClaimableBalanceTest::setup();
+
+    let balance_id = test.contract.deposit(
+        &test.deposit_address,
+        &test.token.address,
+        &800,
+        &vec![&test.env, test.claim_addresses[0].clone()],
+        &TimeBound {
+            kind: TimeBoundKind::After,
+            timestamp: 12348,
+        },
+    );
+
+    let time_source = MockTimeSourceClient::new(&test.env, &test.env.register(MockTimeSource, ()));
+    time_source.set_now(&12350);
+    test.contract.update_config(&Config {
+        time_source: Some(time_source.address.clone()),
+        max_time_divergence: 10,
+        ..test.contract.get_config()
+    });
+
+    // Ledger says 12345, but the attested 12350 is trusted
+    test.contract.claim(&test.claim_addresses[0], &balance_id);
+    assert_eq!(test.token.balance(&test.claim_addresses[0]), 800);
+}
+
+#[test]
+#[should_panic(expected = "time predicate is not fulfilled")]
+fn test_attested_time_beyond_divergence_falls_back_to_ledger() {
+    let test = ClaimableBalanceTest::setup();
+
+    let time_source = MockTimeSourceClient::new(&test.env, &test.env.register(MockTimeSource, ()));
+    time_source.set_now(&12350);
+    test.contract.update_config(&Config {
+        time_source: Some(time_source.address.clone()),
+        max_time_divergence: 2,
+        ..test.contract.get_config()
+    });
+
+    let balance_id = test.contract.deposit(
+        &test.deposit_address,
+        &test.token.address,
+        &800,
+        &vec![&test.env, test.claim_addresses[0].clone()],
+        &TimeBound {
+            kind: TimeBoundKind::After,
+            timestamp: 12348,
+        },
+    );
+
+    // Attested time diverges too much, so the ledger timestamp 12345 applies
+    test.contract.claim(&test.claim_addresses[0], &balance_id);
+}
+
+#[test]
+fn test_gift_discovered_and_accepted() {
+    let test = ClaimableBalanceTest::setup();
+    let message = String::from_str(&test.env, "Happy birthday!");
+
+    let balance_id = test.contract.deposit_gift(
+        &test.deposit_address,
+        &test.token.address,
+        &800,
+        &test.claim_addresses[0],
+        &message,
+        &TimeBound {
+            kind: TimeBoundKind::After,
+            timestamp: 12345,
+        },
+    );
+
+    // The recipient's wallet can discover the gift and its greeting
+    assert_eq!(
+        last_event(&test.env),
+        vec![
+            &test.env,
+            (
+                test.contract.address.clone(),
+                (
+                    symbol_short!("gift"),
+                    balance_id,
+                    test.claim_addresses[0].clone(),
+                    test.deposit_address.clone(),
+                )
+                    .into_val(&test.env),
+                (test.token.address.clone(), 800_i128, message).into_val(&test.env),
+            )
+        ]
+    );
+
+    // Only the recipient can accept
+    assert!(test.contract.try_accept(&test.claim_addresses[1], &balance_id).is_err());
+    assert_eq!(test.contract.accept(&test.claim_addresses[0], &balance_id), 800);
+    assert_eq!(test.token.balance(&test.claim_addresses[0]), 800);
+
+    // Nothing is left to revoke
+    assert!(test.contract.try_revoke_gift(&test.deposit_address, &balance_id).is_err());
+}
+
+#[test]
+fn test_gift_declined_or_revoked_returns_to_sender() {
+    let test = ClaimableBalanceTest::setup();
+
+    let balance_id = test.contract.deposit_gift(
+        &test.deposit_address,
+        &test.token.address,
+        &800,
+        &test.claim_addresses[0],
+        &String::from_str(&test.env, "Enjoy"),
+        &TimeBound {
+            kind: TimeBoundKind::After,
+            timestamp: 12345,
+        },
+    );
+
+    // Only the sender can revoke, only the recipient can decline
+    assert!(test.contract.try_revoke_gift(&test.claim_addresses[0], &balance_id).is_err());
+    assert!(test.contract.try_decline(&test.deposit_address, &balance_id).is_err());
+
+    assert_eq!(test.contract.decline(&test.claim_addresses[0], &balance_id), 800);
+    assert_eq!(test.token.balance(&test.deposit_address), 1000);
+    assert_eq!(test.token.balance(&test.claim_addresses[0]), 0);
+}
+
+#[test]
+#[should_panic(expected = "balance is not a gift")]
+fn test_revoke_regular_balance_not_possible() {
+    let test = ClaimableBalanceTest::setup();
+
+    let balance_id = test.contract.deposit(
+        &test.deposit_address,
+        &test.token.address,
+        &800,
+        &vec![&test.env, test.claim_addresses[0].clone()],
+        &TimeBound {
+            kind: TimeBoundKind::Before,
+            timestamp: 12346,
+        },
+    );
+
+    test.contract.revoke_gift(&test.deposit_address, &balance_id);
+}
+
+#[test]
+fn test_equal_split_with_remainder_to_last_claimant() {
+    let test = ClaimableBalanceTest::setup();
+
+    // 800 split three ways: 266 each, the remainder of 2 goes to the last claimant
+    let balance_id = test.contract.deposit_with_options(
+        &test.deposit_address,
+        &test.token.address,
+        &800,
+        &vec![
+            &test.env,
+            test.claim_addresses[0].clone(),
+            test.claim_addresses[1].clone(),
+            test.claim_addresses[2].clone(),
+        ],
+        &TimeBound {
+            kind: TimeBoundKind::Before,
+            timestamp: 12346,
+        },
+        &DepositOptions {
+            distribution: DistributionMode::EqualSplit,
+            ..Default::default()
+        },
+    );
+
+    test.contract.claim(&test.claim_addresses[2], &balance_id);
+    assert_eq!(test.token.balance(&test.claim_addresses[2]), 266);
+
+    // Each claimant can only claim its share once
+    assert!(test.contract.try_claim(&test.claim_addresses[2], &balance_id).is_err());
+
+    test.contract.claim(&test.claim_addresses[0], &balance_id);
+    assert_eq!(test.token.balance(&test.claim_addresses[0]), 266);
+
+    test.contract.claim(&test.claim_addresses[1], &balance_id);
+    assert_eq!(test.token.balance(&test.claim_addresses[1]), 268);
+    assert_eq!(test.token.balance(&test.contract.address), 0);
+}
+
+#[test]
+#[should_panic(expected = "amount is too small to split equally")]
+fn test_equal_split_below_claimant_count_not_possible() {
+    let test = ClaimableBalanceTest::setup();
+
+    test.contract.deposit_with_options(
+        &test.deposit_address,
+        &test.token.address,
+        &1,
+        &vec![
+            &test.env,
+            test.claim_addresses[0].clone(),
+            test.claim_addresses[1].clone(),
+        ],
+        &TimeBound {
+            kind: TimeBoundKind::Before,
+            timestamp: 12346,
+        },
+        &DepositOptions {
+            distribution: DistributionMode::EqualSplit,
+            ..Default::default()
+        },
+    );
+}
+
+#[test]
+fn test_hashlocked_claim_requires_preimage() {
+    let test = ClaimableBalanceTest::setup();
+    let preimage = Bytes::from_slice(&test.env, b"secret");
+    let hash = test.env.crypto().sha256(&preimage).to_bytes();
+
+    let balance_id = test.contract.deposit_with_options(
+        &test.deposit_address,
+        &test.token.address,
+        &800,
+        &vec![&test.env, test.claim_addresses[0].clone()],
+        &TimeBound {
+            kind: TimeBoundKind::Before,
+            timestamp: 12346,
+        },
+        &DepositOptions {
+            hashlock: Hashlock::Sha256(hash.clone()),
+            ..Default::default()
+        },
+    );
+
+    // Plain claims and wrong secrets are rejected
+    assert!(test.contract.try_claim(&test.claim_addresses[0], &balance_id).is_err());
+    assert!(test
+        .contract
+        .try_claim_with_preimage(
+            &test.claim_addresses[0],
+            &balance_id,
+            &Bytes::from_slice(&test.env, b"guess")
+        )
+        .is_err());
+
+    test.contract
+        .claim_with_preimage(&test.claim_addresses[0], &balance_id, &preimage);
+
+    // The revealed secret is published for the counterparty
+    let revealed = find_event_data(&test.env, symbol_short!("preimage")).unwrap();
+    assert_eq!(Bytes::try_from_val(&test.env, &revealed).unwrap(), preimage);
+    assert_eq!(test.token.balance(&test.claim_addresses[0]), 800);
+}
+
+#[test]
+fn test_hashlocked_balance_refunded_after_timeout() {
+    let test = ClaimableBalanceTest::setup();
+    let preimage = Bytes::from_slice(&test.env, b"secret");
+
+    let balance_id = test.contract.deposit_with_options(
+        &test.deposit_address,
+        &test.token.address,
+        &800,
+        &vec![&test.env, test.claim_addresses[0].clone()],
+        &TimeBound {
+            kind: TimeBoundKind::Before,
+            timestamp: 12346,
+        },
+        &DepositOptions {
+            hashlock: Hashlock::Sha256(test.env.crypto().sha256(&preimage).to_bytes()),
+            ..Default::default()
+        },
+    );
+
+    // No refund while the preimage can still be revealed
+    assert!(test.contract.try_refund(&test.deposit_address, &balance_id).is_err());
+
+    test.env.ledger().with_mut(|li| li.timestamp = 12347);
+    assert!(test
+        .contract
+        .try_claim_with_preimage(&test.claim_addresses[0], &balance_id, &preimage)
+        .is_err());
+
+    assert_eq!(test.contract.refund(&test.deposit_address, &balance_id), 800);
+    assert_eq!(test.token.balance(&test.deposit_address), 1000);
+}
+
+#[test]
+fn test_rolling_withdrawal_cap_limits_vested_claims() {
+    let test = ClaimableBalanceTest::setup();
+
+    // Vests linearly over 100 seconds, at most 300 per claimant every 50 seconds
+    let balance_id = test.contract.deposit_with_options(
+        &test.deposit_address,
+        &test.token.address,
+        &800,
+        &vec![&test.env, test.claim_addresses[0].clone()],
+        &TimeBound {
+            kind: TimeBoundKind::After,
+            timestamp: 12345,
+        },
+        &DepositOptions {
+            vesting: Vesting::Linear(VestingSchedule {
+                start: 12345,
+                cliff: 12345,
+                end: 12445,
+            }),
+            withdrawal_cap: WithdrawalCap::Rolling(RollingWindow {
+                max_amount: 300,
+                window: 50,
+            }),
+            ..Default::default()
+        },
+    );
+
+    // 400 vested, but only 300 can be withdrawn in the window
+    test.env.ledger().with_mut(|li| li.timestamp = 12395);
+    assert_eq!(test.contract.claim_vested(&test.claim_addresses[0], &balance_id), 300);
+
+    // The window is exhausted
+    test.env.ledger().with_mut(|li| li.timestamp = 12420);
+    assert!(test.contract.try_claim_vested(&test.claim_addresses[0], &balance_id).is_err());
+
+    // Once the first withdrawal leaves the window, the cap frees up again
+    test.env.ledger().with_mut(|li| li.timestamp = 12445);
+    assert_eq!(test.contract.claim_vested(&test.claim_addresses[0], &balance_id), 300);
+    test.env.ledger().with_mut(|li| li.timestamp = 12495);
+    assert_eq!(test.contract.claim_vested(&test.claim_addresses[0], &balance_id), 200);
+    assert_eq!(test.token.balance(&test.claim_addresses[0]), 800);
+}
+
+#[test]
+#[should_panic(expected = "withdrawal caps require linear vesting")]
+fn test_rolling_withdrawal_cap_rejected_on_lump_sums() {
+    let test = ClaimableBalanceTest::setup();
+
+    // A claim of 800 could never fit in a cap of 500, which would lock the balance for good
+    test.contract.deposit_with_options(
+        &test.deposit_address,
+        &test.token.address,
+        &800,
+        &vec![&test.env, test.claim_addresses[0].clone()],
+        &TimeBound {
+            kind: TimeBoundKind::Before,
+            timestamp: 12346,
+        },
+        &DepositOptions {
+            withdrawal_cap: WithdrawalCap::Rolling(RollingWindow {
+                max_amount: 500,
+                window: 50,
+            }),
+            ..Default::default()
+        },
+    );
+}
+
+#[test]
+fn test_state_checkpoint_publishes_balances() {
+    let test = ClaimableBalanceTest::setup();
+
+    // Nothing to publish before any deposit
+    assert_eq!(test.contract.emit_state_checkpoint(&0, &10), 0);
+
+    test.contract.deposit(
+        &test.deposit_address,
+        &test.token.address,
+        &800,
+        &vec![&test.env, test.claim_addresses[0].clone()],
+        &TimeBound {
+            kind: TimeBoundKind::Before,
+            timestamp: 12346,
+        },
+    );
+
+    assert_eq!(test.contract.emit_state_checkpoint(&0, &10), 1);
+    let data = find_event_data(&test.env, symbol_short!("ckpt")).unwrap();
+    let published = ClaimableBalance::try_from_val(&test.env, &data).unwrap();
+    assert_eq!(published.depositor, test.deposit_address);
+    assert_eq!(published.amount, 800);
+    assert_eq!(published.claimants, vec![&test.env, test.claim_addresses[0].clone()]);
+
+    // Pages past the end are empty
+    assert_eq!(test.contract.emit_state_checkpoint(&1, &10), 0);
+}
+
+/// Utility function hashing two Merkle nodes in sorted order, mirroring the contract.
+fn merkle_parent(e: &Env, a: &BytesN<32>, b: &BytesN<32>) -> BytesN<32> {
+    let (first, second) = if a <= b { (a, b) } else { (b, a) };
+    let mut pair = Bytes::from(first.clone());
+    pair.append(&Bytes::from(second.clone()));
+    e.crypto().sha256(&pair).to_bytes()
+}
+
+#[test]
+fn test_merkle_airdrop_claims() {
+    let test = ClaimableBalanceTest::setup();
+
+    // Three-leaf tree: root = H(H(leaf0, leaf1), leaf2)
+    let leaf0 = merkle_leaf(&test.env, &test.claim_addresses[0], 500);
+    let leaf1 = merkle_leaf(&test.env, &test.claim_addresses[1], 200);
+    let leaf2 = merkle_leaf(&test.env, &test.claim_addresses[2], 100);
+    let node01 = merkle_parent(&test.env, &leaf0, &leaf1);
+    let root = merkle_parent(&test.env, &node01, &leaf2);
+
+    let balance_id = test.contract.deposit_merkle(
+        &test.deposit_address,
+        &test.token.address,
+        &800,
+        &root,
+        &TimeBound {
+            kind: TimeBoundKind::Before,
+            timestamp: 12346,
+        },
+    );
+
+    let proof1 = vec![&test.env, leaf0.clone(), leaf2.clone()];
+
+    // Proofs for a different amount are rejected
+    assert!(test
+        .contract
+        .try_claim_with_proof(&test.claim_addresses[1], &balance_id, &300, &proof1)
+        .is_err());
+
+    test.contract
+        .claim_with_proof(&test.claim_addresses[1], &balance_id, &200, &proof1);
+    assert_eq!(test.token.balance(&test.claim_addresses[1]), 200);
+
+    // Each leaf can only be claimed once
+    assert!(test
+        .contract
+        .try_claim_with_proof(&test.claim_addresses[1], &balance_id, &200, &proof1)
+        .is_err());
+
+    test.contract
+        .claim_with_proof(&test.claim_addresses[2], &balance_id, &100, &vec![&test.env, node01]);
+    assert_eq!(test.token.balance(&test.claim_addresses[2]), 100);
+    assert_eq!(test.token.balance(&test.contract.address), 500);
+}
+
+#[test]
+fn test_claim_held_until_claimant_can_receive() {
+    let test = ClaimableBalanceTest::setup();
+
+    // Classic asset whose issuer can revoke authorization (stands in for a missing trustline)
+    let sac = test
+        .env
+        .register_stellar_asset_contract_v2(Address::generate(&test.env));
+    sac.issuer().set_flag(IssuerFlags::RevocableFlag);
+    let token = TokenClient::new(&test.env, &sac.address());
+    let token_admin = TokenAdminClient::new(&test.env, &sac.address());
+    token_admin.mint(&test.deposit_address, &800);
+
+    let balance_id = test.contract.deposit(
+        &test.deposit_address,
+        &token.address,
+        &800,
+        &vec![&test.env, test.claim_addresses[0].clone()],
+        &TimeBound {
+            kind: TimeBoundKind::Before,
+            timestamp: 12346,
+        },
+    );
+
+    token_admin.set_authorized(&test.claim_addresses[0], &false);
+
+    // The claim is consumed, but the tokens are held for the claimant
+    test.contract.claim(&test.claim_addresses[0], &balance_id);
+    assert_eq!(
+        test.contract
+            .pending_delivery(&test.claim_addresses[0], &token.address),
+        800
+    );
+    assert_eq!(token.balance(&test.contract.address), 800);
+
+    // Retrying fails until the claimant can receive
+    assert!(test
+        .contract
+        .try_retry_delivery(&test.claim_addresses[0], &token.address)
+        .is_err());
+
+    token_admin.set_authorized(&test.claim_addresses[0], &true);
+    assert_eq!(
+        test.contract
+            .retry_delivery(&test.claim_addresses[0], &token.address),
+        800
+    );
+    assert_eq!(token.balance(&test.claim_addresses[0]), 800);
+    assert_eq!(
+        test.contract
+            .pending_delivery(&test.claim_addresses[0], &token.address),
+        0
+    );
+}
+
+#[test]
+fn test_scheduled_operation_executes_after_delay() {
+    let test = ClaimableBalanceTest::setup();
+    grant_roles(&test, &[Role::Proposer, Role::Executor], &test.admin);
+    let target = MockTargetClient::new(&test.env, &test.env.register(MockTarget, ()));
+    let args = vec![&test.env, 7_u32.into_val(&test.env)];
+    let eta = 12345 + 2 * MIN_DELAY;
+
+    // The eta must respect the minimum delay
+    assert!(test
+        .contract
+        .try_schedule(&test.admin, &target.address, &symbol_short!("set_value"), &args, &(eta - 1), &None)
+        .is_err());
+
+    let op_id = test
+        .contract
+        .schedule(&test.admin, &target.address, &symbol_short!("set_value"), &args, &eta, &None);
+    assert_eq!(op_id, 0);
+
+    // Too early to execute
+    assert!(test.contract.try_execute(&test.admin, &op_id).is_err());
+
+    test.env.ledger().with_mut(|li| li.timestamp = eta);
+    let results = test.contract.execute(&test.admin, &op_id);
+    assert_eq!(results.len(), 1);
+    assert_eq!(u32::try_from_val(&test.env, &results.get_unchecked(0)).unwrap(), 7);
+    assert_eq!(target.value(), 7);
+
+    // Operations run only once
+    assert!(test.contract.try_execute(&test.admin, &op_id).is_err());
+}
+
+#[test]
+#[should_panic(expected = "operation is not pending")]
+fn test_cancelled_operation_cannot_execute() {
+    let test = ClaimableBalanceTest::setup();
+    grant_roles(&test, &[Role::Proposer, Role::Executor, Role::Canceller], &test.admin);
+    let target = MockTargetClient::new(&test.env, &test.env.register(MockTarget, ()));
+
+    let op_id = test.contract.schedule(
+        &test.admin,
+        &target.address,
+        &symbol_short!("set_value"),
+        &vec![&test.env, 7_u32.into_val(&test.env)],
+        &(12345 + 2 * MIN_DELAY),
+        &None,
+    );
+    test.contract.cancel(&test.admin, &op_id);
+
+    test.contract.execute(&test.admin, &op_id);
+}
+
+#[test]
+fn test_keeper_sweeps_expired_balance() {
+    let test = ClaimableBalanceTest::setup();
+    let scheduler = MockSchedulerClient::new(&test.env, &test.env.register(MockScheduler, ()));
+
+    let balance_id = test.contract.deposit(
+        &test.deposit_address,
+        &test.token.address,
+        &800,
+        &vec![&test.env, test.claim_addresses[0].clone()],
+        &TimeBound {
+            kind: TimeBoundKind::Before,
+            timestamp: 12346,
+        },
+    );
+    assert_eq!(
+        test.contract.next_action_time(&Task::ExpirySweep(balance_id)),
+        Some(12347)
+    );
+
+    // Not due yet
+    assert!(!scheduler.tick(&test.contract.address, &Task::ExpirySweep(balance_id)));
+
+    test.env.ledger().with_mut(|li| li.timestamp = 12347);
+    assert!(scheduler.tick(&test.contract.address, &Task::ExpirySweep(balance_id)));
+    assert_eq!(test.token.balance(&test.deposit_address), 1000);
+    assert_eq!(test.contract.next_action_time(&Task::ExpirySweep(balance_id)), None);
+}
+
+#[test]
+fn test_keeper_bumps_rent_and_executes_operations() {
+    let test = ClaimableBalanceTest::setup();
+    let scheduler = MockSchedulerClient::new(&test.env, &test.env.register(MockScheduler, ()));
+    test.contract.update_config(&Config {
+        rent_bump_fee: 10,
+        ..test.contract.get_config()
+    });
+
+    let balance_id = test.contract.deposit(
+        &test.deposit_address,
+        &test.token.address,
+        &800,
+        &vec![&test.env, test.claim_addresses[0].clone()],
+        &TimeBound {
+            kind: TimeBoundKind::After,
+            timestamp: 12345,
+        },
+    );
+
+    // No rent budget means no bump to perform
+    assert_eq!(test.contract.next_action_time(&Task::RentBump(balance_id)), None);
+    test.contract.fund_rent(&test.deposit_address, &balance_id, &100);
+    assert!(scheduler.tick(&test.contract.address, &Task::RentBump(balance_id)));
+    assert_eq!(test.native_token.balance(&scheduler.address), 10);
+
+    // The next bump is a week of ledgers away
+    assert!(!scheduler.tick(&test.contract.address, &Task::RentBump(balance_id)));
+
+    // The keeper needs the executor role to run scheduled operations
+    test.contract.grant_role(&test.admin, &Role::Proposer, &test.admin);
+    grant_roles(&test, &[Role::Executor], &scheduler.address);
+    let target = MockTargetClient::new(&test.env, &test.env.register(MockTarget, ()));
+    let op_id = test.contract.schedule(
+        &test.admin,
+        &target.address,
+        &symbol_short!("set_value"),
+        &vec![&test.env, 3_u32.into_val(&test.env)],
+        &(12345 + 2 * MIN_DELAY),
+        &None,
+    );
+    assert!(!scheduler.tick(&test.contract.address, &Task::Operation(op_id)));
+    test.env.ledger().with_mut(|li| li.timestamp = 12345 + 2 * MIN_DELAY);
+    assert!(scheduler.tick(&test.contract.address, &Task::Operation(op_id)));
+    assert_eq!(target.value(), 3);
+}
+
+#[test]
+fn test_roles_gate_scheduler() {
+    let test = ClaimableBalanceTest::setup();
+    let target = MockTargetClient::new(&test.env, &test.env.register(MockTarget, ()));
+    let proposer = &test.claim_addresses[0];
+    let args = vec![&test.env, 5_u32.into_val(&test.env)];
+
+    // The contract admin implicitly holds the admin role
+    assert!(test.contract.has_role(&Role::Admin, &test.admin));
+    assert!(!test.contract.has_role(&Role::Proposer, proposer));
+
+    // Without the proposer role scheduling is rejected
+    assert!(test
+        .contract
+        .try_schedule(proposer, &target.address, &symbol_short!("set_value"), &args, &(12345 + MIN_DELAY), &None)
+        .is_err());
+
+    // Grants only take effect once the scheduler delay has passed
+    test.contract.grant_role(&test.admin, &Role::Proposer, proposer);
+    assert!(!test.contract.has_role(&Role::Proposer, proposer));
+    assert!(test
+        .contract
+        .try_schedule(proposer, &target.address, &symbol_short!("set_value"), &args, &(12345 + MIN_DELAY), &None)
+        .is_err());
+
+    test.env.ledger().set_timestamp(12345 + MIN_DELAY);
+    assert!(test.contract.has_role(&Role::Proposer, proposer));
+    let op_id = test
+        .contract
+        .schedule(proposer, &target.address, &symbol_short!("set_value"), &args, &(12345 + 2 * MIN_DELAY), &None);
+
+    // Proposers can neither execute nor grant roles
+    assert!(test.contract.try_execute(proposer, &op_id).is_err());
+    assert!(test
+        .contract
+        .try_grant_role(proposer, &Role::Executor, proposer)
+        .is_err());
+
+    test.contract.revoke_role(&test.admin, &Role::Proposer, proposer);
+    assert!(!test.contract.has_role(&Role::Proposer, proposer));
+}
+
+#[test]
+fn test_pause_blocks_deposits_and_optionally_claims() {
+    let test = ClaimableBalanceTest::setup();
+    let claimants = vec![&test.env, test.claim_addresses[0].clone()];
+    let time_bound = TimeBound {
+        kind: TimeBoundKind::Before,
+        timestamp: 12346,
+    };
+
+    let balance_id = test.contract.deposit(
+        &test.deposit_address,
+        &test.token.address,
+        &400,
+        &claimants,
+        &time_bound,
+    );
+
+    // Any pause blocks new deposits
+    test.contract.pause(&false);
+    assert!(test.contract.is_paused());
+    assert!(test
+        .contract
+        .try_deposit(&test.deposit_address, &test.token.address, &400, &claimants, &time_bound)
+        .is_err());
+
+    // Pausing claims as well blocks payouts until unpaused
+    test.contract.pause(&true);
+    assert!(test
+        .contract
+        .try_claim(&test.claim_addresses[0], &balance_id)
+        .is_err());
+
+    test.contract.unpause();
+    assert!(!test.contract.is_paused());
+    test.contract.claim(&test.claim_addresses[0], &balance_id);
+    assert_eq!(test.token.balance(&test.claim_addresses[0]), 400);
+}
+
+#[test]
+fn test_upgrade_requires_announcement_and_delay() {
+    let test = ClaimableBalanceTest::setup();
+    test.contract.update_config(&Config {
+        upgrade_delay: 1000,
+        ..test.contract.get_config()
+    });
+    let wasm_hash = BytesN::from_array(&test.env, &[7; 32]);
+
+    // Nothing can be installed without an announcement
+    assert!(test.contract.try_upgrade(&wasm_hash).is_err());
+
+    assert_eq!(test.contract.announce_upgrade(&wasm_hash), 13345);
+    assert_eq!(test.contract.pending_upgrade().unwrap().eta, 13345);
+
+    // Neither before the delay nor with different code
+    assert!(test.contract.try_upgrade(&wasm_hash).is_err());
+    test.env.ledger().with_mut(|li| li.timestamp = 13345);
+    assert!(test
+        .contract
+        .try_upgrade(&BytesN::from_array(&test.env, &[8; 32]))
+        .is_err());
+
+    // A cancelled announcement cannot be installed anymore
+    test.contract.cancel_upgrade();
+    assert!(test.contract.pending_upgrade().is_none());
+    assert!(test.contract.try_upgrade(&wasm_hash).is_err());
+}
+
+#[test]
+fn test_version_reports_installed_code() {
+    let test = ClaimableBalanceTest::setup();
+    assert_eq!(test.contract.version(), CONTRACT_VERSION);
+    assert_eq!(test.contract.version(), 1);
+}
+
+#[test]
+fn test_fresh_deployment_needs_no_migration() {
+    let test = ClaimableBalanceTest::setup();
+    assert_eq!(test.contract.storage_version(), CONTRACT_VERSION);
+    assert!(test.contract.try_migrate().is_err());
+}
+
+#[test]
+fn test_migrate_moves_legacy_balance_to_keyed_layout() {
+    let test = ClaimableBalanceTest::setup();
+    let claimant = &test.claim_addresses[0];
+
+    // Seed the layout of the original deployment, which predates storage versions
+    test.token.transfer(&test.deposit_address, &test.contract.address, &500);
+    test.env.as_contract(&test.contract.address, || {
+        let storage = test.env.storage().instance();
+        storage.remove(&DataKey::StorageVersion);
+        storage.set(&LegacyDataKey::Init, &());
+        storage.set(
+            &LegacyDataKey::Balance,
+            &LegacyClaimableBalance {
+                token: test.token.address.clone(),
+                amount: 500,
+                claimants: vec![&test.env, claimant.clone()],
+                time_bound: TimeBound {
+                    kind: TimeBoundKind::After,
+                    timestamp: 12400,
+                },
+            },
+        );
+    });
+    assert_eq!(test.contract.storage_version(), 0);
+
+    assert_eq!(test.contract.migrate(), CONTRACT_VERSION);
+    assert_eq!(test.contract.storage_version(), CONTRACT_VERSION);
+    assert!(test.contract.try_migrate().is_err());
+    test.env.as_contract(&test.contract.address, || {
+        assert!(!test.env.storage().instance().has(&LegacyDataKey::Balance));
+        assert!(!test.env.storage().instance().has(&LegacyDataKey::Init));
+    });
+
+    // The balance is claimable under its new id, with the admin standing in as depositor
+    let claimable_balance = test.contract.get_balance(&0);
+    assert_eq!(claimable_balance.amount, 500);
+    assert_eq!(claimable_balance.depositor, test.admin);
+    assert_eq!(test.contract.total_locked(&test.token.address), 500);
+    test.env.travel_to(12400);
+    test.contract.claim(claimant, &0);
+    assert_eq!(test.token.balance(claimant), 500);
+}
+
+#[test]
+fn test_admin_transfer_requires_acceptance() {
+    let test = ClaimableBalanceTest::setup();
+    let new_admin = Address::generate(&test.env);
+
+    // Proposing does not hand over control yet
+    assert!(test.contract.try_accept_admin().is_err());
+    test.contract.transfer_admin(&new_admin);
+    assert_eq!(test.contract.admin(), test.admin);
+
+    test.contract.accept_admin();
+    assert_eq!(
+        test.env.auths(),
+        [(
+            new_admin.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    test.contract.address.clone(),
+                    Symbol::new(&test.env, "accept_admin"),
+                    ().into_val(&test.env),
+                )),
+                sub_invocations: std::vec![]
+            }
+        ),]
+    );
+    assert_eq!(test.contract.admin(), new_admin);
+
+    // The proposal is consumed
+    assert!(test.contract.try_accept_admin().is_err());
+}
+
+#[test]
+fn test_claim_to_delivers_to_destination() {
+    let test = ClaimableBalanceTest::setup();
+    let treasury = Address::generate(&test.env);
+
+    let balance_id = test.contract.deposit(
+        &test.deposit_address,
+        &test.token.address,
+        &800,
+        &vec![&test.env, test.claim_addresses[0].clone()],
+        &TimeBound {
+            kind: TimeBoundKind::Before,
+            timestamp: 12346,
+        },
+    );
+
+    // Only the claimant authorizes; the destination needs no say
+    test.contract.claim_to(&test.claim_addresses[0], &treasury, &balance_id);
+
+    // The claim event records both the claimant and the destination
+    assert_eq!(
+        last_event(&test.env),
+        vec![
+            &test.env,
+            (
+                test.contract.address.clone(),
+                (
+                    symbol_short!("claim"),
+                    EVENT_VERSION,
+                    balance_id,
+                    test.claim_addresses[0].clone(),
+                    test.token.address.clone(),
+                )
+                    .into_val(&test.env),
+                ClaimEvent {
+                    amount: 800,
+                    timestamp: 12345,
+                    destination: treasury.clone(),
+                }
+                .into_val(&test.env),
+            )
+        ]
+    );
+    assert_eq!(test.token.balance(&treasury), 800);
+    assert_eq!(test.token.balance(&test.claim_addresses[0]), 0);
+}
+
+#[test]
+fn test_distribute_pushes_remaining_shares() {
+    let test = ClaimableBalanceTest::setup();
+
+    let balance_id = test.contract.deposit_with_options(
+        &test.deposit_address,
+        &test.token.address,
+        &800,
+        &vec![
+            &test.env,
+            test.claim_addresses[0].clone(),
+            test.claim_addresses[1].clone(),
+            test.claim_addresses[2].clone(),
+        ],
+        &TimeBound {
+            kind: TimeBoundKind::After,
+            timestamp: 12400,
+        },
+        &DepositOptions {
+            distribution: DistributionMode::EqualSplit,
+            ..Default::default()
+        },
+    );
+
+    // Nothing can be pushed before the unlock
+    let keeper = Address::generate(&test.env);
+    assert!(test.contract.try_distribute(&keeper, &balance_id).is_err());
+
+    test.env.ledger().with_mut(|li| li.timestamp = 12400);
+    test.contract.claim(&test.claim_addresses[1], &balance_id);
+
+    // The remaining claimants are paid without signing anything
+    assert_eq!(test.contract.distribute(&keeper, &balance_id), 534);
+    assert_eq!(test.env.auths(), std::vec![]);
+    assert_eq!(test.token.balance(&test.claim_addresses[0]), 266);
+    assert_eq!(test.token.balance(&test.claim_addresses[1]), 266);
+    assert_eq!(test.token.balance(&test.claim_addresses[2]), 268);
+    assert_eq!(test.token.balance(&test.contract.address), 0);
+}
+
+#[test]
+fn test_keeper_bounty_paid_on_refund_expired_and_distribute() {
+    let test = ClaimableBalanceTest::setup();
+    let keeper = Address::generate(&test.env);
+
+    // 5% bounty on top of an expiring balance
+    let expiring_id = test.contract.deposit_with_options(
+        &test.deposit_address,
+        &test.token.address,
+        &400,
+        &vec![&test.env, test.claim_addresses[0].clone()],
+        &TimeBound {
+            kind: TimeBoundKind::Before,
+            timestamp: 12346,
+        },
+        &DepositOptions {
+            bounty: Bounty::Bps(500),
+            ..Default::default()
+        },
+    );
+    assert_eq!(test.token.balance(&test.deposit_address), 580);
+
+    // Fixed bounty on an allocated balance pushed by the keeper
+    let allocated_id = test.contract.deposit_with_options(
+        &test.deposit_address,
+        &test.token.address,
+        &300,
+        &vec![&test.env, test.claim_addresses[1].clone()],
+        &TimeBound {
+            kind: TimeBoundKind::Before,
+            timestamp: 12400,
+        },
+        &DepositOptions {
+            distribution: DistributionMode::EqualSplit,
+            bounty: Bounty::Fixed(10),
+            ..Default::default()
+        },
+    );
+    assert_eq!(test.contract.distribute(&keeper, &allocated_id), 300);
+    assert_eq!(test.token.balance(&test.claim_addresses[1]), 300);
+    assert_eq!(test.token.balance(&keeper), 10);
+
+    // Too early to refund
+    assert!(test
+        .contract
+        .try_refund_expired(&keeper, &expiring_id)
+        .is_err());
+
+    test.env.ledger().with_mut(|li| li.timestamp = 12347);
+    assert_eq!(test.contract.refund_expired(&keeper, &expiring_id), 400);
+    assert_eq!(test.token.balance(&keeper), 30);
+    assert_eq!(test.token.balance(&test.deposit_address), 670);
+    assert_eq!(test.token.balance(&test.contract.address), 0);
+}
+
+#[test]
+fn test_refund_expired_settles_state_and_pays_everyone() {
+    let test = ClaimableBalanceTest::setup();
+    let keeper = Address::generate(&test.env);
+
+    let balance_id = test.contract.deposit_with_options(
+        &test.deposit_address,
+        &test.token.address,
+        &400,
+        &vec![&test.env, test.claim_addresses[0].clone()],
+        &TimeBound {
+            kind: TimeBoundKind::Before,
+            timestamp: 12346,
+        },
+        &DepositOptions {
+            bounty: Bounty::Fixed(20),
+            ..Default::default()
+        },
+    );
+    test.contract.fund_rent(&test.deposit_address, &balance_id, &100);
+
+    // The balance is closed before the bounty, the rent budget and the remainder leave the contract
+    test.env.ledger().with_mut(|li| li.timestamp = 12347);
+    assert_eq!(test.contract.refund_expired(&keeper, &balance_id), 400);
+    assert_eq!(test.contract.status(&balance_id), BalanceStatus::Expired);
+    assert!(test.contract.try_get_balance(&balance_id).is_err());
+    assert_eq!(test.contract.rent_budget(&balance_id), 0);
+    assert_eq!(test.token.balance(&keeper), 20);
+    assert_eq!(test.token.balance(&test.deposit_address), 980);
+    assert_eq!(test.native_token.balance(&test.deposit_address), 1000);
+    assert_eq!(test.token.balance(&test.contract.address), 0);
+}
+
+#[test]
+fn test_unearned_bounty_returns_to_depositor() {
+    let test = ClaimableBalanceTest::setup();
+
+    let balance_id = test.contract.deposit_with_options(
+        &test.deposit_address,
+        &test.token.address,
+        &800,
+        &vec![&test.env, test.claim_addresses[0].clone()],
+        &TimeBound {
+            kind: TimeBoundKind::Before,
+            timestamp: 12346,
+        },
+        &DepositOptions {
+            bounty: Bounty::Fixed(50),
+            ..Default::default()
+        },
+    );
+    assert_eq!(test.token.balance(&test.deposit_address), 150);
+
+    // A claimant claiming itself leaves no keeper to reward
+    test.contract.claim(&test.claim_addresses[0], &balance_id);
+    assert_eq!(test.token.balance(&test.claim_addresses[0]), 800);
+    assert_eq!(test.token.balance(&test.deposit_address), 200);
+}
+
+#[test]
+fn test_revoke_splits_vested_and_unvested() {
+    let test = ClaimableBalanceTest::setup();
+
+    // Vests linearly from 12345 to 12445
+    let balance_id = test.contract.deposit_with_options(
+        &test.deposit_address,
+        &test.token.address,
+        &800,
+        &vec![&test.env, test.claim_addresses[0].clone()],
+        &TimeBound {
+            kind: TimeBoundKind::After,
+            timestamp: 12345,
+        },
+        &DepositOptions {
+            vesting: Vesting::Linear(VestingSchedule {
+                start: 12345,
+                cliff: 12345,
+                end: 12445,
+            }),
+            revocable: true,
+            ..Default::default()
+        },
+    );
+
+    test.env.ledger().with_mut(|li| li.timestamp = 12370);
+    assert_eq!(test.contract.claim_vested(&test.claim_addresses[0], &balance_id), 200);
+
+    // Half vested: the claimant gets the other 200 vested tokens, the depositor the unvested 400
+    test.env.ledger().with_mut(|li| li.timestamp = 12395);
+    assert_eq!(test.contract.revoke(&balance_id), 400);
+    assert_eq!(test.token.balance(&test.claim_addresses[0]), 400);
+    assert_eq!(test.token.balance(&test.deposit_address), 600);
+    assert!(test
+        .contract
+        .try_claim_vested(&test.claim_addresses[0], &balance_id)
+        .is_err());
+}
+
+#[test]
+#[should_panic(expected = "balance is not revocable")]
+fn test_revoke_non_revocable_balance_not_possible() {
+    let test = ClaimableBalanceTest::setup();
+
+    let balance_id = test.contract.deposit_with_options(
+        &test.deposit_address,
+        &test.token.address,
+        &800,
+        &vec![&test.env, test.claim_addresses[0].clone()],
+        &TimeBound {
+            kind: TimeBoundKind::After,
+            timestamp: 12345,
+        },
+        &DepositOptions {
+            vesting: Vesting::Linear(VestingSchedule {
+                start: 12345,
+                cliff: 12345,
+                end: 12445,
+            }),
+            ..Default::default()
+        },
+    );
+
+    test.contract.revoke(&balance_id);
+}
+
+#[test]
+fn test_stream_accrues_and_withdraws() {
+    let test = ClaimableBalanceTest::setup();
+    let recipient = &test.claim_addresses[0];
+
+    // 800 tokens streamed over 100 seconds
+    let stream_id = test.contract.create_stream(
+        &test.deposit_address,
+        recipient,
+        &test.token.address,
+        &800,
+        &12345,
+        &12445,
+    );
+    assert_eq!(test.contract.stream_withdrawable(&stream_id), 0);
+
+    test.env.ledger().with_mut(|li| li.timestamp = 12370);
+    assert_eq!(test.contract.stream_withdrawable(&stream_id), 200);
+    assert!(test
+        .contract
+        .try_withdraw_from_stream(&stream_id, &201)
+        .is_err());
+    test.contract.withdraw_from_stream(&stream_id, &150);
+    assert_eq!(test.token.balance(recipient), 150);
+    assert_eq!(test.contract.get_stream(&stream_id).withdrawn, 150);
+
+    // Everything can be withdrawn after the stop, which ends the stream
+    test.env.ledger().with_mut(|li| li.timestamp = 12500);
+    test.contract.withdraw_from_stream(&stream_id, &650);
+    assert_eq!(test.token.balance(recipient), 800);
+    assert!(test.contract.try_get_stream(&stream_id).is_err());
+}
+
+#[test]
+fn test_cancel_stream_splits_pro_rata() {
+    let test = ClaimableBalanceTest::setup();
+    let recipient = &test.claim_addresses[0];
+
+    let stream_id = test.contract.create_stream(
+        &test.deposit_address,
+        recipient,
+        &test.token.address,
+        &800,
+        &12345,
+        &12445,
+    );
+
+    test.env.ledger().with_mut(|li| li.timestamp = 12370);
+    test.contract.withdraw_from_stream(&stream_id, &150);
+
+    // Half streamed: the recipient gets the unwithdrawn 250, the sender the unstreamed 400
+    test.env.ledger().with_mut(|li| li.timestamp = 12395);
+    test.contract.cancel_stream(&stream_id);
+    assert_eq!(test.token.balance(recipient), 400);
+    assert_eq!(test.token.balance(&test.deposit_address), 600);
+    assert_eq!(test.token.balance(&test.contract.address), 0);
+}
+
+#[test]
+fn test_tranched_claim_releases_matured_tranches() {
+    let test = ClaimableBalanceTest::setup();
+
+    // 100 at 12345, then 300 at 12400 and 400 at 12500
+    let balance_id = test.contract.deposit_tranched(
+        &test.deposit_address,
+        &test.token.address,
+        &vec![&test.env, test.claim_addresses[0].clone()],
+        &TimeBound {
+            kind: TimeBoundKind::After,
+            timestamp: 12345,
+        },
+        &vec![
+            &test.env,
+            Tranche { timestamp: 12345, amount: 100 },
+            Tranche { timestamp: 12400, amount: 300 },
+            Tranche { timestamp: 12500, amount: 400 },
+        ],
+    );
+    assert_eq!(test.token.balance(&test.contract.address), 800);
+
+    // Two tranches matured: both are paid in a single claim
+    test.env.ledger().with_mut(|li| li.timestamp = 12450);
+    assert_eq!(test.contract.vested_amount(&balance_id), 400);
+    test.contract.claim(&test.claim_addresses[0], &balance_id);
+    assert_eq!(test.token.balance(&test.claim_addresses[0]), 400);
+
+    // Nothing new until the last tranche matures
+    assert!(test
+        .contract
+        .try_claim(&test.claim_addresses[0], &balance_id)
+        .is_err());
+
+    test.env.ledger().with_mut(|li| li.timestamp = 12500);
+    test.contract.claim(&test.claim_addresses[0], &balance_id);
+    assert_eq!(test.token.balance(&test.claim_addresses[0]), 800);
+    assert_eq!(test.token.balance(&test.contract.address), 0);
+}
+
+#[test]
+#[should_panic(expected = "tranches must be ordered by timestamp")]
+fn test_unordered_tranches_not_possible() {
+    let test = ClaimableBalanceTest::setup();
+
+    test.contract.deposit_tranched(
+        &test.deposit_address,
+        &test.token.address,
+        &vec![&test.env, test.claim_addresses[0].clone()],
+        &TimeBound {
+            kind: TimeBoundKind::After,
+            timestamp: 12345,
+        },
+        &vec![
+            &test.env,
+            Tranche { timestamp: 12500, amount: 400 },
+            Tranche { timestamp: 12400, amount: 400 },
+        ],
+    );
+}
+
+#[test]
+fn test_predicate_tree_gates_claims() {
+    let test = ClaimableBalanceTest::setup();
+
+    // Claimable before 12400, or from 12500 until (excluding) 12600
+    let balance_id = test.contract.deposit_with_options(
+        &test.deposit_address,
+        &test.token.address,
+        &800,
+        &vec![&test.env, test.claim_addresses[0].clone()],
+        &TimeBound {
+            kind: TimeBoundKind::After,
+            timestamp: 12345,
+        },
+        &DepositOptions {
+            predicate: Predicate::Or(vec![
+                &test.env,
+                Predicate::BeforeAbs(12400),
+                Predicate::And(vec![
+                    &test.env,
+                    Predicate::AfterAbs(12500),
+                    Predicate::Not(vec![&test.env, Predicate::AfterAbs(12600)]),
+                ]),
+            ]),
+            ..Default::default()
+        },
+    );
+
+    // Neither branch holds in the gap or after the second window closes
+    test.env.ledger().with_mut(|li| li.timestamp = 12450);
+    assert!(test
+        .contract
+        .try_claim(&test.claim_addresses[0], &balance_id)
+        .is_err());
+    test.env.ledger().with_mut(|li| li.timestamp = 12600);
+    assert!(test
+        .contract
+        .try_claim(&test.claim_addresses[0], &balance_id)
+        .is_err());
+
+    test.env.ledger().with_mut(|li| li.timestamp = 12599);
+    test.contract.claim(&test.claim_addresses[0], &balance_id);
+    assert_eq!(test.token.balance(&test.claim_addresses[0]), 800);
+}
+
+#[test]
+fn test_per_claimant_predicates() {
+    let test = ClaimableBalanceTest::setup();
+
+    // The beneficiary can claim any time, the fallback only from 12345 + 30 days
+    let fallback_time = 12345 + 30 * 24 * 60 * 60;
+    let balance_id = test.contract.deposit_with_predicates(
+        &test.deposit_address,
+        &test.token.address,
+        &800,
+        &vec![
+            &test.env,
+            Claimant {
+                address: test.claim_addresses[0].clone(),
+                predicate: Predicate::Unconditional,
+            },
+            Claimant {
+                address: test.claim_addresses[1].clone(),
+                predicate: Predicate::AfterAbs(fallback_time),
+            },
+        ],
+        &TimeBound {
+            kind: TimeBoundKind::After,
+            timestamp: 12345,
+        },
+    );
+
+    assert!(test
+        .contract
+        .try_claim(&test.claim_addresses[1], &balance_id)
+        .is_err());
+
+    // Once the fallback window opens the fallback address can take the balance
+    test.env.ledger().with_mut(|li| li.timestamp = fallback_time);
+    test.contract.claim(&test.claim_addresses[1], &balance_id);
+    assert_eq!(test.token.balance(&test.claim_addresses[1]), 800);
+}
+
+#[test]
+fn test_dead_man_switch_heartbeat_postpones_unlock() {
+    let test = ClaimableBalanceTest::setup();
+
+    // The backup claimant gets the balance after 100 seconds without heartbeat
+    let balance_id = test.contract.deposit_with_options(
+        &test.deposit_address,
+        &test.token.address,
+        &800,
+        &vec![&test.env, test.claim_addresses[0].clone()],
+        &TimeBound {
+            kind: TimeBoundKind::After,
+            timestamp: 12445,
+        },
+        &DepositOptions {
+            dead_man_switch: DeadManSwitch::Heartbeat(100),
+            ..Default::default()
+        },
+    );
+
+    test.env.ledger().with_mut(|li| li.timestamp = 12400);
+    assert_eq!(test.contract.heartbeat(&test.deposit_address, &balance_id), 12500);
+
+    // The heartbeat kept the backup claimant out past the original unlock
+    test.env.ledger().with_mut(|li| li.timestamp = 12450);
+    assert!(test
+        .contract
+        .try_claim(&test.claim_addresses[0], &balance_id)
+        .is_err());
+
+    // Silence for a whole interval fires the switch for good
+    test.env.ledger().with_mut(|li| li.timestamp = 12500);
+    assert!(test
+        .contract
+        .try_heartbeat(&test.deposit_address, &balance_id)
+        .is_err());
+    test.contract.claim(&test.claim_addresses[0], &balance_id);
+    assert_eq!(test.token.balance(&test.claim_addresses[0]), 800);
+}
+
+#[test]
+fn test_multisig_claim_requires_threshold_approvals() {
+    let test = ClaimableBalanceTest::setup();
+    let treasury = Address::generate(&test.env);
+
+    let balance_id = test.contract.deposit_with_options(
+        &test.deposit_address,
+        &test.token.address,
+        &800,
+        &vec![
+            &test.env,
+            test.claim_addresses[0].clone(),
+            test.claim_addresses[1].clone(),
+            test.claim_addresses[2].clone(),
+        ],
+        &TimeBound {
+            kind: TimeBoundKind::Before,
+            timestamp: 12346,
+        },
+        &DepositOptions {
+            multisig: Multisig::Threshold(2),
+            ..Default::default()
+        },
+    );
+
+    // A single claimant can no longer take the balance
+    assert!(test
+        .contract
+        .try_claim(&test.claim_addresses[0], &balance_id)
+        .is_err());
+
+    // Approving another destination discards the earlier approval
+    let [first, second, third] = &test.claim_addresses;
+    assert_eq!(test.contract.approve_claim(first, &balance_id, first), 1);
+    assert_eq!(test.contract.approve_claim(second, &balance_id, &treasury), 1);
+    assert!(test.contract.try_execute_claim(&balance_id, &treasury).is_err());
+
+    assert_eq!(test.contract.approve_claim(third, &balance_id, &treasury), 2);
+    assert_eq!(test.contract.execute_claim(&balance_id, &treasury), 800);
+    assert_eq!(test.token.balance(&treasury), 800);
+    assert!(test.contract.claim_approval(&balance_id).is_none());
+}
+
+#[test]
+#[should_panic(expected = "approval threshold must be between 1 and the number of claimants")]
+fn test_multisig_threshold_above_claimants_not_possible() {
+    let test = ClaimableBalanceTest::setup();
+
+    test.contract.deposit_with_options(
+        &test.deposit_address,
+        &test.token.address,
+        &800,
+        &vec![&test.env, test.claim_addresses[0].clone()],
+        &TimeBound {
+            kind: TimeBoundKind::Before,
+            timestamp: 12346,
+        },
+        &DepositOptions {
+            multisig: Multisig::Threshold(2),
+            ..Default::default()
+        },
+    );
+}
+
+#[test]
+fn test_escrow_releases_on_mutual_confirmation() {
+    let test = ClaimableBalanceTest::setup();
+    let seller = &test.claim_addresses[0];
+
+    let balance_id = test.contract.deposit_with_options(
+        &test.deposit_address,
+        &test.token.address,
+        &800,
+        &vec![&test.env, seller.clone()],
+        &TimeBound {
+            kind: TimeBoundKind::Before,
+            timestamp: 12400,
+        },
+        &DepositOptions {
+            escrow: true,
+            ..Default::default()
+        },
+    );
+
+    // The seller cannot take the funds on its own
+    assert_eq!(test.contract.confirm(seller, &balance_id), 0);
+    assert!(test.contract.try_claim(seller, &balance_id).is_err());
+
+    assert_eq!(test.contract.confirm(&test.deposit_address, &balance_id), 800);
+    assert_eq!(test.token.balance(seller), 800);
+}
+
+#[test]
+fn test_escrow_refunds_buyer_after_timeout() {
+    let test = ClaimableBalanceTest::setup();
+    let seller = &test.claim_addresses[0];
+
+    let balance_id = test.contract.deposit_with_options(
+        &test.deposit_address,
+        &test.token.address,
+        &800,
+        &vec![&test.env, seller.clone()],
+        &TimeBound {
+            kind: TimeBoundKind::Before,
+            timestamp: 12400,
+        },
+        &DepositOptions {
+            escrow: true,
+            ..Default::default()
+        },
+    );
+    test.contract.confirm(&test.deposit_address, &balance_id);
+
+    // The seller never confirmed before the timeout
+    test.env.ledger().with_mut(|li| li.timestamp = 12401);
+    assert!(test.contract.try_confirm(seller, &balance_id).is_err());
+    assert_eq!(test.contract.refund(&test.deposit_address, &balance_id), 800);
+    assert_eq!(test.token.balance(&test.deposit_address), 1000);
+}
+
+#[test]
+fn test_arbiter_resolves_disputed_escrow() {
+    let test = ClaimableBalanceTest::setup();
+    let seller = &test.claim_addresses[0];
+    let arbiter = Address::generate(&test.env);
+
+    let balance_id = test.contract.deposit_with_options(
+        &test.deposit_address,
+        &test.token.address,
+        &800,
+        &vec![&test.env, seller.clone()],
+        &TimeBound {
+            kind: TimeBoundKind::Before,
+            timestamp: 12400,
+        },
+        &DepositOptions {
+            escrow: true,
+            arbiter: Some(arbiter.clone()),
+            ..Default::default()
+        },
+    );
+
+    // A dispute freezes the escrow, even past its timeout
+    test.contract.raise_dispute(seller, &balance_id);
+    assert!(test.contract.try_confirm(&test.deposit_address, &balance_id).is_err());
+    test.env.ledger().with_mut(|li| li.timestamp = 12401);
+    assert!(test.contract.try_refund(&test.deposit_address, &balance_id).is_err());
+
+    test.contract.resolve(&balance_id, &300, &500);
+    assert_eq!(test.token.balance(&test.deposit_address), 500);
+    assert_eq!(test.token.balance(seller), 500);
+}
+
+#[test]
+fn test_get_balance() {
+    let test = ClaimableBalanceTest::setup();
+
+    let balance_id = test.contract.deposit(
+        &test.deposit_address,
+        &test.token.address,
+        &800,
+        &vec![&test.env, test.claim_addresses[0].clone()],
+        &TimeBound {
+            kind: TimeBoundKind::Before,
+            timestamp: 12346,
+        },
+    );
+
+    let claimable_balance = test.contract.get_balance(&balance_id);
+    assert_eq!(claimable_balance.depositor, test.deposit_address);
+    assert_eq!(claimable_balance.amount, 800);
+    assert_eq!(claimable_balance.claimed, 0);
+    assert_eq!(claimable_balance.created_at, 12345);
+
+    // Settled balances are gone
+    test.contract.claim(&test.claim_addresses[0], &balance_id);
+    assert!(test.contract.try_get_balance(&balance_id).is_err());
+}
+
+#[test]
+fn test_is_claimable() {
+    let test = ClaimableBalanceTest::setup();
+
+    let balance_id = test.contract.deposit(
+        &test.deposit_address,
+        &test.token.address,
+        &800,
+        &vec![&test.env, test.claim_addresses[0].clone()],
+        &TimeBound {
+            kind: TimeBoundKind::After,
+            timestamp: 12400,
+        },
+    );
+
+    assert!(!test.contract.is_claimable(&balance_id, &test.claim_addresses[0]));
+
+    test.env.ledger().with_mut(|li| li.timestamp = 12400);
+    assert!(test.contract.is_claimable(&balance_id, &test.claim_addresses[0]));
+    assert!(!test.contract.is_claimable(&balance_id, &test.claim_addresses[1]));
+    assert!(!test.contract.is_claimable(&(balance_id + 1), &test.claim_addresses[0]));
+}
+
+#[test]
+fn test_next_unlock_time_follows_tranches() {
+    let test = ClaimableBalanceTest::setup();
+
+    let balance_id = test.contract.deposit_tranched(
+        &test.deposit_address,
+        &test.token.address,
+        &vec![&test.env, test.claim_addresses[0].clone()],
+        &TimeBound {
+            kind: TimeBoundKind::After,
+            timestamp: 12400,
+        },
+        &vec![
+            &test.env,
+            Tranche { timestamp: 12345, amount: 100 },
+            Tranche { timestamp: 12500, amount: 700 },
+        ],
+    );
+
+    // The time bound opens after the first tranche matured
+    assert_eq!(test.contract.next_unlock_time(&balance_id), Some(12400));
+    assert_eq!(test.contract.time_remaining(&balance_id), Some(55));
+
+    test.env.ledger().with_mut(|li| li.timestamp = 12400);
+    assert_eq!(test.contract.time_remaining(&balance_id), Some(0));
+    test.contract.claim(&test.claim_addresses[0], &balance_id);
+    assert_eq!(test.contract.next_unlock_time(&balance_id), Some(12500));
+    assert_eq!(test.contract.time_remaining(&balance_id), Some(100));
+}
+
+#[test]
+fn test_time_remaining_of_expired_balance() {
+    let test = ClaimableBalanceTest::setup();
+
+    let balance_id = test.contract.deposit(
+        &test.deposit_address,
+        &test.token.address,
+        &800,
+        &vec![&test.env, test.claim_addresses[0].clone()],
+        &TimeBound {
+            kind: TimeBoundKind::Before,
+            timestamp: 12346,
+        },
+    );
+    assert_eq!(test.contract.next_unlock_time(&balance_id), Some(12345));
+
+    test.env.ledger().with_mut(|li| li.timestamp = 12347);
+    assert_eq!(test.contract.time_remaining(&balance_id), None);
+}
+
+#[test]
+fn test_list_balances_by_claimant_and_depositor() {
+    let test = ClaimableBalanceTest::setup();
+
+    let mut balance_ids = vec![&test.env];
+    for claimant in test.claim_addresses.iter() {
+        balance_ids.push_back(test.contract.deposit(
+            &test.deposit_address,
+            &test.token.address,
+            &100,
+            &vec![&test.env, claimant.clone(), test.claim_addresses[0].clone()],
+            &TimeBound {
+                kind: TimeBoundKind::Before,
+                timestamp: 12346,
+            },
+        ));
+    }
+
+    assert_eq!(test.contract.list_for_depositor(&test.deposit_address, &0, &10), balance_ids);
+    assert_eq!(
+        test.contract.list_for_claimant(&test.claim_addresses[0], &1, &10),
+        balance_ids.slice(1..3)
+    );
+    assert_eq!(
+        test.contract.list_for_claimant(&test.claim_addresses[2], &0, &10),
+        vec![&test.env, balance_ids.get_unchecked(2)]
+    );
+
+    // Settled balances leave the indexes
+    test.contract.claim(&test.claim_addresses[0], &balance_ids.get_unchecked(0));
+    assert_eq!(
+        test.contract.list_for_claimant(&test.claim_addresses[0], &0, &1),
+        vec![&test.env, balance_ids.get_unchecked(1)]
+    );
+    assert_eq!(test.contract.list_for_depositor(&test.deposit_address, &5, &10).len(), 0);
+}
+
+#[test]
+fn test_total_locked_tracks_deposits_claims_and_refunds() {
+    let test = ClaimableBalanceTest::setup();
+
+    let claimed_id = test.contract.deposit(
+        &test.deposit_address,
+        &test.token.address,
+        &300,
+        &vec![&test.env, test.claim_addresses[0].clone()],
+        &TimeBound {
+            kind: TimeBoundKind::Before,
+            timestamp: 12346,
+        },
+    );
+    let refunded_id = test.contract.deposit(
+        &test.deposit_address,
+        &test.token.address,
+        &500,
+        &vec![&test.env, test.claim_addresses[1].clone()],
+        &TimeBound {
+            kind: TimeBoundKind::Before,
+            timestamp: 12346,
+        },
+    );
+    assert_eq!(test.contract.total_locked(&test.token.address), 800);
+    assert_eq!(test.contract.tokens(), vec![&test.env, test.token.address.clone()]);
+
+    test.contract.claim(&test.claim_addresses[0], &claimed_id);
+    assert_eq!(test.contract.total_locked(&test.token.address), 500);
+
+    test.env.ledger().with_mut(|li| li.timestamp = 12347);
+    test.contract.refund(&test.deposit_address, &refunded_id);
+    assert_eq!(test.contract.total_locked(&test.token.address), 0);
+}
+
+#[test]
+fn test_claim_history_outlives_balance() {
+    let test = ClaimableBalanceTest::setup();
+
+    let balance_id = test.contract.deposit_tranched(
+        &test.deposit_address,
+        &test.token.address,
+        &vec![&test.env, test.claim_addresses[0].clone()],
+        &TimeBound {
+            kind: TimeBoundKind::After,
+            timestamp: 12345,
+        },
+        &vec![
+            &test.env,
+            Tranche { timestamp: 12345, amount: 300 },
+            Tranche { timestamp: 12400, amount: 500 },
+        ],
+    );
+
+    test.contract.claim(&test.claim_addresses[0], &balance_id);
+    test.env.ledger().with_mut(|li| li.timestamp = 12400);
+    test.contract.claim(&test.claim_addresses[0], &balance_id);
+
+    assert_eq!(test.contract.claim_count(&balance_id), 2);
+    assert_eq!(
+        test.contract.claim_history(&balance_id, &1, &10),
+        vec![
+            &test.env,
+            ClaimRecord {
+                claimant: test.claim_addresses[0].clone(),
+                amount: 500,
+                timestamp: 12400,
+            },
+        ]
+    );
+    assert_eq!(
+        test.contract.claim_history(&balance_id, &0, &10),
+        vec![
+            &test.env,
+            ClaimRecord {
+                claimant: test.claim_addresses[0].clone(),
+                amount: 300,
+                timestamp: 12345,
+            },
+            ClaimRecord {
+                claimant: test.claim_addresses[0].clone(),
+                amount: 500,
+                timestamp: 12400,
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_deposit_validation_error_codes() {
+    let test = ClaimableBalanceTest::setup();
+    let claimants = vec![&test.env, test.claim_addresses[0].clone()];
+    let time_bound = TimeBound {
+        kind: TimeBoundKind::After,
+        timestamp: 12345,
+    };
+
+    assert_eq!(
+        test.contract
+            .try_deposit(&test.deposit_address, &test.token.address, &0, &claimants, &time_bound),
+        Err(Ok(DepositError::InvalidAmount.into()))
+    );
+    assert_eq!(
+        test.contract.try_deposit(
+            &test.deposit_address,
+            &test.token.address,
+            &800,
+            &vec![&test.env],
+            &time_bound
+        ),
+        Err(Ok(DepositError::NoClaimants.into()))
+    );
+    assert_eq!(
+        test.contract.try_deposit(
+            &test.deposit_address,
+            &test.token.address,
+            &800,
+            &claimants,
+            &TimeBound {
+                kind: TimeBoundKind::After,
+                timestamp: 12344,
+            }
+        ),
+        Err(Ok(DepositError::UnlockInPast.into()))
+    );
+}
+
+#[test]
+fn test_deposit_deduplicates_claimants() {
+    let test = ClaimableBalanceTest::setup();
+
+    let balance_id = test.contract.deposit(
+        &test.deposit_address,
+        &test.token.address,
+        &800,
+        &vec![
+            &test.env,
+            test.claim_addresses[0].clone(),
+            test.claim_addresses[1].clone(),
+            test.claim_addresses[0].clone(),
+        ],
+        &TimeBound {
+            kind: TimeBoundKind::Before,
+            timestamp: 12346,
+        },
+    );
+
+    assert_eq!(
+        test.contract.get_balance(&balance_id).claimants,
+        vec![&test.env, test.claim_addresses[0].clone(), test.claim_addresses[1].clone()]
+    );
+}
+
+#[test]
+fn test_protocol_fee_split_and_exemption() {
+    let test = ClaimableBalanceTest::setup();
+    let fee_collector = Address::generate(&test.env);
+
+    // 2.5% fee, with the second claimant exempt
+    test.contract.update_config(&Config {
+        fee_bps: 250,
+        fee_collector: Some(fee_collector.clone()),
+        fee_exempt: vec![&test.env, test.claim_addresses[1].clone()],
+        ..test.contract.get_config()
+    });
+
+    let balance_id = test.contract.deposit_allocated(
+        &test.deposit_address,
+        &test.token.address,
+        &vec![
+            &test.env,
+            (test.claim_addresses[0].clone(), 400),
+            (test.claim_addresses[1].clone(), 400),
+        ],
+        &TimeBound {
+            kind: TimeBoundKind::Before,
+            timestamp: 12346,
+        },
+    );
+
+    test.contract.claim(&test.claim_addresses[0], &balance_id);
+    assert_eq!(test.token.balance(&test.claim_addresses[0]), 390);
+    assert_eq!(test.token.balance(&fee_collector), 10);
+
+    test.contract.claim(&test.claim_addresses[1], &balance_id);
+    assert_eq!(test.token.balance(&test.claim_addresses[1]), 400);
+    assert_eq!(test.token.balance(&fee_collector), 10);
+}
+
+#[test]
+fn test_fee_on_transfer_deposit_locks_received_amount() {
+    let test = ClaimableBalanceTest::setup();
+
+    let fee_token = MockFeeTokenClient::new(&test.env, &test.env.register(MockFeeToken, ()));
+    fee_token.mint(&test.deposit_address, &1000);
+
+    // 1% of the 800 tokens is burnt on the way in
+    let balance_id = test.contract.deposit(
+        &test.deposit_address,
+        &fee_token.address,
+        &800,
+        &vec![&test.env, test.claim_addresses[0].clone()],
+        &TimeBound {
+            kind: TimeBoundKind::Before,
+            timestamp: 12346,
+        },
+    );
+    assert_eq!(test.contract.get_balance(&balance_id).amount, 792);
+    assert_eq!(test.contract.total_locked(&fee_token.address), 792);
+
+    test.contract.claim(&test.claim_addresses[0], &balance_id);
+    assert_eq!(fee_token.balance(&test.contract.address), 0);
+}
+
+#[test]
+fn test_basket_is_claimed_and_refunded_whole() {
+    let test = ClaimableBalanceTest::setup();
+
+    let (stablecoin, stablecoin_admin) =
+        create_token_contract(&test.env, &Address::generate(&test.env));
+    stablecoin_admin.mint(&test.deposit_address, &1200);
+
+    let assets = vec![
+        &test.env,
+        (test.token.address.clone(), 400),
+        (stablecoin.address.clone(), 600),
+    ];
+    let claimed_id = test.contract.deposit_basket(
+        &test.deposit_address,
+        &assets,
+        &vec![&test.env, test.claim_addresses[0].clone()],
+        &TimeBound {
+            kind: TimeBoundKind::Before,
+            timestamp: 12346,
+        },
+    );
+    let refunded_id = test.contract.deposit_basket(
+        &test.deposit_address,
+        &assets,
+        &vec![&test.env, test.claim_addresses[1].clone()],
+        &TimeBound {
+            kind: TimeBoundKind::Before,
+            timestamp: 12346,
+        },
+    );
+    assert_eq!(test.contract.total_locked(&stablecoin.address), 1200);
+
+    test.contract.claim(&test.claim_addresses[0], &claimed_id);
+    assert_eq!(test.token.balance(&test.claim_addresses[0]), 400);
+    assert_eq!(stablecoin.balance(&test.claim_addresses[0]), 600);
+
+    test.env.ledger().with_mut(|li| li.timestamp = 12347);
+    test.contract.refund(&test.deposit_address, &refunded_id);
+    assert_eq!(stablecoin.balance(&test.deposit_address), 600);
+    assert_eq!(stablecoin.balance(&test.contract.address), 0);
+    assert_eq!(test.contract.total_locked(&stablecoin.address), 0);
+}
+
+#[test]
+fn test_top_up_before_unlock() {
+    let test = ClaimableBalanceTest::setup();
+
+    let balance_id = test.contract.deposit(
+        &test.deposit_address,
+        &test.token.address,
+        &500,
+        &vec![&test.env, test.claim_addresses[0].clone()],
+        &TimeBound {
+            kind: TimeBoundKind::After,
+            timestamp: 12400,
+        },
+    );
+
+    assert_eq!(test.contract.top_up(&balance_id, &300), 800);
+    assert_eq!(test.contract.get_balance(&balance_id).amount, 800);
+    assert_eq!(test.token.balance(&test.contract.address), 800);
+
+    // Once unlocked the balance can only be claimed
+    test.env.ledger().with_mut(|li| li.timestamp = 12400);
+    assert!(test.contract.try_top_up(&balance_id, &100).is_err());
+    test.contract.claim(&test.claim_addresses[0], &balance_id);
+    assert_eq!(test.token.balance(&test.claim_addresses[0]), 800);
+}
+
+#[test]
+fn test_deposit_batch_funds_all_entries_at_once() {
+    let test = ClaimableBalanceTest::setup();
+
+    let entries = vec![
+        &test.env,
+        DepositEntry {
+            amount: 300,
+            claimants: vec![&test.env, test.claim_addresses[0].clone()],
+            time_bound: TimeBound {
+                kind: TimeBoundKind::Before,
+                timestamp: 12346,
+            },
+        },
+        DepositEntry {
+            amount: 500,
+            claimants: vec![&test.env, test.claim_addresses[1].clone()],
+            time_bound: TimeBound {
+                kind: TimeBoundKind::After,
+                timestamp: 12400,
+            },
+        },
+    ];
+    let balance_ids = test
+        .contract
+        .deposit_batch(&test.deposit_address, &test.token.address, &entries);
+    assert_eq!(balance_ids.len(), 2);
+    assert_eq!(test.token.balance(&test.contract.address), 800);
+
+    // Each entry became its own balance
+    test.contract.claim(&test.claim_addresses[0], &balance_ids.get_unchecked(0));
+    assert_eq!(test.token.balance(&test.claim_addresses[0]), 300);
+    assert_eq!(test.contract.get_balance(&balance_ids.get_unchecked(1)).amount, 500);
+}
+
+#[test]
+fn test_claim_all_claims_every_ready_balance() {
+    let test = ClaimableBalanceTest::setup();
+    let claimant = &test.claim_addresses[0];
+
+    let unlocked_id = test.contract.deposit(
+        &test.deposit_address,
+        &test.token.address,
+        &300,
+        &vec![&test.env, claimant.clone()],
+        &TimeBound {
+            kind: TimeBoundKind::Before,
+            timestamp: 12400,
+        },
+    );
+    let locked_id = test.contract.deposit(
+        &test.deposit_address,
+        &test.token.address,
+        &200,
+        &vec![&test.env, claimant.clone()],
+        &TimeBound {
+            kind: TimeBoundKind::After,
+            timestamp: 12400,
+        },
+    );
+    let vesting_id = test.contract.deposit_with_options(
+        &test.deposit_address,
+        &test.token.address,
+        &300,
+        &vec![&test.env, claimant.clone()],
+        &TimeBound {
+            kind: TimeBoundKind::After,
+            timestamp: 12345,
+        },
+        &DepositOptions {
+            vesting: Vesting::Linear(VestingSchedule {
+                start: 12345,
+                cliff: 12345,
+                end: 12445,
+            }),
+            ..Default::default()
+        },
+    );
+
+    // A third of the vesting balance vested; the locked balance is skipped
+    test.env.ledger().with_mut(|li| li.timestamp = 12378);
+    assert_eq!(
+        test.contract.claim_all(claimant, &1),
+        vec![&test.env, (unlocked_id, test.token.address.clone(), 300)]
+    );
+    assert_eq!(
+        test.contract.claim_all(claimant, &10),
+        vec![&test.env, (vesting_id, test.token.address.clone(), 99)]
+    );
+    assert_eq!(test.token.balance(claimant), 399);
+    assert_eq!(test.contract.get_balance(&locked_id).amount, 200);
+}
+
+/// Utility function returning a signing key and the Stellar account address it controls.
+fn signing_account(env: &Env, seed: u8) -> (ed25519_dalek::SigningKey, Address, BytesN<32>) {
+    use soroban_sdk::xdr::FromXdr;
+
+    let signing_key = ed25519_dalek::SigningKey::from_bytes(&[seed; 32]);
+    let public_key = signing_key.verifying_key().to_bytes();
+    let mut xdr = Bytes::from_array(env, &[0, 0, 0, 18, 0, 0, 0, 0, 0, 0, 0, 0]);
+    xdr.extend_from_array(&public_key);
+    let address = Address::from_xdr(env, &xdr).unwrap();
+    (signing_key, address, BytesN::from_array(env, &public_key))
+}
+
+/// Utility function signing a claim the way a claimant's wallet would.
+fn sign_claim(
+    contract: &ClaimableBalanceContractClient,
+    signing_key: &ed25519_dalek::SigningKey,
+    claim: &SignedClaim,
+) -> BytesN<64> {
+    use ed25519_dalek::Signer;
+
+    let message = contract.claim_message(claim);
+    let mut buffer = [0u8; 256];
+    let len = message.len() as usize;
+    message.copy_into_slice(&mut buffer[..len]);
+    BytesN::from_array(&contract.env, &signing_key.sign(&buffer[..len]).to_bytes())
+}
+
+#[test]
+fn test_claim_with_signature_by_relayer() {
+    let test = ClaimableBalanceTest::setup();
+    let (signing_key, claimant, public_key) = signing_account(&test.env, 7);
+    let destination = &test.claim_addresses[1];
+
+    let balance_id = test.contract.deposit(
+        &test.deposit_address,
+        &test.token.address,
+        &500,
+        &vec![&test.env, claimant.clone()],
+        &TimeBound {
+            kind: TimeBoundKind::Before,
+            timestamp: 12400,
+        },
+    );
+
+    let claim = SignedClaim {
+        balance_id,
+        destination: destination.clone(),
+        expiry: 12350,
+        nonce: 1,
+    };
+    let signature = sign_claim(&test.contract, &signing_key, &claim);
+
+    // No authorization is needed from the relayer or the claimant
+    test.env.set_auths(&[]);
+    assert_eq!(
+        test.contract
+            .claim_with_signature(&claimant, &public_key, &claim, &signature),
+        500
+    );
+    assert_eq!(test.token.balance(destination), 500);
+}
+
+#[test]
+fn test_claim_with_signature_rejects_replay_expiry_and_tampering() {
+    let test = ClaimableBalanceTest::setup();
+    let (signing_key, claimant, public_key) = signing_account(&test.env, 7);
+    let (_, other_account, other_key) = signing_account(&test.env, 8);
+
+    let deposit = || {
+        test.contract.deposit(
+            &test.deposit_address,
+            &test.token.address,
+            &100,
+            &vec![&test.env, claimant.clone()],
+            &TimeBound {
+                kind: TimeBoundKind::Before,
+                timestamp: 12400,
+            },
+        )
+    };
+    let first_id = deposit();
+    let second_id = deposit();
+
+    let claim = SignedClaim {
+        balance_id: first_id,
+        destination: test.claim_addresses[1].clone(),
+        expiry: 12350,
+        nonce: 1,
+    };
+    let signature = sign_claim(&test.contract, &signing_key, &claim);
+
+    // The key has to belong to the claimant account
+    assert!(test
+        .contract
+        .try_claim_with_signature(&other_account, &other_key, &claim, &signature)
+        .is_err());
+
+    // A relayer cannot redirect the payout
+    let redirected = SignedClaim {
+        destination: test.claim_addresses[0].clone(),
+        ..claim.clone()
+    };
+    assert!(test
+        .contract
+        .try_claim_with_signature(&claimant, &public_key, &redirected, &signature)
+        .is_err());
+
+    test.contract
+        .claim_with_signature(&claimant, &public_key, &claim, &signature);
+
+    // A used nonce cannot be spent again, even on another balance
+    let reused = SignedClaim {
+        balance_id: second_id,
+        ..claim.clone()
+    };
+    let reused_signature = sign_claim(&test.contract, &signing_key, &reused);
+    assert!(test
+        .contract
+        .try_claim_with_signature(&claimant, &public_key, &reused, &reused_signature)
+        .is_err());
+
+    // Expired signatures are refused
+    let late = SignedClaim {
+        balance_id: second_id,
+        nonce: 2,
+        ..claim.clone()
+    };
+    let late_signature = sign_claim(&test.contract, &signing_key, &late);
+    test.env.ledger().with_mut(|li| li.timestamp = 12351);
+    assert!(test
+        .contract
+        .try_claim_with_signature(&claimant, &public_key, &late, &late_signature)
+        .is_err());
+    assert_eq!(test.token.balance(&test.claim_addresses[1]), 100);
+}
+
+#[test]
+fn test_gate_decides_who_can_claim() {
+    let test = ClaimableBalanceTest::setup();
+    let gate = MockGateClient::new(&test.env, &test.env.register(MockGate, ()));
+
+    let balance_id = test.contract.deposit_with_options(
+        &test.deposit_address,
+        &test.token.address,
+        &800,
+        &vec![&test.env, test.claim_addresses[0].clone()],
+        &TimeBound {
+            kind: TimeBoundKind::Before,
+            timestamp: 12400,
+        },
+        &DepositOptions {
+            gate: Some(gate.address.clone()),
+            ..Default::default()
+        },
+    );
+
+    // The gate has not admitted the claimant yet
+    assert!(test
+        .contract
+        .try_claim(&test.claim_addresses[0], &balance_id)
+        .is_err());
+    assert!(!test.contract.is_claimable(&balance_id, &test.claim_addresses[0]));
+
+    gate.allow(&test.claim_addresses[0]);
+    test.contract.claim(&test.claim_addresses[0], &balance_id);
+    assert_eq!(test.token.balance(&test.claim_addresses[0]), 800);
+}
+
+#[test]
+fn test_price_predicate_unlocks_on_oracle_price() {
+    let test = ClaimableBalanceTest::setup();
+    let oracle = MockOracleClient::new(&test.env, &test.env.register(MockOracle, ()));
+
+    // Claimable once the asset trades above 100, within the time bound
+    let balance_id = test.contract.deposit_with_options(
+        &test.deposit_address,
+        &test.token.address,
+        &800,
+        &vec![&test.env, test.claim_addresses[0].clone()],
+        &TimeBound {
+            kind: TimeBoundKind::Before,
+            timestamp: 12400,
+        },
+        &DepositOptions {
+            predicate: Predicate::Price(PriceCondition {
+                oracle: oracle.address.clone(),
+                asset: OracleAsset::Stellar(test.token.address.clone()),
+                comparator: Comparator::Above,
+                price: 100,
+            }),
+            ..Default::default()
+        },
+    );
+
+    // Without a price, or below the target, the balance stays locked
+    assert!(!test.contract.is_claimable(&balance_id, &test.claim_addresses[0]));
+    oracle.set_price(&100);
+    assert_eq!(
+        test.contract.try_claim(&test.claim_addresses[0], &balance_id),
+        Err(Ok(ClaimError::OracleConditionNotMet.into()))
+    );
+
+    oracle.set_price(&101);
+    test.contract.claim(&test.claim_addresses[0], &balance_id);
+    assert_eq!(test.token.balance(&test.claim_addresses[0]), 800);
+}
+
+#[test]
+fn test_extend_ttl_keeps_long_locks_alive() {
+    use soroban_sdk::testutils::storage::{Instance as _, Persistent as _};
+
+    let test = ClaimableBalanceTest::setup();
+    let balance_id = test.contract.deposit(
+        &test.deposit_address,
+        &test.token.address,
+        &800,
+        &vec![&test.env, test.claim_addresses[0].clone()],
+        &TimeBound {
+            kind: TimeBoundKind::Before,
+            timestamp: 12400,
+        },
+    );
+    let balance_ttl = || {
+        test.env.as_contract(&test.contract.address, || {
+            test.env
+                .storage()
+                .persistent()
+                .get_ttl(&DataKey::Balance(balance_id))
+        })
+    };
+
+    // Deposits extend the TTL of the new balance right away
+    assert_eq!(balance_ttl(), 30 * 17280);
+
+    // Anyone can push it further, up to the network maximum
+    test.env.set_auths(&[]);
+    assert_eq!(test.contract.extend_ttl(&balance_id, &(365 * 17280)), 365 * 17280);
+    assert_eq!(balance_ttl(), 365 * 17280);
+    let max_ttl = test.contract.extend_ttl(&balance_id, &u32::MAX);
+    assert!(max_ttl < u32::MAX);
+    assert_eq!(balance_ttl(), max_ttl);
+    test.env.as_contract(&test.contract.address, || {
+        assert_eq!(test.env.storage().instance().get_ttl(), max_ttl);
+    });
+}
+
+#[test]
+fn test_published_client_drives_the_contract() {
+    let test = ClaimableBalanceTest::setup();
+    let client = interface::ClaimableBalanceClient::new(&test.env, &test.contract.address);
+
+    let balance_id = client.deposit(
+        &test.deposit_address,
+        &test.token.address,
+        &800,
+        &vec![&test.env, test.claim_addresses[0].clone()],
+        &TimeBound {
+            kind: TimeBoundKind::Before,
+            timestamp: 12400,
+        },
+    );
+    assert_eq!(client.get_balance(&balance_id).amount, 800);
+    assert_eq!(
+        client.list_for_claimant(&test.claim_addresses[0], &0, &10),
+        vec![&test.env, balance_id]
+    );
+    assert!(client.is_claimable(&balance_id, &test.claim_addresses[0]));
+
+    client.claim(&test.claim_addresses[0], &balance_id);
+    assert_eq!(client.total_locked(&test.token.address), 0);
+    assert_eq!(test.token.balance(&test.claim_addresses[0]), 800);
+}
+
+#[test]
+fn test_core_time_bounds_and_predicates_without_contract() {
+    let env = Env::default();
+    let window = TimeBound {
+        kind: TimeBoundKind::Between(200),
+        timestamp: 100,
+    };
+    assert!(!timelock_core::time_bound_holds(&window, 99));
+    assert!(timelock_core::time_bound_holds(&window, 200));
+    assert!(!timelock_core::time_bound_expired(&window, 200));
+    assert!(timelock_core::time_bound_expired(&window, 201));
+
+    // Price conditions are answered by the caller instead of an oracle call
+    let predicate = Predicate::Or(vec![
+        &env,
+        Predicate::AfterAbs(500),
+        Predicate::Price(PriceCondition {
+            oracle: Address::generate(&env),
+            asset: OracleAsset::Other(symbol_short!("XLM")),
+            comparator: Comparator::Above,
+            price: 100,
+        }),
+    ]);
+    assert!(!timelock_core::evaluate_predicate(&predicate, 400, &|_| false));
+    assert!(timelock_core::evaluate_predicate(&predicate, 400, &|_| true));
+    assert!(timelock_core::evaluate_predicate(&predicate, 500, &|_| false));
+}
+
+#[test]
+fn test_core_vesting_math_and_deposit_checks() {
+    let test = ClaimableBalanceTest::setup();
+    let env = test.env.clone();
+    let vesting = VestingSchedule {
+        start: 100,
+        cliff: 150,
+        end: 200,
+    };
+    assert_eq!(timelock_core::linear_vested(&vesting, 1000, 149), Ok(0));
+    assert_eq!(timelock_core::linear_vested(&vesting, 1000, 150), Ok(500));
+    assert_eq!(timelock_core::linear_vested(&vesting, 1000, 250), Ok(1000));
+
+    let tranches = vec![
+        &env,
+        Tranche {
+            timestamp: 100,
+            amount: 10,
+        },
+        Tranche {
+            timestamp: 200,
+            amount: 90,
+        },
+    ];
+    assert_eq!(timelock_core::matured_tranches(&tranches, 0, 150), Ok((10, 1)));
+    assert_eq!(timelock_core::matured_tranches(&tranches, 1, 200), Ok((90, 2)));
+
+    // Balances are built in the contract's context, which provides the deposit time
+    let claimant = Address::generate(&env);
+    let claimable_balance = env.as_contract(&test.contract.address, || {
+        ClaimableBalance::new(
+            &env,
+            test.deposit_address.clone(),
+            test.token.address.clone(),
+            100,
+            timelock_core::dedup_claimants(&vec![&env, claimant.clone(), claimant.clone()]),
+            TimeBound {
+                kind: TimeBoundKind::After,
+                timestamp: 50,
+            },
+        )
+    });
+    assert_eq!(claimable_balance.claimants, vec![&env, claimant]);
+    let config = default_config(&env);
+    assert_eq!(timelock_core::check_deposit(&claimable_balance, &config, 50), Ok(()));
+    assert_eq!(
+        timelock_core::check_deposit(&claimable_balance, &config, 51),
+        Err(DepositError::UnlockInPast)
+    );
+}
+
+#[test]
+fn test_time_travel_moves_clock_and_sequence() {
+    let test = ClaimableBalanceTest::setup();
+    let balance_id = test.contract.deposit(
+        &test.deposit_address,
+        &test.token.address,
+        &800,
+        &vec![&test.env, test.claim_addresses[0].clone()],
+        &TimeBound {
+            kind: TimeBoundKind::After,
+            timestamp: 12445,
+        },
+    );
+    let sequence = test.env.ledger().sequence();
+
+    test.env.travel_by(50);
+    assert_eq!(test.contract.time_remaining(&balance_id), Some(50));
+    assert_eq!(test.env.ledger().sequence(), sequence + 10);
+
+    test.env.travel_to(12445);
+    test.contract.claim(&test.claim_addresses[0], &balance_id);
+    assert_eq!(test.token.balance(&test.claim_addresses[0]), 800);
+}
+
+#[test]
+fn test_claim_windows_pass_the_turn_to_fallback_claimant() {
+    let test = ClaimableBalanceTest::setup();
+    let primary = &test.claim_addresses[0];
+    let fallback = &test.claim_addresses[1];
+
+    // The primary claimant has [12400, 12500], the fallback (12500, 12600]; the depositor reclaims after 12700
+    let windows = vec![
+        &test.env,
+        ClaimWindow {
+            claimant: primary.clone(),
+            start: 12400,
+            end: 12500,
+        },
+        ClaimWindow {
+            claimant: fallback.clone(),
+            start: 12501,
+            end: 12600,
+        },
+    ];
+    let balance_id = test.contract.deposit_with_windows(
+        &test.deposit_address,
+        &test.token.address,
+        &800,
+        &windows,
+        &12700,
+    );
+
+    // Nobody's turn yet, then only the primary's
+    assert!(test.contract.try_claim(primary, &balance_id).is_err());
+    test.env.travel_to(12450);
+    assert!(test.contract.try_claim(fallback, &balance_id).is_err());
+    assert!(test.contract.is_claimable(&balance_id, primary));
+
+    // The primary let its window pass, so the fallback takes it
+    test.env.travel_to(12501);
+    assert!(test.contract.try_claim(primary, &balance_id).is_err());
+    test.contract.claim(fallback, &balance_id);
+    assert_eq!(test.token.balance(fallback), 800);
+}
+
+#[test]
+fn test_claim_windows_reclaimed_by_depositor_after_deadline() {
+    let test = ClaimableBalanceTest::setup();
+    let windows = vec![
+        &test.env,
+        ClaimWindow {
+            claimant: test.claim_addresses[0].clone(),
+            start: 12345,
+            end: 12400,
+        },
+    ];
+
+    // Overlapping windows are rejected
+    let mut overlapping = windows.clone();
+    overlapping.push_back(ClaimWindow {
+        claimant: test.claim_addresses[1].clone(),
+        start: 12399,
+        end: 12500,
+    });
+    assert!(test
+        .contract
+        .try_deposit_with_windows(&test.deposit_address, &test.token.address, &800, &overlapping, &12600)
+        .is_err());
+
+    let balance_id = test.contract.deposit_with_windows(
+        &test.deposit_address,
+        &test.token.address,
+        &800,
+        &windows,
+        &12500,
+    );
+
+    // Between the last window and the deadline neither side can take the tokens
+    test.env.travel_to(12450);
+    assert!(test.contract.try_claim(&test.claim_addresses[0], &balance_id).is_err());
+    assert!(test.contract.try_refund(&test.deposit_address, &balance_id).is_err());
+
+    test.env.travel_to(12501);
+    assert_eq!(test.contract.refund(&test.deposit_address, &balance_id), 800);
+    assert_eq!(test.token.balance(&test.deposit_address), 1000);
+}
+
+#[test]
+fn test_claimant_rotation_needs_depositor_approval() {
+    let test = ClaimableBalanceTest::setup();
+    let old_key = &test.claim_addresses[0];
+    let new_key = &test.claim_addresses[1];
+    let attacker = &test.claim_addresses[2];
+
+    let balance_id = test.contract.deposit_with_options(
+        &test.deposit_address,
+        &test.token.address,
+        &1000,
+        &vec![&test.env, old_key.clone()],
+        &TimeBound {
+            kind: TimeBoundKind::After,
+            timestamp: 12345,
+        },
+        &DepositOptions {
+            vesting: Vesting::Linear(VestingSchedule {
+                start: 12345,
+                cliff: 12345,
+                end: 12445,
+            }),
+            ..Default::default()
+        },
+    );
+    test.env.travel_to(12395);
+    assert_eq!(test.contract.claim_vested(old_key, &balance_id), 500);
+
+    // The rotation only takes effect for the address the depositor approves
+    test.contract.propose_rotation(old_key, &balance_id, new_key);
+    assert!(test
+        .contract
+        .try_approve_rotation(&balance_id, old_key, attacker)
+        .is_err());
+    test.contract.approve_rotation(&balance_id, old_key, new_key);
+    assert_eq!(test.env.auths()[0].0, test.deposit_address);
+
+    // The new key takes over the grant and its claim progress
+    assert_eq!(test.contract.get_balance(&balance_id).claimants, vec![&test.env, new_key.clone()]);
+    assert_eq!(test.contract.list_for_claimant(old_key, &0, &10).len(), 0);
+    assert_eq!(
+        test.contract.list_for_claimant(new_key, &0, &10),
+        vec![&test.env, balance_id]
+    );
+    test.env.travel_to(12445);
+    assert!(test.contract.try_claim_vested(old_key, &balance_id).is_err());
+    assert_eq!(test.contract.claim_vested(new_key, &balance_id), 500);
+    assert_eq!(test.token.balance(new_key), 500);
+}
+
+#[test]
+fn test_only_depositor_edits_claimants_before_unlock() {
+    let test = ClaimableBalanceTest::setup();
+    let [original, added, stranger] = test.claim_addresses.clone();
+    let time_bound = TimeBound {
+        kind: TimeBoundKind::After,
+        timestamp: 12445,
+    };
+    let balance_id = test.contract.deposit(
+        &test.deposit_address,
+        &test.token.address,
+        &800,
+        &vec![&test.env, original.clone()],
+        &time_bound,
+    );
+
+    // The depositor adds a claimant and removes the original one
+    test.contract.add_claimant(&test.deposit_address, &balance_id, &added);
+    let added_event = find_event_data(&test.env, symbol_short!("cl_add")).unwrap();
+    assert_eq!(Address::try_from_val(&test.env, &added_event).unwrap(), added);
+    test.contract.remove_claimant(&test.deposit_address, &balance_id, &original);
+    assert!(test.contract.try_add_claimant(&stranger, &balance_id, &stranger).is_err());
+
+    // The admin cannot swap in its own claimant
+    assert!(test.contract.try_add_claimant(&test.admin, &balance_id, &test.admin).is_err());
+    assert!(test.contract.try_remove_claimant(&test.admin, &balance_id, &added).is_err());
+    assert_eq!(test.contract.get_balance(&balance_id).claimants, vec![&test.env, added.clone()]);
+    assert_eq!(test.contract.list_for_claimant(&original, &0, &10).len(), 0);
+    assert_eq!(
+        test.contract.list_for_claimant(&added, &0, &10),
+        vec![&test.env, balance_id]
+    );
+
+    // Once unlocked the list is frozen
+    test.env.travel_to(12445);
+    assert!(test
+        .contract
+        .try_add_claimant(&test.deposit_address, &balance_id, &original)
+        .is_err());
+    test.contract.claim(&added, &balance_id);
+    assert_eq!(test.token.balance(&added), 800);
+}
+
+#[test]
+fn test_immutable_balance_claimants_cannot_be_edited() {
+    let test = ClaimableBalanceTest::setup();
+    let balance_id = test.contract.deposit_with_options(
+        &test.deposit_address,
+        &test.token.address,
+        &800,
+        &vec![&test.env, test.claim_addresses[0].clone()],
+        &TimeBound {
+            kind: TimeBoundKind::After,
+            timestamp: 12445,
+        },
+        &DepositOptions {
+            immutable: true,
+            ..Default::default()
+        },
+    );
+
+    assert!(test
+        .contract
+        .try_add_claimant(&test.deposit_address, &balance_id, &test.claim_addresses[1])
+        .is_err());
+    assert!(test
+        .contract
+        .try_remove_claimant(&test.admin, &balance_id, &test.claim_addresses[0])
+        .is_err());
+}
+
+#[test]
+fn test_memo_is_stored_and_published() {
+    let test = ClaimableBalanceTest::setup();
+    let memo = String::from_str(&test.env, "payroll 2026-10 / invoice 4711");
+    let balance_id = test.contract.deposit_with_options(
+        &test.deposit_address,
+        &test.token.address,
+        &800,
+        &vec![&test.env, test.claim_addresses[0].clone()],
+        &TimeBound {
+            kind: TimeBoundKind::Before,
+            timestamp: 12400,
+        },
+        &DepositOptions {
+            memo: Some(memo.clone()),
+            ..Default::default()
+        },
+    );
+
+    let memo_event = find_event_data(&test.env, symbol_short!("memo")).unwrap();
+    assert_eq!(String::try_from_val(&test.env, &memo_event).unwrap(), memo);
+    assert_eq!(test.contract.get_balance(&balance_id).memo, Some(memo));
+
+    // Memos are capped in length
+    let long_memo = String::from_bytes(&test.env, &[b'x'; 129]);
+    assert!(test
+        .contract
+        .try_deposit_with_options(
+            &test.deposit_address,
+            &test.token.address,
+            &100,
+            &vec![&test.env, test.claim_addresses[0].clone()],
+            &TimeBound {
+                kind: TimeBoundKind::Before,
+                timestamp: 12400,
+            },
+            &DepositOptions {
+                memo: Some(long_memo),
+                ..Default::default()
+            },
+        )
+        .is_err());
+}
+
+#[test]
+fn test_deposit_with_salt_uses_predictable_id_once() {
+    let test = ClaimableBalanceTest::setup();
+    let salt = BytesN::from_array(&test.env, &[9; 32]);
+    let claimants = vec![&test.env, test.claim_addresses[0].clone()];
+    let time_bound = TimeBound {
+        kind: TimeBoundKind::Before,
+        timestamp: 12400,
+    };
+
+    // The id is known before submission and never collides with sequential ids
+    let predicted = test.contract.salted_balance_id(&test.deposit_address, &salt);
+    assert!(predicted >= 1 << 63);
+    assert_ne!(predicted, test.contract.salted_balance_id(&test.claim_addresses[1], &salt));
+    let balance_id = test.contract.deposit_with_salt(
+        &test.deposit_address,
+        &test.token.address,
+        &300,
+        &claimants,
+        &time_bound,
+        &salt,
+    );
+    assert_eq!(balance_id, predicted);
+    assert_eq!(
+        test.contract.deposit(&test.deposit_address, &test.token.address, &100, &claimants, &time_bound),
+        0
+    );
+
+    // The same payout cannot be funded twice, even once the first balance is claimed
+    test.contract.claim(&test.claim_addresses[0], &balance_id);
+    assert!(test
+        .contract
+        .try_deposit_with_salt(&test.deposit_address, &test.token.address, &300, &claimants, &time_bound, &salt)
+        .is_err());
+    assert_eq!(test.token.balance(&test.claim_addresses[0]), 300);
+}
+
+#[test]
+fn test_deposit_from_allowance_pulls_approved_tokens() {
+    let test = ClaimableBalanceTest::setup();
+    let claimants = vec![&test.env, test.claim_addresses[0].clone()];
+    let time_bound = TimeBound {
+        kind: TimeBoundKind::Before,
+        timestamp: 12400,
+    };
+    test.token
+        .approve(&test.deposit_address, &test.contract.address, &500, &1000);
+
+    // The depositor only authorizes the deposit itself, not a nested transfer
+    let balance_id = test.contract.deposit_from_allowance(
+        &test.deposit_address,
+        &test.token.address,
+        &300,
+        &claimants,
+        &time_bound,
+    );
+    let auths = test.env.auths();
+    assert_eq!(auths.len(), 1);
+    assert_eq!(auths[0].0, test.deposit_address);
+    assert!(auths[0].1.sub_invocations.is_empty());
+    assert_eq!(test.token.allowance(&test.deposit_address, &test.contract.address), 200);
+    assert_eq!(test.contract.get_balance(&balance_id).amount, 300);
+
+    // Deposits beyond the allowance fail
+    assert!(test
+        .contract
+        .try_deposit_from_allowance(&test.deposit_address, &test.token.address, &300, &claimants, &time_bound)
+        .is_err());
+}
+
+#[test]
+fn test_deposit_for_refunds_to_sponsored_party() {
+    let test = ClaimableBalanceTest::setup();
+    let subsidiary = &test.claim_addresses[2];
+
+    let balance_id = test.contract.deposit_for(
+        &test.deposit_address,
+        subsidiary,
+        &test.token.address,
+        &300,
+        &vec![&test.env, test.claim_addresses[0].clone()],
+        &TimeBound {
+            kind: TimeBoundKind::Before,
+            timestamp: 12400,
+        },
+    );
+
+    // The sponsor authorized and paid, the subsidiary is the recorded depositor
+    assert_eq!(test.env.auths()[0].0, test.deposit_address);
+    assert_eq!(test.token.balance(&test.deposit_address), 700);
+    assert_eq!(test.contract.get_balance(&balance_id).depositor, *subsidiary);
+    assert_eq!(
+        test.contract.list_for_depositor(subsidiary, &0, &10),
+        vec![&test.env, balance_id]
+    );
+
+    // The unclaimed grant is refunded to the subsidiary, not the sponsor
+    test.env.travel_to(12401);
+    assert!(test.contract.try_refund(&test.deposit_address, &balance_id).is_err());
+    assert_eq!(test.contract.refund(subsidiary, &balance_id), 300);
+    assert_eq!(test.token.balance(subsidiary), 300);
+}
+
+#[test]
+fn test_recurring_unlocks_pay_matured_installments() {
+    let test = ClaimableBalanceTest::setup();
+    let employee = &test.claim_addresses[0];
+    let month = 30 * 24 * 60 * 60;
+
+    let balance_id = test.contract.deposit_with_options(
+        &test.deposit_address,
+        &test.token.address,
+        &1000,
+        &vec![&test.env, employee.clone()],
+        &TimeBound {
+            kind: TimeBoundKind::After,
+            timestamp: 12345,
+        },
+        &DepositOptions {
+            vesting: Vesting::Recurring(RecurringSchedule {
+                first_unlock: 12345 + month,
+                interval: month,
+                installments: 3,
+            }),
+            ..Default::default()
+        },
+    );
+    assert!(test.contract.try_claim(employee, &balance_id).is_err());
+
+    // Two months in, two installments are paid at once
+    test.env.ledger().set_timestamp(12345 + 2 * month);
+    test.contract.claim(employee, &balance_id);
+    assert_eq!(test.token.balance(employee), 666);
+    assert_eq!(test.contract.next_unlock_time(&balance_id), Some(12345 + 3 * month));
+    assert!(test.contract.try_claim(employee, &balance_id).is_err());
+
+    // The last installment carries the remainder and settles the balance
+    test.env.ledger().set_timestamp(12345 + 5 * month);
+    test.contract.claim(employee, &balance_id);
+    assert_eq!(test.token.balance(employee), 1000);
+    assert!(test.contract.try_get_balance(&balance_id).is_err());
+}
+
+#[test]
+fn test_early_claim_forfeits_penalty_to_depositor() {
+    let test = ClaimableBalanceTest::setup();
+    let claimant = &test.claim_addresses[0];
+
+    let balance_id = test.contract.deposit_with_options(
+        &test.deposit_address,
+        &test.token.address,
+        &1000,
+        &vec![&test.env, claimant.clone()],
+        &TimeBound {
+            kind: TimeBoundKind::After,
+            timestamp: 20000,
+        },
+        &DepositOptions {
+            early_claim: EarlyClaim::Penalty(EarlyClaimTerms {
+                penalty_bps: 2500,
+                earliest: 15000,
+                recipient: None,
+            }),
+            ..Default::default()
+        },
+    );
+
+    // Early claims open at the earliest time only
+    assert!(test.contract.try_claim_early(claimant, &balance_id).is_err());
+    test.env.travel_to(15000);
+    assert!(test.contract.try_claim(claimant, &balance_id).is_err());
+
+    // The claimant receives the amount minus the penalty, which goes back to the depositor
+    assert_eq!(test.contract.claim_early(claimant, &balance_id), 750);
+    assert_eq!(test.token.balance(claimant), 750);
+    assert_eq!(test.token.balance(&test.deposit_address), 250);
+    assert!(test.contract.try_get_balance(&balance_id).is_err());
+    assert_eq!(test.contract.total_locked(&test.token.address), 0);
+}
+
+#[test]
+fn test_early_claim_terms_are_validated() {
+    let test = ClaimableBalanceTest::setup();
+    let treasury = Address::generate(&test.env);
+    let deposit = |time_bound: TimeBound, penalty_bps: u32| {
+        test.contract.try_deposit_with_options(
+            &test.deposit_address,
+            &test.token.address,
+            &100,
+            &vec![&test.env, test.claim_addresses[0].clone()],
+            &time_bound,
+            &DepositOptions {
+                early_claim: EarlyClaim::Penalty(EarlyClaimTerms {
+                    penalty_bps,
+                    earliest: 15000,
+                    recipient: Some(treasury.clone()),
+                }),
+                ..Default::default()
+            },
+        )
+    };
+    let after = |timestamp| TimeBound {
+        kind: TimeBoundKind::After,
+        timestamp,
+    };
+
+    assert!(deposit(after(20000), 10001).is_err());
+    assert!(deposit(after(15000), 1000).is_err());
+    let before = TimeBound {
+        kind: TimeBoundKind::Before,
+        timestamp: 20000,
+    };
+    assert!(deposit(before, 1000).is_err());
+
+    // A treasury recipient collects the penalty instead of the depositor
+    let balance_id = deposit(after(20000), 1000).unwrap().unwrap();
+    test.env.travel_to(15000);
+    test.contract.claim_early(&test.claim_addresses[0], &balance_id);
+    assert_eq!(test.token.balance(&treasury), 10);
+    assert_eq!(test.token.balance(&test.claim_addresses[0]), 90);
+}
+
+#[test]
+fn test_claimant_extends_own_lock() {
+    let test = ClaimableBalanceTest::setup();
+    let staker = &test.claim_addresses[0];
+    let other = &test.claim_addresses[1];
+
+    let balance_id = test.contract.deposit_with_options(
+        &test.deposit_address,
+        &test.token.address,
+        &1000,
+        &vec![&test.env, staker.clone(), other.clone()],
+        &TimeBound {
+            kind: TimeBoundKind::After,
+            timestamp: 13000,
+        },
+        &DepositOptions {
+            distribution: DistributionMode::EqualSplit,
+            ..Default::default()
+        },
+    );
+
+    // Locks only move forward and only for the calling claimant
+    assert!(test.contract.try_extend_my_lock(staker, &balance_id, &13000).is_err());
+    test.contract.extend_my_lock(staker, &balance_id, &20000);
+    assert!(test.contract.try_extend_my_lock(staker, &balance_id, &19000).is_err());
+    assert!(test
+        .contract
+        .try_extend_my_lock(&test.deposit_address, &balance_id, &30000)
+        .is_err());
+
+    test.env.travel_to(13000);
+    test.contract.claim(other, &balance_id);
+    assert_eq!(test.token.balance(other), 500);
+    assert!(test.contract.try_claim(staker, &balance_id).is_err());
+
+    test.env.travel_to(20000);
+    test.contract.claim(staker, &balance_id);
+    assert_eq!(test.token.balance(staker), 500);
+}
+
+#[test]
+fn test_extension_needs_every_claimant() {
+    let test = ClaimableBalanceTest::setup();
+    let (first, second) = (&test.claim_addresses[0], &test.claim_addresses[1]);
+    let balance_id = test.contract.deposit(
+        &test.deposit_address,
+        &test.token.address,
+        &1000,
+        &vec![&test.env, first.clone(), second.clone()],
+        &TimeBound {
+            kind: TimeBoundKind::After,
+            timestamp: 13000,
+        },
+    );
+
+    assert!(test.contract.try_propose_extension(&balance_id, &12000).is_err());
+    test.contract.propose_extension(&balance_id, &20000);
+    assert!(test.contract.try_accept_extension(first, &balance_id, &30000).is_err());
+    test.contract.accept_extension(first, &balance_id, &20000);
+    assert_eq!(test.contract.get_balance(&balance_id).time_bound.timestamp, 13000);
+    assert_eq!(
+        test.contract.pending_extension(&balance_id).unwrap().accepted,
+        vec![&test.env, first.clone()]
+    );
+
+    // The last acceptance moves the unlock time
+    test.contract.accept_extension(second, &balance_id, &20000);
+    assert_eq!(test.contract.get_balance(&balance_id).time_bound.timestamp, 20000);
+    assert!(test.contract.pending_extension(&balance_id).is_none());
+
+    test.env.travel_to(13000);
+    assert!(test.contract.try_claim(first, &balance_id).is_err());
+    test.env.travel_to(20000);
+    test.contract.claim(first, &balance_id);
+    assert_eq!(test.token.balance(first), 1000);
+}
+
+#[test]
+fn test_freeze_blocks_claims_until_redirected() {
+    let test = ClaimableBalanceTest::setup();
+    test.contract.update_config(&Config {
+        freeze_delay: 100,
+        ..test.contract.get_config()
+    });
+    let claimant = &test.claim_addresses[0];
+    let balance_id = test.contract.deposit(
+        &test.deposit_address,
+        &test.token.address,
+        &1000,
+        &vec![&test.env, claimant.clone()],
+        &TimeBound {
+            kind: TimeBoundKind::After,
+            timestamp: 12500,
+        },
+    );
+
+    // Freezing only takes effect once the delay has passed
+    let eta = test.contract.announce_freeze_action(&balance_id, &FreezeAction::Freeze);
+    assert_eq!(eta, 12445);
+    assert!(test
+        .contract
+        .try_apply_freeze_action(&balance_id, &FreezeAction::Freeze)
+        .is_err());
+    test.env.travel_to(eta);
+    assert!(test
+        .contract
+        .try_apply_freeze_action(&balance_id, &FreezeAction::Redirect)
+        .is_err());
+    test.contract.apply_freeze_action(&balance_id, &FreezeAction::Freeze);
+    assert!(test.contract.is_frozen(&balance_id));
+
+    test.env.travel_to(12500);
+    assert!(!test.contract.is_claimable(&balance_id, claimant));
+    assert!(test.contract.try_claim(claimant, &balance_id).is_err());
+
+    // Redirecting returns the frozen balance to the depositor
+    test.contract.announce_freeze_action(&balance_id, &FreezeAction::Redirect);
+    assert_eq!(
+        test.contract.pending_freeze_action(&balance_id).unwrap().action,
+        FreezeAction::Redirect
+    );
+    test.env.travel_by(100);
+    assert_eq!(
+        test.contract.apply_freeze_action(&balance_id, &FreezeAction::Redirect),
+        1000
+    );
+    assert_eq!(test.token.balance(&test.deposit_address), 1000);
+    assert!(test.contract.try_get_balance(&balance_id).is_err());
+}
+
+#[test]
+fn test_unfrozen_balance_can_be_claimed() {
+    let test = ClaimableBalanceTest::setup();
+    let claimant = &test.claim_addresses[0];
+    let balance_id = test.contract.deposit(
+        &test.deposit_address,
+        &test.token.address,
+        &1000,
+        &vec![&test.env, claimant.clone()],
+        &TimeBound {
+            kind: TimeBoundKind::After,
+            timestamp: 12345,
+        },
+    );
+
+    test.contract.announce_freeze_action(&balance_id, &FreezeAction::Freeze);
+    test.contract.apply_freeze_action(&balance_id, &FreezeAction::Freeze);
+    assert!(test.contract.try_claim(claimant, &balance_id).is_err());
+    assert!(test.contract.try_refund(&test.deposit_address, &balance_id).is_err());
+
+    test.contract.announce_freeze_action(&balance_id, &FreezeAction::Unfreeze);
+    test.contract.cancel_freeze_action(&balance_id);
+    assert!(test
+        .contract
+        .try_apply_freeze_action(&balance_id, &FreezeAction::Unfreeze)
+        .is_err());
+
+    test.contract.announce_freeze_action(&balance_id, &FreezeAction::Unfreeze);
+    test.contract.apply_freeze_action(&balance_id, &FreezeAction::Unfreeze);
+    test.contract.claim(claimant, &balance_id);
+    assert_eq!(test.token.balance(claimant), 1000);
+}
+
+#[test]
+fn test_per_balance_withdrawal_cap_spans_claimants() {
+    let test = ClaimableBalanceTest::setup();
+    let (first, second) = (&test.claim_addresses[0], &test.claim_addresses[1]);
+
+    let balance_id = test.contract.deposit_with_options(
+        &test.deposit_address,
+        &test.token.address,
+        &1000,
+        &vec![&test.env, first.clone(), second.clone()],
+        &TimeBound {
+            kind: TimeBoundKind::After,
+            timestamp: 12345,
+        },
+        &DepositOptions {
+            vesting: Vesting::Linear(VestingSchedule {
+                start: 12345,
+                cliff: 12345,
+                end: 12445,
+            }),
+            withdrawal_cap: WithdrawalCap::PerBalance(RollingWindow {
+                max_amount: 500,
+                window: 100,
+            }),
+            ..Default::default()
+        },
+    );
+
+    // The first claimant uses up the cap of the whole balance
+    test.env.travel_to(12445);
+    assert_eq!(test.contract.claim_vested(first, &balance_id), 500);
+    assert!(test.contract.try_claim_vested(second, &balance_id).is_err());
+
+    test.env.travel_by(100);
+    assert_eq!(test.contract.claim_vested(second, &balance_id), 500);
+    assert_eq!(test.token.balance(first), 500);
+    assert_eq!(test.token.balance(second), 500);
+}
+
+#[test]
+fn test_claim_cooldown_spaces_out_vested_claims() {
+    let test = ClaimableBalanceTest::setup();
+    let claimant = &test.claim_addresses[0];
+
+    let balance_id = test.contract.deposit_with_options(
+        &test.deposit_address,
+        &test.token.address,
+        &1000,
+        &vec![&test.env, claimant.clone()],
+        &TimeBound {
+            kind: TimeBoundKind::After,
+            timestamp: 12345,
+        },
+        &DepositOptions {
+            vesting: Vesting::Linear(VestingSchedule {
+                start: 12345,
+                cliff: 12345,
+                end: 13345,
+            }),
+            claim_cooldown: 100,
+            ..Default::default()
+        },
+    );
+
+    test.env.travel_by(10);
+    assert_eq!(test.contract.claim_vested(claimant, &balance_id), 10);
+    assert_eq!(test.contract.next_unlock_time(&balance_id), Some(12455));
+
+    // Dust claims are rejected until the cooldown has elapsed
+    test.env.travel_by(50);
+    assert!(test.contract.try_claim_vested(claimant, &balance_id).is_err());
+    test.env.travel_by(50);
+    assert_eq!(test.contract.claim_vested(claimant, &balance_id), 100);
+}
+
+#[test]
+#[should_panic(expected = "only vesting balances can have a claim cooldown")]
+fn test_claim_cooldown_requires_vesting() {
+    let test = ClaimableBalanceTest::setup();
+
+    test.contract.deposit_with_options(
+        &test.deposit_address,
+        &test.token.address,
+        &1000,
+        &vec![&test.env, test.claim_addresses[0].clone()],
+        &TimeBound {
+            kind: TimeBoundKind::After,
+            timestamp: 12345,
+        },
+        &DepositOptions {
+            claim_cooldown: 100,
+            ..Default::default()
+        },
+    );
+}
+
+#[test]
+fn test_status_follows_balance_lifecycle() {
+    let test = ClaimableBalanceTest::setup();
+    let (first, second) = (&test.claim_addresses[0], &test.claim_addresses[1]);
+
+    let balance_id = test.contract.deposit_with_options(
+        &test.deposit_address,
+        &test.token.address,
+        &1000,
+        &vec![&test.env, first.clone(), second.clone()],
+        &TimeBound {
+            kind: TimeBoundKind::After,
+            timestamp: 12400,
+        },
+        &DepositOptions {
+            distribution: DistributionMode::EqualSplit,
+            ..Default::default()
+        },
+    );
+    assert_eq!(test.contract.status(&balance_id), BalanceStatus::Pending);
+
+    test.env.travel_to(12400);
+    assert_eq!(test.contract.status(&balance_id), BalanceStatus::Claimable);
+
+    test.contract.announce_freeze_action(&balance_id, &FreezeAction::Freeze);
+    test.contract.apply_freeze_action(&balance_id, &FreezeAction::Freeze);
+    assert_eq!(test.contract.status(&balance_id), BalanceStatus::Frozen);
+    test.contract.announce_freeze_action(&balance_id, &FreezeAction::Unfreeze);
+    test.contract.apply_freeze_action(&balance_id, &FreezeAction::Unfreeze);
+
+    test.contract.claim(first, &balance_id);
+    assert_eq!(test.contract.status(&balance_id), BalanceStatus::PartiallyClaimed);
+
+    // The final state outlives the balance
+    test.contract.claim(second, &balance_id);
+    assert_eq!(test.contract.status(&balance_id), BalanceStatus::Claimed);
+    assert!(test.contract.try_status(&(balance_id + 1)).is_err());
+}
+
+#[test]
+fn test_status_of_expired_and_revoked_balances() {
+    let test = ClaimableBalanceTest::setup();
+    let claimant = &test.claim_addresses[0];
+
+    let expiring_id = test.contract.deposit(
+        &test.deposit_address,
+        &test.token.address,
+        &400,
+        &vec![&test.env, claimant.clone()],
+        &TimeBound {
+            kind: TimeBoundKind::Before,
+            timestamp: 12400,
+        },
+    );
+    let revocable_id = test.contract.deposit_with_options(
+        &test.deposit_address,
+        &test.token.address,
+        &600,
+        &vec![&test.env, claimant.clone()],
+        &TimeBound {
+            kind: TimeBoundKind::After,
+            timestamp: 12345,
+        },
+        &DepositOptions {
+            vesting: Vesting::Linear(VestingSchedule {
+                start: 12345,
+                cliff: 12345,
+                end: 13345,
+            }),
+            revocable: true,
+            ..Default::default()
+        },
+    );
+
+    test.env.travel_to(12401);
+    assert_eq!(test.contract.status(&expiring_id), BalanceStatus::Expired);
+    test.contract.refund(&test.deposit_address, &expiring_id);
+    assert_eq!(test.contract.status(&expiring_id), BalanceStatus::Expired);
+
+    test.contract.revoke(&revocable_id);
+    assert_eq!(test.contract.status(&revocable_id), BalanceStatus::Cancelled);
+}
+
+#[test]
+fn test_claimant_entries_follow_claimant_list() {
+    let test = ClaimableBalanceTest::setup();
+    let (first, second) = (&test.claim_addresses[0], &test.claim_addresses[1]);
+    let newcomer = Address::generate(&test.env);
+
+    let balance_id = test.contract.deposit(
+        &test.deposit_address,
+        &test.token.address,
+        &1000,
+        &vec![&test.env, first.clone(), second.clone()],
+        &TimeBound {
+            kind: TimeBoundKind::After,
+            timestamp: 13000,
+        },
+    );
+    let is_claimant = |claimant: &Address| {
+        test.env.as_contract(&test.contract.address, || {
+            index::is_claimant(&test.env, balance_id, claimant)
+        })
+    };
+    assert!(is_claimant(first) && is_claimant(second));
+
+    test.contract.remove_claimant(&test.deposit_address, &balance_id, second);
+    test.contract.add_claimant(&test.deposit_address, &balance_id, &newcomer);
+    assert!(!is_claimant(second) && is_claimant(&newcomer));
+
+    // Closing the balance drops every entry
+    test.env.travel_to(13000);
+    test.contract.claim(&newcomer, &balance_id);
+    assert!(!is_claimant(first) && !is_claimant(&newcomer));
+}
+
+#[test]
+fn test_claims_keep_metadata_stored_apart() {
+    let test = ClaimableBalanceTest::setup();
+    let (first, second) = (&test.claim_addresses[0], &test.claim_addresses[1]);
+    let memo = String::from_str(&test.env, "grant round 7");
+
+    let balance_id = test.contract.deposit_with_options(
+        &test.deposit_address,
+        &test.token.address,
+        &1000,
+        &vec![&test.env, first.clone(), second.clone()],
+        &TimeBound {
+            kind: TimeBoundKind::After,
+            timestamp: 12345,
+        },
+        &DepositOptions {
+            distribution: DistributionMode::EqualSplit,
+            memo: Some(memo.clone()),
+            ..Default::default()
+        },
+    );
+    test.contract.claim(first, &balance_id);
+
+    // The claim-relevant record leaves out the claimants and memo
+    let record: ClaimableBalance = test.env.as_contract(&test.contract.address, || {
+        test.env
+            .storage()
+            .persistent()
+            .get(&DataKey::Balance(balance_id))
+            .unwrap()
+    });
+    assert!(record.claimants.is_empty() && record.memo.is_none());
+    assert_eq!(record.claimed, 500);
+
+    let claimable_balance = test.contract.get_balance(&balance_id);
+    assert_eq!(claimable_balance.claimants, vec![&test.env, first.clone(), second.clone()]);
+    assert_eq!(claimable_balance.memo, Some(memo));
+
+    test.contract.claim(second, &balance_id);
+    assert_eq!(test.token.balance(second), 500);
+    assert_eq!(test.contract.list_for_claimant(second, &0, &10).len(), 0);
+}
+
+#[test]
+fn test_math_reports_typed_errors() {
+    assert_eq!(math::add(i128::MAX, 1), Err(MathError::Overflow));
+    assert_eq!(math::mul(i128::MAX, 2), Err(MathError::Overflow));
+    assert_eq!(math::div(1, 0), Err(MathError::DivisionByZero));
+    assert_eq!(math::bps_of(MAX_AMOUNT, 10_000), Ok(MAX_AMOUNT));
+    assert_eq!(math::sub(i128::MIN, 1), Err(MathError::Overflow));
+    assert_eq!(math::add_time(u64::MAX, 1), Err(MathError::Overflow));
+
+    // Vesting math on huge amounts errors instead of wrapping
+    let vesting = VestingSchedule {
+        start: 0,
+        cliff: 0,
+        end: u64::MAX,
+    };
+    assert_eq!(
+        timelock_core::linear_vested(&vesting, MAX_AMOUNT, u64::MAX - 1),
+        Err(MathError::Overflow)
+    );
+}
+
+#[test]
+fn test_deposit_rejects_amounts_above_max() {
+    let test = ClaimableBalanceTest::setup();
+    let result = test.contract.try_deposit(
+        &test.deposit_address,
+        &test.token.address,
+        &(MAX_AMOUNT + 1),
+        &vec![&test.env, test.claim_addresses[0].clone()],
+        &TimeBound {
+            kind: TimeBoundKind::After,
+            timestamp: 12345,
+        },
+    );
+    assert_eq!(result, Err(Ok(DepositError::AmountTooLarge.into())));
+}
+
+#[test]
+fn test_rescue_only_sweeps_untracked_tokens() {
+    let test = ClaimableBalanceTest::setup();
+    let balance_id = test.contract.deposit_with_options(
+        &test.deposit_address,
+        &test.token.address,
+        &400,
+        &vec![&test.env, test.claim_addresses[0].clone()],
+        &TimeBound {
+            kind: TimeBoundKind::Before,
+            timestamp: 12400,
+        },
+        &DepositOptions {
+            bounty: Bounty::Fixed(20),
+            ..Default::default()
+        },
+    );
+    test.contract.fund_rent(&test.deposit_address, &balance_id, &100);
+    test.contract.create_stream(
+        &test.deposit_address,
+        &test.claim_addresses[1],
+        &test.token.address,
+        &300,
+        &12345,
+        &12445,
+    );
+    assert_eq!(test.contract.rescuable(&test.token.address), 0);
+    assert_eq!(test.contract.rescuable(&test.native_token.address), 0);
+
+    // Tokens sent directly to the contract are not owed to anyone
+    test.token.transfer(&test.deposit_address, &test.contract.address, &50);
+    test.native_token.transfer(&test.deposit_address, &test.contract.address, &30);
+    assert_eq!(test.contract.rescuable(&test.token.address), 50);
+    assert_eq!(test.contract.rescuable(&test.native_token.address), 30);
+
+    let to = Address::generate(&test.env);
+    assert!(test
+        .contract
+        .try_rescue(&test.token.address, &51, &to)
+        .is_err());
+    test.contract.rescue(&test.token.address, &50, &to);
+    test.contract.rescue(&test.native_token.address, &30, &to);
+    assert_eq!(test.token.balance(&to), 50);
+    assert_eq!(test.native_token.balance(&to), 30);
+    assert_eq!(test.contract.rescuable(&test.token.address), 0);
+
+    // Claimant funds, bounties, rent budgets and streams are all still paid out in full
+    test.contract.claim(&test.claim_addresses[0], &balance_id);
+    assert_eq!(test.token.balance(&test.claim_addresses[0]), 400);
+    test.env.travel_to(12445);
+    test.contract.withdraw_from_stream(&0, &300);
+    assert_eq!(test.token.balance(&test.contract.address), 0);
+    assert_eq!(test.native_token.balance(&test.contract.address), 0);
+}
+
+#[test]
+fn test_rescue_requires_admin() {
+    let test = ClaimableBalanceTest::setup();
+    test.token.transfer(&test.deposit_address, &test.contract.address, &50);
+
+    test.env.set_auths(&[]);
+    assert!(test
+        .contract
+        .try_rescue(&test.token.address, &50, &test.deposit_address)
+        .is_err());
+}
+
+#[test]
+fn test_preview_claim_reports_net_payout_and_blocker() {
+    let test = ClaimableBalanceTest::setup();
+    let fee_collector = Address::generate(&test.env);
+    test.contract.update_config(&Config {
+        fee_bps: 250,
+        fee_collector: Some(fee_collector),
+        ..test.contract.get_config()
+    });
+    let balance_id = test.contract.deposit(
+        &test.deposit_address,
+        &test.token.address,
+        &400,
+        &vec![&test.env, test.claim_addresses[0].clone()],
+        &TimeBound {
+            kind: TimeBoundKind::After,
+            timestamp: 12400,
+        },
+    );
+
+    // Locked balances show what they are worth and why they cannot be claimed yet
+    let preview = test.contract.preview_claim(&balance_id, &test.claim_addresses[0]);
+    assert_eq!(
+        preview,
+        ClaimPreview {
+            payout: 400,
+            fee: 10,
+            net: 390,
+            blocker: ClaimBlocker::Locked,
+        }
+    );
+
+    test.env.travel_to(12400);
+    assert_eq!(
+        test.contract.preview_claim(&balance_id, &test.claim_addresses[1]).blocker,
+        ClaimBlocker::NotClaimant
+    );
+    let preview = test.contract.preview_claim(&balance_id, &test.claim_addresses[0]);
+    assert_eq!(preview.blocker, ClaimBlocker::None);
+
+    // The preview matches what the claim then pays
+    test.contract.claim(&test.claim_addresses[0], &balance_id);
+    assert_eq!(test.token.balance(&test.claim_addresses[0]), preview.net);
+}
+
+#[test]
+fn test_preview_claim_of_vesting_balance() {
+    let test = ClaimableBalanceTest::setup();
+    let balance_id = test.contract.deposit_with_options(
+        &test.deposit_address,
+        &test.token.address,
+        &800,
+        &vec![&test.env, test.claim_addresses[0].clone()],
+        &TimeBound {
+            kind: TimeBoundKind::After,
+            timestamp: 12345,
+        },
+        &DepositOptions {
+            vesting: Vesting::Linear(VestingSchedule {
+                start: 12345,
+                cliff: 12345,
+                end: 12445,
+            }),
+            claim_cooldown: 50,
+            ..Default::default()
+        },
+    );
+
+    // Only the vested part is due
+    test.env.travel_to(12370);
+    let preview = test.contract.preview_claim(&balance_id, &test.claim_addresses[0]);
+    assert_eq!((preview.payout, preview.net, preview.blocker), (200, 200, ClaimBlocker::None));
+    test.contract.claim_vested(&test.claim_addresses[0], &balance_id);
+
+    // The next partial claim waits for the cooldown
+    test.env.travel_to(12395);
+    let preview = test.contract.preview_claim(&balance_id, &test.claim_addresses[0]);
+    assert_eq!((preview.payout, preview.blocker), (200, ClaimBlocker::Cooldown));
+
+    // Frozen balances report the freeze first
+    test.contract.announce_freeze_action(&balance_id, &FreezeAction::Freeze);
+    test.contract.apply_freeze_action(&balance_id, &FreezeAction::Freeze);
+    assert_eq!(
+        test.contract.preview_claim(&balance_id, &test.claim_addresses[0]).blocker,
+        ClaimBlocker::Frozen
+    );
+}
+
+#[test]
+fn test_weighted_share_rounding() {
+    let env = Env::default();
+    let [a, b, c] = [Address::generate(&env), Address::generate(&env), Address::generate(&env)];
+
+    // Exact shares leave nothing to round
+    let weights = vec![&env, (a.clone(), 50), (b.clone(), 30), (c.clone(), 20)];
+    assert_eq!(timelock_core::weighted_share(1000, &weights, &a), Ok(500));
+    assert_eq!(timelock_core::weighted_share(1000, &weights, &b), Ok(300));
+    assert_eq!(timelock_core::weighted_share(1000, &weights, &c), Ok(200));
+
+    // The remainder goes to the first claimant with the largest weight
+    let weights = vec![&env, (a.clone(), 1), (b.clone(), 2), (c.clone(), 2)];
+    assert_eq!(timelock_core::weighted_share(101, &weights, &a), Ok(20));
+    assert_eq!(timelock_core::weighted_share(101, &weights, &b), Ok(41));
+    assert_eq!(timelock_core::weighted_share(101, &weights, &c), Ok(40));
+
+    // Addresses without a weight get nothing
+    let outsider = Address::generate(&env);
+    assert_eq!(timelock_core::weighted_share(101, &weights, &outsider), Ok(0));
+}
+
+#[test]
+fn test_weighted_split_pays_each_claimant_its_share() {
+    let test = ClaimableBalanceTest::setup();
+    let [a, b, c] = test.claim_addresses.clone();
+    let keeper = Address::generate(&test.env);
+
+    let balance_id = test.contract.deposit_with_options(
+        &test.deposit_address,
+        &test.token.address,
+        &1000,
+        &vec![&test.env, a.clone(), b.clone(), c.clone()],
+        &TimeBound {
+            kind: TimeBoundKind::After,
+            timestamp: 12345,
+        },
+        &DepositOptions {
+            distribution: DistributionMode::Weighted(vec![
+                &test.env,
+                (a.clone(), 50),
+                (b.clone(), 30),
+                (c.clone(), 20),
+            ]),
+            ..Default::default()
+        },
+    );
+
+    // Each claimant gets its share once, and a keeper pushes the rest
+    test.contract.claim(&b, &balance_id);
+    assert_eq!(test.token.balance(&b), 300);
+    assert!(test.contract.try_claim(&b, &balance_id).is_err());
+    assert_eq!(test.contract.distribute(&keeper, &balance_id), 700);
+    assert_eq!(test.token.balance(&a), 500);
+    assert_eq!(test.token.balance(&c), 200);
+    assert_eq!(test.contract.status(&balance_id), BalanceStatus::Claimed);
+}
+
+#[test]
+fn test_weighted_split_needs_one_weight_per_claimant() {
+    let test = ClaimableBalanceTest::setup();
+    let [a, b, c] = test.claim_addresses.clone();
+    let deposit = |weights| {
+        test.contract.try_deposit_with_options(
+            &test.deposit_address,
+            &test.token.address,
+            &1000,
+            &vec![&test.env, a.clone(), b.clone()],
+            &TimeBound {
+                kind: TimeBoundKind::After,
+                timestamp: 12345,
+            },
+            &DepositOptions {
+                distribution: DistributionMode::Weighted(weights),
+                ..Default::default()
+            },
+        )
+    };
+
+    assert!(deposit(vec![&test.env, (a.clone(), 1)]).is_err());
+    assert!(deposit(vec![&test.env, (a.clone(), 1), (c.clone(), 1)]).is_err());
+    assert!(deposit(vec![&test.env, (a.clone(), 1), (a.clone(), 1)]).is_err());
+    assert!(deposit(vec![&test.env, (a.clone(), 1), (b.clone(), 0)]).is_err());
+    assert!(deposit(vec![&test.env, (a.clone(), 1), (b.clone(), 3)]).is_ok());
+}
+
+#[test]
+fn test_milestones_release_once_approved() {
+    let test = ClaimableBalanceTest::setup();
+    let claimant = &test.claim_addresses[0];
+    let reviewer = Address::generate(&test.env);
+    let milestone = |amount| Milestone {
+        amount,
+        approved: false,
+        rejected: false,
+        paid: false,
+    };
+
+    let balance_id = test.contract.deposit_with_options(
+        &test.deposit_address,
+        &test.token.address,
+        &1000,
+        &vec![&test.env, claimant.clone()],
+        &TimeBound {
+            kind: TimeBoundKind::After,
+            timestamp: 12345,
+        },
+        &DepositOptions {
+            vesting: Vesting::Milestones(MilestoneSchedule {
+                milestones: vec![&test.env, milestone(300), milestone(700)],
+                reviewer: Some(reviewer.clone()),
+                arbiter: None,
+                rejection_bond: 0,
+            }),
+            ..Default::default()
+        },
+    );
+
+    // Nothing can be claimed before an approval
+    assert!(test.contract.try_claim(claimant, &balance_id).is_err());
+    assert_eq!(test.contract.next_unlock_time(&balance_id), Some(u64::MAX));
+
+    // Milestones can be approved in any order, by the reviewer
+    assert_eq!(test.contract.approve_milestone(&balance_id, &1), 700);
+    assert_eq!(
+        test.env.auths()[0].1.function,
+        AuthorizedFunction::Contract((
+            test.contract.address.clone(),
+            Symbol::new(&test.env, "approve_milestone"),
+            (balance_id, 1_u32).into_val(&test.env),
+        ))
+    );
+    assert_eq!(test.env.auths()[0].0, reviewer);
+    assert!(test.contract.try_approve_milestone(&balance_id, &1).is_err());
+    assert!(test.contract.try_approve_milestone(&balance_id, &2).is_err());
+
+    test.contract.claim(claimant, &balance_id);
+    assert_eq!(test.token.balance(claimant), 700);
+    assert!(test.contract.try_claim(claimant, &balance_id).is_err());
+
+    test.contract.approve_milestone(&balance_id, &0);
+    test.contract.claim(claimant, &balance_id);
+    assert_eq!(test.token.balance(claimant), 1000);
+    assert_eq!(test.contract.status(&balance_id), BalanceStatus::Claimed);
+}
+
+#[test]
+fn test_milestones_must_add_up_to_amount() {
+    let test = ClaimableBalanceTest::setup();
+    let deposit = |milestones| {
+        test.contract.try_deposit_with_options(
+            &test.deposit_address,
+            &test.token.address,
+            &1000,
+            &vec![&test.env, test.claim_addresses[0].clone()],
+            &TimeBound {
+                kind: TimeBoundKind::After,
+                timestamp: 12345,
+            },
+            &DepositOptions {
+                vesting: Vesting::Milestones(MilestoneSchedule {
+                    milestones,
+                    reviewer: None,
+                    arbiter: None,
+                    rejection_bond: 0,
+                }),
+                ..Default::default()
+            },
+        )
+    };
+    let milestone = |amount, approved| Milestone {
+        amount,
+        approved,
+        rejected: false,
+        paid: false,
+    };
+
+    assert!(deposit(vec![&test.env, milestone(400, false), milestone(500, false)]).is_err());
+    assert!(deposit(vec![&test.env, milestone(1000, true)]).is_err());
+    assert!(deposit(vec![&test.env, milestone(1000, false), milestone(0, false)]).is_err());
+    assert!(deposit(vec![&test.env]).is_err());
+    assert!(deposit(vec![&test.env, milestone(1000, false)]).is_ok());
+}
+
+#[test]
+fn test_milestone_rejections_are_bonded_and_arbitrated() {
+    let test = ClaimableBalanceTest::setup();
+    let claimant = &test.claim_addresses[0];
+    let arbiter = Address::generate(&test.env);
+    let milestone = |amount| Milestone {
+        amount,
+        approved: false,
+        rejected: false,
+        paid: false,
+    };
+
+    let balance_id = test.contract.deposit_with_options(
+        &test.deposit_address,
+        &test.token.address,
+        &900,
+        &vec![&test.env, claimant.clone()],
+        &TimeBound {
+            kind: TimeBoundKind::After,
+            timestamp: 12345,
+        },
+        &DepositOptions {
+            vesting: Vesting::Milestones(MilestoneSchedule {
+                milestones: vec![&test.env, milestone(400), milestone(500)],
+                reviewer: None,
+                arbiter: Some(arbiter.clone()),
+                rejection_bond: 50,
+            }),
+            ..Default::default()
+        },
+    );
+    assert_eq!(test.token.balance(&test.deposit_address), 100);
+
+    // The depositor rejects both milestones, posting a bond each time
+    test.contract.reject_milestone(&balance_id, &0);
+    test.contract.reject_milestone(&balance_id, &1);
+    assert_eq!(test.token.balance(&test.deposit_address), 0);
+    assert!(test.contract.try_approve_milestone(&balance_id, &0).is_err());
+    assert_eq!(test.contract.rescuable(&test.token.address), 0);
+
+    // Overturning a rejection approves the milestone and pays the bond to the claimant
+    test.contract.resolve_milestone(&balance_id, &0, &true);
+    assert_eq!(test.env.auths()[0].0, arbiter);
+    assert_eq!(test.token.balance(claimant), 50);
+    test.contract.claim(claimant, &balance_id);
+    assert_eq!(test.token.balance(claimant), 450);
+
+    // Upholding a rejection returns the bond and leaves the milestone open
+    test.contract.resolve_milestone(&balance_id, &1, &false);
+    assert_eq!(test.token.balance(&test.deposit_address), 50);
+    assert!(test.contract.try_resolve_milestone(&balance_id, &1, &true).is_err());
+    test.contract.approve_milestone(&balance_id, &1);
+    test.contract.claim(claimant, &balance_id);
+    assert_eq!(test.token.balance(claimant), 950);
+}
+
+#[test]
+fn test_milestone_rejections_need_an_arbiter_and_a_bond() {
+    let test = ClaimableBalanceTest::setup();
+    let deposit = |arbiter: Option<Address>, rejection_bond| {
+        test.contract.try_deposit_with_options(
+            &test.deposit_address,
+            &test.token.address,
+            &400,
+            &vec![&test.env, test.claim_addresses[0].clone()],
+            &TimeBound {
+                kind: TimeBoundKind::After,
+                timestamp: 12345,
+            },
+            &DepositOptions {
+                vesting: Vesting::Milestones(MilestoneSchedule {
+                    milestones: vec![
+                        &test.env,
+                        Milestone {
+                            amount: 400,
+                            approved: false,
+                            rejected: false,
+                            paid: false,
+                        },
+                    ],
+                    reviewer: None,
+                    arbiter,
+                    rejection_bond,
+                }),
+                ..Default::default()
+            },
+        )
+    };
+    let arbiter = Address::generate(&test.env);
+
+    assert!(deposit(Some(arbiter.clone()), 0).is_err());
+    assert!(deposit(None, -1).is_err());
+    let balance_id = deposit(None, 0).unwrap().unwrap();
+    assert!(test.contract.try_reject_milestone(&balance_id, &0).is_err());
+    assert!(deposit(Some(arbiter), 10).is_ok());
+}
+
+#[test]
+fn test_operation_waits_for_its_predecessor() {
+    let test = ClaimableBalanceTest::setup();
+    grant_roles(&test, &[Role::Proposer, Role::Executor, Role::Canceller], &test.admin);
+    let target = MockTargetClient::new(&test.env, &test.env.register(MockTarget, ()));
+    let schedule = |value: u32, predecessor: Option<u64>| {
+        test.contract.schedule(
+            &test.admin,
+            &target.address,
+            &symbol_short!("set_value"),
+            &vec![&test.env, value.into_val(&test.env)],
+            &(test.env.ledger().timestamp() + MIN_DELAY),
+            &predecessor,
+        )
+    };
+
+    let first = schedule(1, None);
+    let second = schedule(2, Some(first));
+
+    // The second step cannot run, nor is it due for keepers, before the first one
+    assert!(test.contract.try_execute(&test.admin, &second).is_err());
+    assert_eq!(test.contract.next_action_time(&Task::Operation(second)), None);
+
+    test.env.ledger().set_timestamp(12345 + 2 * MIN_DELAY);
+    test.contract.execute(&test.admin, &first);
+    test.contract.execute(&test.admin, &second);
+    assert_eq!(target.value(), 2);
+
+    // Operations cannot be chained behind a cancelled one
+    let cancelled = schedule(3, None);
+    test.contract.cancel(&test.admin, &cancelled);
+    assert!(test
+        .contract
+        .try_schedule(
+            &test.admin,
+            &target.address,
+            &symbol_short!("set_value"),
+            &vec![&test.env, 4_u32.into_val(&test.env)],
+            &(12345 + 3 * MIN_DELAY),
+            &Some(cancelled),
+        )
+        .is_err());
+}
+
+#[test]
+fn test_batch_executes_all_calls_or_none() {
+    let test = ClaimableBalanceTest::setup();
+    grant_roles(&test, &[Role::Proposer, Role::Executor], &test.admin);
+    let first = MockTargetClient::new(&test.env, &test.env.register(MockTarget, ()));
+    let second = MockTargetClient::new(&test.env, &test.env.register(MockTarget, ()));
+    let call = |target: &Address, function: &str, value: u32| Call {
+        target: target.clone(),
+        function: Symbol::new(&test.env, function),
+        args: vec![&test.env, value.into_val(&test.env)],
+    };
+
+    assert!(test
+        .contract
+        .try_schedule_batch(&test.admin, &vec![&test.env], &12345, &None)
+        .is_err());
+
+    // A failing call reverts the calls before it
+    let failing = test.contract.schedule_batch(
+        &test.admin,
+        &vec![&test.env, call(&first.address, "set_value", 1), call(&second.address, "missing", 2)],
+        &(12345 + 2 * MIN_DELAY),
+        &None,
+    );
+    let op_id = test.contract.schedule_batch(
+        &test.admin,
+        &vec![&test.env, call(&first.address, "set_value", 1), call(&second.address, "set_value", 2)],
+        &(12345 + 2 * MIN_DELAY),
+        &None,
+    );
+    test.env.ledger().set_timestamp(12345 + 2 * MIN_DELAY);
+    assert!(test.contract.try_execute(&test.admin, &failing).is_err());
+    assert_eq!(first.value(), 0);
+
+    let results = test.contract.execute(&test.admin, &op_id);
+    assert_eq!(
+        Vec::<u32>::try_from_val(&test.env, &results.to_val()).unwrap(),
+        vec![&test.env, 1, 2]
+    );
+    assert_eq!((first.value(), second.value()), (1, 2));
+}
+
+#[test]
+fn test_min_delay_is_only_updated_through_the_timelock() {
+    let test = ClaimableBalanceTest::setup();
+    grant_roles(&test, &[Role::Proposer, Role::Executor], &test.admin);
+
+    let update_delay = |delay: u64| {
+        test.contract.schedule(
+            &test.admin,
+            &test.contract.address,
+            &Symbol::new(&test.env, "update_delay"),
+            &vec![&test.env, delay.into_val(&test.env)],
+            &(12345 + 2 * MIN_DELAY),
+            &None,
+        )
+    };
+    let op_id = update_delay(2 * MIN_DELAY);
+    let below_floor = update_delay(MIN_DELAY - 1);
+    // The admin cannot change the delay directly, and there is no entrypoint to call outside of an operation
+    assert!(test
+        .contract
+        .try_update_config(&Config {
+            min_delay: 2 * MIN_DELAY,
+            ..test.contract.get_config()
+        })
+        .is_err());
+
+    test.env.ledger().with_mut(|li| li.timestamp = 12345 + 2 * MIN_DELAY);
+    test.contract.execute(&test.admin, &op_id);
+    assert_eq!(test.contract.get_config().min_delay, 2 * MIN_DELAY);
+
+    // The delay never drops below the floor
+    assert!(test.contract.try_execute(&test.admin, &below_floor).is_err());
+
+    // Operations calling anything else on the timelock are rejected
+    let op_id = test.contract.schedule(
+        &test.admin,
+        &test.contract.address,
+        &Symbol::new(&test.env, "pause"),
+        &vec![&test.env, true.into_val(&test.env)],
+        &(12345 + 4 * MIN_DELAY),
+        &None,
+    );
+    test.env.ledger().with_mut(|li| li.timestamp = 12345 + 4 * MIN_DELAY);
+    assert!(test.contract.try_execute(&test.admin, &op_id).is_err());
+}
+
+#[test]
+fn test_guardian_can_only_cancel() {
+    let test = ClaimableBalanceTest::setup();
+    let guardian = &test.claim_addresses[1];
+    test.contract.grant_role(&test.admin, &Role::Guardian, guardian);
+    grant_roles(&test, &[Role::Proposer], &test.admin);
+    let target = MockTargetClient::new(&test.env, &test.env.register(MockTarget, ()));
+    let args = vec![&test.env, 7_u32.into_val(&test.env)];
+
+    // Guardians can neither schedule nor execute
+    assert!(test
+        .contract
+        .try_schedule(guardian, &target.address, &symbol_short!("set_value"), &args, &(12345 + 2 * MIN_DELAY), &None)
+        .is_err());
+    let op_id = test
+        .contract
+        .schedule(&test.admin, &target.address, &symbol_short!("set_value"), &args, &(12345 + 2 * MIN_DELAY), &None);
+    assert!(test.contract.try_execute(guardian, &op_id).is_err());
+
+    test.contract.cancel(guardian, &op_id);
+    assert_eq!(test.env.auths()[0].0, *guardian);
+    assert!(test.contract.try_execute(&test.admin, &op_id).is_err());
+
+    // Guardians can veto an announced freeze, which the admin then cannot apply
+    let balance_id = test.contract.deposit(
+        &test.deposit_address,
+        &test.token.address,
+        &1000,
+        &vec![&test.env, test.claim_addresses[0].clone()],
+        &TimeBound {
+            kind: TimeBoundKind::After,
+            timestamp: 12500 + MIN_DELAY,
+        },
+    );
+    test.contract.announce_freeze_action(&balance_id, &FreezeAction::Freeze);
+    assert!(test
+        .contract
+        .try_veto_freeze_action(&test.claim_addresses[0], &balance_id)
+        .is_err());
+    test.contract.veto_freeze_action(guardian, &balance_id);
+    assert!(test
+        .contract
+        .try_apply_freeze_action(&balance_id, &FreezeAction::Freeze)
+        .is_err());
+    assert!(!test.contract.is_frozen(&balance_id));
+}
+
+#[test]
+fn test_operation_state_tracks_the_queue() {
+    let test = ClaimableBalanceTest::setup();
+    grant_roles(&test, &[Role::Proposer, Role::Executor, Role::Canceller], &test.admin);
+    let target = MockTargetClient::new(&test.env, &test.env.register(MockTarget, ()));
+    let args = vec![&test.env, 7_u32.into_val(&test.env)];
+    assert_eq!(test.contract.operation_state(&0), OperationState::Unset);
+    assert!(test.contract.get_operation(&0).is_none());
+
+    let first = test
+        .contract
+        .schedule(&test.admin, &target.address, &symbol_short!("set_value"), &args, &(12345 + 2 * MIN_DELAY), &None);
+    let second = test.contract.schedule(
+        &test.admin,
+        &target.address,
+        &symbol_short!("set_value"),
+        &args,
+        &(12345 + 2 * MIN_DELAY),
+        &Some(first),
+    );
+    let operation = test.contract.get_operation(&first).unwrap();
+    assert_eq!(operation.calls.get_unchecked(0).target, target.address);
+    assert_eq!(operation.calls.get_unchecked(0).function, symbol_short!("set_value"));
+    assert_eq!(operation.eta, 12345 + 2 * MIN_DELAY);
+    assert_eq!(test.contract.operation_state(&first), OperationState::Pending);
+
+    // An operation is ready once its eta passed and its predecessor is done
+    test.env.travel_to(12345 + 2 * MIN_DELAY);
+    assert_eq!(test.contract.operation_state(&first), OperationState::Ready);
+    assert_eq!(test.contract.operation_state(&second), OperationState::Pending);
+    test.contract.execute(&test.admin, &first);
+    assert_eq!(test.contract.operation_state(&first), OperationState::Done);
+    assert_eq!(test.contract.operation_state(&second), OperationState::Ready);
+    test.contract.cancel(&test.admin, &second);
+    assert_eq!(test.contract.operation_state(&second), OperationState::Cancelled);
+}
+
+#[test]
+fn test_operations_expire_after_the_grace_period() {
+    let test = ClaimableBalanceTest::setup();
+    test.contract.update_config(&Config {
+        grace_period: 100,
+        ..test.contract.get_config()
+    });
+    grant_roles(&test, &[Role::Proposer, Role::Executor], &test.admin);
+    let target = MockTargetClient::new(&test.env, &test.env.register(MockTarget, ()));
+    let schedule = |value: u32| {
+        test.contract.schedule(
+            &test.admin,
+            &target.address,
+            &symbol_short!("set_value"),
+            &vec![&test.env, value.into_val(&test.env)],
+            &(12345 + 2 * MIN_DELAY),
+            &None,
+        )
+    };
+    let executed = schedule(1);
+    let forgotten = schedule(2);
+
+    // Operations can run until the end of the grace period
+    test.env.travel_to(12445 + 2 * MIN_DELAY);
+    test.contract.execute(&test.admin, &executed);
+    assert_eq!(test.contract.operation_state(&forgotten), OperationState::Ready);
+
+    test.env.travel_to(12446 + 2 * MIN_DELAY);
+    assert_eq!(test.contract.operation_state(&forgotten), OperationState::Expired);
+    assert_eq!(test.contract.next_action_time(&Task::Operation(forgotten)), None);
+    assert!(test.contract.try_execute(&test.admin, &forgotten).is_err());
+    assert_eq!(target.value(), 1);
+}
+
+#[test]
+fn test_deposit_native_resolves_the_native_asset() {
+    let test = ClaimableBalanceTest::setup();
+    let claimant = &test.claim_addresses[0];
+    assert_eq!(test.contract.native_token(), Some(test.native_token.address.clone()));
+
+    let balance_id = test.contract.deposit_native(
+        &test.deposit_address,
+        &400,
+        &vec![&test.env, claimant.clone()],
+        &TimeBound {
+            kind: TimeBoundKind::After,
+            timestamp: 12345,
+        },
+    );
+    assert_eq!(test.contract.get_balance(&balance_id).token, test.native_token.address);
+    assert_eq!(test.native_token.balance(&test.deposit_address), 600);
+
+    test.contract.claim(claimant, &balance_id);
+    assert_eq!(test.native_token.balance(claimant), 400);
+
+    // Without a configured native asset the wrappers are unavailable
+    test.contract.update_config(&Config {
+        native_token: None,
+        ..test.contract.get_config()
+    });
+    assert!(test
+        .contract
+        .try_deposit_native_with_options(
+            &test.deposit_address,
+            &100,
+            &vec![&test.env, claimant.clone()],
+            &TimeBound {
+                kind: TimeBoundKind::After,
+                timestamp: 12345,
+            },
+            &DepositOptions::default(),
+        )
+        .is_err());
+}
+
+#[test]
+fn test_token_allowlist_restricts_deposits() {
+    let test = ClaimableBalanceTest::setup();
+    let claimants = vec![&test.env, test.claim_addresses[0].clone()];
+    let time_bound = TimeBound {
+        kind: TimeBoundKind::After,
+        timestamp: 12345,
+    };
+
+    // An empty allowlist accepts every token
+    assert!(test.contract.allowed_tokens().is_empty());
+    assert!(test.contract.is_token_allowed(&test.token.address));
+
+    test.contract.allow_token(&test.native_token.address);
+    assert_eq!(test.contract.allowed_tokens(), vec![&test.env, test.native_token.address.clone()]);
+    assert!(!test.contract.is_token_allowed(&test.token.address));
+    assert_eq!(
+        test.contract
+            .try_deposit(&test.deposit_address, &test.token.address, &100, &claimants, &time_bound),
+        Err(Ok(DepositError::TokenNotAllowed.into()))
+    );
+    test.contract.deposit_native(&test.deposit_address, &100, &claimants, &time_bound);
+
+    // Removing the last token reopens deposits of every token
+    test.contract.disallow_token(&test.native_token.address);
+    assert!(test.contract.try_disallow_token(&test.native_token.address).is_err());
+    test.contract.deposit(&test.deposit_address, &test.token.address, &100, &claimants, &time_bound);
+}
+
+#[test]
+fn test_min_deposit_rejects_dust() {
+    let test = ClaimableBalanceTest::setup();
+    let claimants = vec![&test.env, test.claim_addresses[0].clone()];
+    let time_bound = TimeBound {
+        kind: TimeBoundKind::After,
+        timestamp: 12345,
+    };
+    assert_eq!(test.contract.min_deposit(&test.token.address), 0);
+
+    test.contract.set_min_deposit(&test.token.address, &100);
+    assert_eq!(test.contract.min_deposit(&test.token.address), 100);
+    assert_eq!(
+        test.contract
+            .try_deposit(&test.deposit_address, &test.token.address, &99, &claimants, &time_bound),
+        Err(Ok(DepositError::BelowMinDeposit.into()))
+    );
+    test.contract.deposit(&test.deposit_address, &test.token.address, &100, &claimants, &time_bound);
+
+    // The minimum only applies to its token
+    test.contract.deposit_native(&test.deposit_address, &1, &claimants, &time_bound);
+    assert!(test.contract.try_set_min_deposit(&test.token.address, &-1).is_err());
+}
+
+#[test]
+fn test_active_balances_per_depositor_are_capped() {
+    let test = ClaimableBalanceTest::setup();
+    test.contract.update_config(&Config {
+        max_active_balances: 2,
+        ..test.contract.get_config()
+    });
+    let claimant = &test.claim_addresses[0];
+    let deposit = || {
+        test.contract.try_deposit(
+            &test.deposit_address,
+            &test.token.address,
+            &100,
+            &vec![&test.env, claimant.clone()],
+            &TimeBound {
+                kind: TimeBoundKind::After,
+                timestamp: 12345,
+            },
+        )
+    };
+
+    let balance_id = deposit().unwrap().unwrap();
+    deposit().unwrap().unwrap();
+    assert_eq!(test.contract.active_balance_count(&test.deposit_address), 2);
+    assert_eq!(deposit(), Err(Ok(DepositError::TooManyActiveBalances.into())));
+
+    // Settling a balance frees a slot
+    test.contract.claim(claimant, &balance_id);
+    assert_eq!(test.contract.active_balance_count(&test.deposit_address), 1);
+
+    // A batch counts all of its entries against the cap
+    let entry = DepositEntry {
+        amount: 100,
+        claimants: vec![&test.env, claimant.clone()],
+        time_bound: TimeBound {
+            kind: TimeBoundKind::After,
+            timestamp: 12345,
+        },
+    };
+    assert_eq!(
+        test.contract.try_deposit_batch(
+            &test.deposit_address,
+            &test.token.address,
+            &vec![&test.env, entry.clone(), entry],
+        ),
+        Err(Ok(DepositError::TooManyActiveBalances.into()))
+    );
+    assert!(deposit().is_ok());
+}
+
+#[test]
+fn test_sweep_expired_sends_remainder_to_treasury() {
+    let test = ClaimableBalanceTest::setup();
+    let treasury = Address::generate(&test.env);
+    let balance_id = test.contract.deposit(
+        &test.deposit_address,
+        &test.token.address,
+        &600,
+        &vec![&test.env, test.claim_addresses[0].clone()],
+        &TimeBound {
+            kind: TimeBoundKind::Before,
+            timestamp: 12400,
+        },
+    );
+
+    // Without a treasury there is nothing to sweep to
+    test.env.travel_to(12401);
+    assert!(test.contract.try_sweep_expired(&balance_id).is_err());
+
+    test.contract.update_config(&Config {
+        treasury: Some(treasury.clone()),
+        sweep_delay: 100,
+        ..test.contract.get_config()
+    });
+    assert_eq!(test.contract.sweepable_after(&balance_id), 12500);
+
+    // The depositor keeps the sweep delay to get a refund instead
+    test.env.travel_to(12500);
+    assert!(test.contract.try_sweep_expired(&balance_id).is_err());
+
+    test.env.travel_to(12501);
+    assert_eq!(test.contract.sweep_expired(&balance_id), 600);
+    assert_eq!(test.token.balance(&treasury), 600);
+    assert_eq!(test.token.balance(&test.deposit_address), 400);
+    assert_eq!(test.contract.status(&balance_id), BalanceStatus::Expired);
+    assert_eq!(test.contract.total_locked(&test.token.address), 0);
+}
+
+#[test]
+fn test_refunds_go_to_the_chosen_refund_address() {
+    let test = ClaimableBalanceTest::setup();
+    let dao_treasury = Address::generate(&test.env);
+    let balance_id = test.contract.deposit_with_options(
+        &test.deposit_address,
+        &test.token.address,
+        &300,
+        &vec![&test.env, test.claim_addresses[0].clone()],
+        &TimeBound {
+            kind: TimeBoundKind::Before,
+            timestamp: 12400,
+        },
+        &DepositOptions {
+            refund_to: Some(dao_treasury.clone()),
+            ..Default::default()
+        },
+    );
+    let claimable_balance = test.contract.get_balance(&balance_id);
+    assert_eq!(claimable_balance.depositor, test.deposit_address);
+    assert_eq!(claimable_balance.refund_to, dao_treasury);
+
+    // The depositor still triggers the refund, but the funds go to the refund address
+    test.env.travel_to(12401);
+    assert_eq!(test.contract.refund(&test.deposit_address, &balance_id), 300);
+    assert_eq!(test.token.balance(&dao_treasury), 300);
+    assert_eq!(test.token.balance(&test.deposit_address), 700);
+}
+
+#[test]
+fn test_lottery_pays_the_drawn_claimant() {
+    let test = ClaimableBalanceTest::setup();
+    let claimants = vec![
+        &test.env,
+        test.claim_addresses[0].clone(),
+        test.claim_addresses[1].clone(),
+        test.claim_addresses[2].clone(),
+    ];
+    let seed = BytesN::from_array(&test.env, &[7; 32]);
+    let commitment = test.env.crypto().sha256(&seed.clone().into()).to_bytes();
+    let balance_id = test.contract.deposit_with_options(
+        &test.deposit_address,
+        &test.token.address,
+        &900,
+        &claimants,
+        &TimeBound {
+            kind: TimeBoundKind::After,
+            timestamp: 12400,
+        },
+        &DepositOptions {
+            distribution: DistributionMode::Lottery(commitment),
+            ..Default::default()
+        },
+    );
+
+    // The draw only happens after unlock, and nobody can claim before it
+    assert!(test.contract.try_draw(&balance_id, &seed).is_err());
+    test.env.travel_to(12400);
+    assert!(test.contract.try_claim(&claimants.get_unchecked(0), &balance_id).is_err());
+
+    // Only the committed seed draws
+    assert!(test
+        .contract
+        .try_draw(&balance_id, &BytesN::from_array(&test.env, &[8; 32]))
+        .is_err());
+    let winner = test.contract.draw(&balance_id, &seed);
+    assert_eq!(test.env.auths()[0].0, test.deposit_address);
+    assert!(claimants.contains(&winner));
+    assert_eq!(test.contract.lottery_winner(&balance_id), Some(winner.clone()));
+    assert!(test.contract.try_draw(&balance_id, &seed).is_err());
+
+    // Only the winner can claim, and it receives everything
+    for claimant in claimants.iter().filter(|claimant| *claimant != winner) {
+        assert!(test.contract.try_claim(&claimant, &balance_id).is_err());
+    }
+    test.contract.claim(&winner, &balance_id);
+    assert_eq!(test.token.balance(&winner), 900);
+    assert_eq!(test.contract.status(&balance_id), BalanceStatus::Claimed);
+}
+
+#[test]
+fn test_holding_condition_gates_claimants() {
+    let test = ClaimableBalanceTest::setup();
+    let (membership, membership_admin) = create_token_contract(&test.env, &Address::generate(&test.env));
+    let member = &test.claim_addresses[0];
+    let outsider = &test.claim_addresses[1];
+    membership_admin.mint(member, &1);
+    let holds_membership = Predicate::Holds(HoldingCondition {
+        token: membership.address.clone(),
+        min_balance: 1,
+    });
+    let deposit = |predicate: Predicate| {
+        test.contract.try_deposit_with_options(
+            &test.deposit_address,
+            &test.token.address,
+            &500,
+            &vec![&test.env, outsider.clone(), member.clone()],
+            &TimeBound {
+                kind: TimeBoundKind::After,
+                timestamp: 12345,
+            },
+            &DepositOptions {
+                predicate,
+                ..Default::default()
+            },
+        )
+    };
+
+    // Negated holdings cannot be checked without a claimant, so they are rejected
+    assert!(deposit(Predicate::Not(vec![&test.env, holds_membership.clone()])).is_err());
+
+    // Only claimants holding the membership token can claim
+    let balance_id = deposit(holds_membership).unwrap().unwrap();
+    assert!(!test.contract.is_claimable(&balance_id, outsider));
+    assert!(test.contract.is_claimable(&balance_id, member));
+    assert!(test.contract.try_claim(outsider, &balance_id).is_err());
+    test.contract.claim(member, &balance_id);
+    assert_eq!(test.token.balance(member), 500);
+}
+
+#[test]
+fn test_claim_swapped_pays_in_another_asset() {
+    let test = ClaimableBalanceTest::setup();
+    let (usdc, usdc_admin) = create_token_contract(&test.env, &Address::generate(&test.env));
+    let router = test.env.register(MockRouter, ());
+    usdc_admin.mint(&router, &10_000);
+    let claimant = &test.claim_addresses[0];
+    let balance_id = test.contract.deposit(
+        &test.deposit_address,
+        &test.token.address,
+        &400,
+        &vec![&test.env, claimant.clone()],
+        &TimeBound {
+            kind: TimeBoundKind::After,
+            timestamp: 12345,
+        },
+    );
+
+    // Swaps need a configured router
+    assert!(test
+        .contract
+        .try_claim_swapped(claimant, &balance_id, &usdc.address, &1)
+        .is_err());
+    test.contract.update_config(&Config {
+        swap_router: Some(router.clone()),
+        ..test.contract.get_config()
+    });
+
+    // The claimant's minimum out is enforced
+    assert!(test
+        .contract
+        .try_claim_swapped(claimant, &balance_id, &usdc.address, &801)
+        .is_err());
+
+    // The locked token goes to the router and the claimant receives the other asset
+    assert_eq!(test.contract.claim_swapped(claimant, &balance_id, &usdc.address, &800), 800);
+    assert_eq!(usdc.balance(claimant), 800);
+    assert_eq!(test.token.balance(claimant), 0);
+    assert_eq!(test.token.balance(&router), 400);
+    assert_eq!(test.token.balance(&test.contract.address), 0);
+}
+
+#[test]
+fn test_deployed_funds_earn_yield_for_the_recipient() {
+    let test = ClaimableBalanceTest::setup();
+    let strategy = test.env.register(MockStrategy, ());
+    TokenAdminClient::new(&test.env, &test.token.address).mint(&strategy, &1000);
+    let claimant = &test.claim_addresses[0];
+    let balance_id = test.contract.deposit(
+        &test.deposit_address,
+        &test.token.address,
+        &500,
+        &vec![&test.env, claimant.clone()],
+        &TimeBound {
+            kind: TimeBoundKind::After,
+            timestamp: 13000,
+        },
+    );
+
+    // Only whitelisted strategies receive funds
+    assert!(test.contract.try_deploy_funds(&balance_id, &strategy, &400).is_err());
+    test.contract.update_config(&Config {
+        strategies: vec![&test.env, strategy.clone()],
+        yield_recipient: YieldRecipient::Claimant,
+        ..test.contract.get_config()
+    });
+    test.contract.deploy_funds(&balance_id, &strategy, &400);
+    assert_eq!(test.token.balance(&test.contract.address), 100);
+    assert_eq!(test.contract.deployment(&balance_id).unwrap().principal, 400);
+
+    // The balance cannot be claimed while its funds are deployed
+    test.env.ledger().set_timestamp(13000);
+    assert!(test.contract.try_claim(claimant, &balance_id).is_err());
+
+    // Once unlocked anyone can recall; the principal stays locked and the yield goes to the claimant
+    assert_eq!(test.contract.recall_funds(&balance_id), 40);
+    assert!(test.contract.deployment(&balance_id).is_none());
+    assert_eq!(test.token.balance(claimant), 40);
+    test.contract.claim(claimant, &balance_id);
+    assert_eq!(test.token.balance(claimant), 540);
+    assert_eq!(test.token.balance(&test.contract.address), 0);
+}
+
+/// Utility function building an authorization entry for a `claim` by a smart wallet, signed with the given PIN.
+fn wallet_claim_auth(
+    env: &Env,
+    contract: &Address,
+    wallet: &Address,
+    balance_id: u64,
+    pin: u32,
+) -> SorobanAuthorizationEntry {
+    SorobanAuthorizationEntry {
+        credentials: SorobanCredentials::Address(SorobanAddressCredentials {
+            address: wallet.into(),
+            nonce: 1,
+            signature_expiration_ledger: env.ledger().sequence() + 100,
+            signature: pin.into(),
+        }),
+        root_invocation: SorobanAuthorizedInvocation {
+            function: SorobanAuthorizedFunction::ContractFn(InvokeContractArgs {
+                contract_address: contract.into(),
+                function_name: "claim".try_into().unwrap(),
+                args: std::vec![wallet.into(), balance_id.into()].try_into().unwrap(),
+            }),
+            sub_invocations: VecM::default(),
+        },
+    }
+}
+
+#[test]
+fn test_smart_wallet_claims_through_check_auth() {
+    let test = ClaimableBalanceTest::setup();
+    let wallet = test.env.register(MockWallet, (1234_u32, false));
+    let balance_id = test.contract.deposit(
+        &test.deposit_address,
+        &test.token.address,
+        &300,
+        &vec![&test.env, wallet.clone()],
+        &TimeBound {
+            kind: TimeBoundKind::After,
+            timestamp: 12345,
+        },
+    );
+
+    // The wallet's own `__check_auth` decides, so a wrong PIN cannot claim
+    test.env
+        .set_auths(&[wallet_claim_auth(&test.env, &test.contract.address, &wallet, balance_id, 1111)]);
+    assert!(test.contract.try_claim(&wallet, &balance_id).is_err());
+
+    test.env
+        .set_auths(&[wallet_claim_auth(&test.env, &test.contract.address, &wallet, balance_id, 1234)]);
+    test.contract.claim(&wallet, &balance_id);
+    assert_eq!(test.token.balance(&wallet), 300);
+    test.env.mock_all_auths();
+}
+
+#[test]
+fn test_claim_by_contract_requires_consent() {
+    let test = ClaimableBalanceTest::setup();
+    let declining = test.env.register(MockWallet, (0_u32, false));
+    let accepting = test.env.register(MockWallet, (0_u32, true));
+    let deposit = |claimant: &Address| {
+        test.contract.deposit(
+            &test.deposit_address,
+            &test.token.address,
+            &200,
+            &vec![&test.env, claimant.clone()],
+            &TimeBound {
+                kind: TimeBoundKind::After,
+                timestamp: 12345,
+            },
+        )
+    };
+
+    // Anyone can trigger the claim, but only with the claimant contract's consent
+    let declined_id = deposit(&declining);
+    assert!(test.contract.try_claim_by_contract(&declining, &declined_id).is_err());
+
+    let accepted_id = deposit(&accepting);
+    test.env.set_auths(&[]);
+    assert_eq!(test.contract.claim_by_contract(&accepting, &accepted_id), 200);
+    assert_eq!(test.token.balance(&accepting), 200);
+    test.env.mock_all_auths();
+}
+
+#[test]
+fn test_consumed_nonce_cancels_signed_claim() {
+    let test = ClaimableBalanceTest::setup();
+    let (signing_key, claimant, public_key) = signing_account(&test.env, 9);
+    let balance_id = test.contract.deposit(
+        &test.deposit_address,
+        &test.token.address,
+        &100,
+        &vec![&test.env, claimant.clone()],
+        &TimeBound {
+            kind: TimeBoundKind::Before,
+            timestamp: 12400,
+        },
+    );
+    let claim = SignedClaim {
+        balance_id,
+        destination: claimant.clone(),
+        expiry: 12350,
+        nonce: 5,
+    };
+    let signature = sign_claim(&test.contract, &signing_key, &claim);
+
+    // The claimant burns the nonce before a relayer submits the signed claim
+    assert!(!test.contract.is_nonce_used(&claimant, &5));
+    test.contract.consume_nonce(&claimant, &5);
+    assert!(test.contract.is_nonce_used(&claimant, &5));
+    assert!(test
+        .contract
+        .try_claim_with_signature(&claimant, &public_key, &claim, &signature)
+        .is_err());
+
+    // Nonces are single-use through every entrypoint
+    assert!(test.contract.try_consume_nonce(&claimant, &5).is_err());
+}
+
+#[test]
+fn test_refund_and_schedule_emit_versioned_events() {
+    let test = ClaimableBalanceTest::setup();
+    let balance_id = test.contract.deposit(
+        &test.deposit_address,
+        &test.token.address,
+        &300,
+        &vec![&test.env, test.claim_addresses[0].clone()],
+        &TimeBound {
+            kind: TimeBoundKind::Before,
+            timestamp: 12346,
+        },
+    );
+    test.env.ledger().set_timestamp(12347);
+    test.contract.refund(&test.deposit_address, &balance_id);
+
+    // Refunds publish a `CancelEvent` under the schema version
+    assert_eq!(
+        last_event(&test.env),
+        vec![
+            &test.env,
+            (
+                test.contract.address.clone(),
+                (
+                    symbol_short!("refund"),
+                    EVENT_VERSION,
+                    balance_id,
+                    test.deposit_address.clone(),
+                    test.token.address.clone(),
+                )
+                    .into_val(&test.env),
+                CancelEvent {
+                    amount: 300,
+                    timestamp: 12347,
+                }
+                .into_val(&test.env),
+            )
+        ]
+    );
+
+    // Single and batched operations publish the same `ScheduleEvent`
+    grant_roles(&test, &[Role::Proposer], &test.admin);
+    let call = Call {
+        target: test.contract.address.clone(),
+        function: Symbol::new(&test.env, "update_delay"),
+        args: vec![&test.env, 100_u64.into_val(&test.env)],
+    };
+    let op_id = test
+        .contract
+        .schedule_batch(&test.admin, &vec![&test.env, call.clone()], &(12400 + 2 * MIN_DELAY), &None);
+    assert_eq!(
+        last_event(&test.env),
+        vec![
+            &test.env,
+            (
+                test.contract.address.clone(),
+                (symbol_short!("schedule"), EVENT_VERSION, op_id).into_val(&test.env),
+                ScheduleEvent {
+                    calls: vec![&test.env, call],
+                    eta: 12400 + 2 * MIN_DELAY,
+                    predecessor: None,
+                }
+                .into_val(&test.env),
+            )
+        ]
+    );
+}
+
+#[test]
+fn test_claimant_edits_keep_airdrops_and_equal_splits_consistent() {
+    let test = ClaimableBalanceTest::setup();
+    let locked = TimeBound {
+        kind: TimeBoundKind::After,
+        timestamp: 12445,
+    };
+
+    // Airdrop recipients are fixed by the Merkle root
+    let leaf = merkle_leaf(&test.env, &test.claim_addresses[0], 100);
+    let airdrop_id = test
+        .contract
+        .deposit_merkle(&test.deposit_address, &test.token.address, &100, &leaf, &locked);
+    assert!(test
+        .contract
+        .try_add_claimant(&test.deposit_address, &airdrop_id, &test.claim_addresses[1])
+        .is_err());
+
+    // An equal split cannot gain more claimants than it has tokens
+    let split_id = test.contract.deposit_with_options(
+        &test.deposit_address,
+        &test.token.address,
+        &2,
+        &vec![&test.env, test.claim_addresses[0].clone(), test.claim_addresses[1].clone()],
+        &locked,
+        &DepositOptions {
+            distribution: DistributionMode::EqualSplit,
+            ..Default::default()
+        },
+    );
+    assert!(test
+        .contract
+        .try_add_claimant(&test.deposit_address, &split_id, &test.claim_addresses[2])
+        .is_err());
+}
+
+#[test]
+fn test_revoke_respects_freezes_and_takes_the_fee() {
+    let test = ClaimableBalanceTest::setup();
+    let fee_collector = Address::generate(&test.env);
+    test.contract.update_config(&Config {
+        fee_bps: 250,
+        fee_collector: Some(fee_collector.clone()),
+        ..test.contract.get_config()
+    });
+    let revocable = || {
+        test.contract.deposit_with_options(
+            &test.deposit_address,
+            &test.token.address,
+            &400,
+            &vec![&test.env, test.claim_addresses[0].clone()],
+            &TimeBound {
+                kind: TimeBoundKind::After,
+                timestamp: 12345,
+            },
+            &DepositOptions {
+                vesting: Vesting::Linear(VestingSchedule {
+                    start: 12345,
+                    cliff: 12345,
+                    end: 12445,
+                }),
+                revocable: true,
+                ..Default::default()
+            },
+        )
+    };
+    let frozen_id = revocable();
+    let balance_id = revocable();
+
+    // A frozen balance cannot be revoked around the freeze
+    test.contract.announce_freeze_action(&frozen_id, &FreezeAction::Freeze);
+    test.contract.apply_freeze_action(&frozen_id, &FreezeAction::Freeze);
+    assert!(test.contract.try_revoke(&frozen_id).is_err());
+
+    // The vested part pays the protocol fee like any claim
+    test.env.ledger().with_mut(|li| li.timestamp = 12395);
+    assert_eq!(test.contract.revoke(&balance_id), 200);
+    assert_eq!(test.token.balance(&test.claim_addresses[0]), 195);
+    assert_eq!(test.token.balance(&fee_collector), 5);
+}
+
+#[test]
+fn test_upgrade_and_freeze_delays_are_only_lowered_through_the_timelock() {
+    let test = ClaimableBalanceTest::setup();
+    grant_roles(&test, &[Role::Proposer, Role::Executor], &test.admin);
+
+    // The admin can raise the delays, but not lower them again
+    test.contract.update_config(&Config {
+        upgrade_delay: 1000,
+        freeze_delay: 500,
+        ..test.contract.get_config()
+    });
+    assert!(test
+        .contract
+        .try_update_config(&Config {
+            upgrade_delay: 0,
+            ..test.contract.get_config()
+        })
+        .is_err());
+    assert!(test
+        .contract
+        .try_update_config(&Config {
+            freeze_delay: 0,
+            ..test.contract.get_config()
+        })
+        .is_err());
+
+    // Lowering them takes an operation on the timelock itself
+    let lower = |function: &str| Call {
+        target: test.contract.address.clone(),
+        function: Symbol::new(&test.env, function),
+        args: vec![&test.env, 10_u64.into_val(&test.env)],
+    };
+    let op_id = test.contract.schedule_batch(
+        &test.admin,
+        &vec![&test.env, lower("update_upgrade_delay"), lower("update_freeze_delay")],
+        &(12345 + 2 * MIN_DELAY),
+        &None,
+    );
+    test.env.ledger().set_timestamp(12345 + 2 * MIN_DELAY);
+    test.contract.execute(&test.admin, &op_id);
+    let config = test.contract.get_config();
+    assert_eq!((config.upgrade_delay, config.freeze_delay), (10, 10));
+}
+
+#[test]
+fn test_strategy_losses_are_absorbed_by_the_balance() {
+    let test = ClaimableBalanceTest::setup();
+    let losing = test.env.register(MockStrategy, ());
+    let earning = test.env.register(MockStrategy, ());
+    MockStrategyClient::new(&test.env, &losing).set_return_bps(&5_000);
+    TokenAdminClient::new(&test.env, &test.token.address).mint(&earning, &1000);
+    test.contract.update_config(&Config {
+        strategies: vec![&test.env, losing.clone(), earning.clone()],
+        yield_recipient: YieldRecipient::Claimant,
+        ..test.contract.get_config()
+    });
+    let claimant = &test.claim_addresses[0];
+    let unlock = TimeBound {
+        kind: TimeBoundKind::After,
+        timestamp: 13000,
+    };
+    let balance_id = test.contract.deposit(
+        &test.deposit_address,
+        &test.token.address,
+        &500,
+        &vec![&test.env, claimant.clone()],
+        &unlock,
+    );
+    let leaf = merkle_leaf(&test.env, claimant, 100);
+    let airdrop_id = test
+        .contract
+        .deposit_merkle(&test.deposit_address, &test.token.address, &100, &leaf, &unlock);
+    test.contract.deploy_funds(&balance_id, &losing, &400);
+    test.contract.deploy_funds(&airdrop_id, &earning, &100);
+
+    // Half of the principal is lost; the balance settles with what came back
+    test.env.ledger().set_timestamp(13000);
+    assert_eq!(test.contract.recall_funds(&balance_id), 0);
+    assert_eq!(test.contract.shortfall(&balance_id), 200);
+    test.contract.claim(claimant, &balance_id);
+    assert_eq!(test.token.balance(claimant), 300);
+
+    // Airdrops have no claimant list, so their yield goes to the depositor
+    assert_eq!(test.contract.recall_funds(&airdrop_id), 10);
+    assert_eq!(test.token.balance(&test.deposit_address), 410);
+}
+
+#[test]
+fn test_claim_all_skips_deployed_and_denied_balances() {
+    let test = ClaimableBalanceTest::setup();
+    let strategy = test.env.register(MockStrategy, ());
+    let hook = MockClaimHookClient::new(&test.env, &test.env.register(MockClaimHook, ()));
+    hook.set_max_amount(&400);
+    test.contract.update_config(&Config {
+        claim_hook: Some(hook.address.clone()),
+        strategies: vec![&test.env, strategy.clone()],
+        ..test.contract.get_config()
+    });
+    let claimant = &test.claim_addresses[0];
+    let deposit = |amount: i128| {
+        test.contract.deposit(
+            &test.deposit_address,
+            &test.token.address,
+            &amount,
+            &vec![&test.env, claimant.clone()],
+            &TimeBound {
+                kind: TimeBoundKind::After,
+                timestamp: 12400,
+            },
+        )
+    };
+    let deployed_id = deposit(300);
+    let denied_id = deposit(500);
+    let ready_id = deposit(200);
+    test.contract.deploy_funds(&deployed_id, &strategy, &300);
+    test.env.ledger().set_timestamp(12400);
+
+    // Neither the deployed nor the denied balance makes the sweep revert
+    assert_eq!(
+        test.contract.claim_all(claimant, &10),
+        vec![&test.env, (ready_id, test.token.address.clone(), 200)]
+    );
+    assert_eq!(
+        test.contract.preview_claim(&deployed_id, claimant).blocker,
+        ClaimBlocker::Deployed
+    );
+    assert_eq!(
+        test.contract.preview_claim(&denied_id, claimant).blocker,
+        ClaimBlocker::Denied(99)
+    );
+}
+
+#[test]
+fn test_top_up_cannot_exceed_max_amount() {
+    let test = ClaimableBalanceTest::setup();
+    TokenAdminClient::new(&test.env, &test.token.address).mint(&test.deposit_address, &MAX_AMOUNT);
+    let balance_id = test.contract.deposit(
+        &test.deposit_address,
+        &test.token.address,
+        &500,
+        &vec![&test.env, test.claim_addresses[0].clone()],
+        &TimeBound {
+            kind: TimeBoundKind::After,
+            timestamp: 12400,
+        },
+    );
+
+    assert_eq!(
+        test.contract.try_top_up(&balance_id, &MAX_AMOUNT),
+        Err(Ok(DepositError::AmountTooLarge.into()))
+    );
+    assert_eq!(test.contract.top_up(&balance_id, &(MAX_AMOUNT - 500)), MAX_AMOUNT);
+}
+
+#[test]
+fn test_claim_hook_judges_the_amount_paid() {
+    let test = ClaimableBalanceTest::setup();
+    let hook = MockClaimHookClient::new(&test.env, &test.env.register(MockClaimHook, ()));
+    hook.set_max_amount(&150);
+    test.contract.update_config(&Config {
+        claim_hook: Some(hook.address.clone()),
+        ..test.contract.get_config()
+    });
+    let claimant = &test.claim_addresses[0];
+    let balance_id = test.contract.deposit_with_options(
+        &test.deposit_address,
+        &test.token.address,
+        &300,
+        &vec![&test.env, claimant.clone()],
+        &TimeBound {
+            kind: TimeBoundKind::After,
+            timestamp: 12345,
+        },
+        &DepositOptions {
+            vesting: Vesting::Linear(VestingSchedule {
+                start: 12345,
+                cliff: 12345,
+                end: 12445,
+            }),
+            ..Default::default()
+        },
+    );
+
+    // Half of the balance vested, which the hook allows even though it would deny the full amount
+    test.env.ledger().set_timestamp(12395);
+    assert_eq!(test.contract.preview_claim(&balance_id, claimant).blocker, ClaimBlocker::None);
+    assert_eq!(test.contract.claim_vested(claimant, &balance_id), 150);
+
+    // The rest is judged on its own amount too
+    test.env.ledger().set_timestamp(12445);
+    assert_eq!(test.contract.claim_vested(claimant, &balance_id), 150);
+}
+
+#[test]
+fn test_top_up_and_stream_events_are_versioned() {
+    let test = ClaimableBalanceTest::setup();
+    let recipient = &test.claim_addresses[0];
+    let balance_id = test.contract.deposit(
+        &test.deposit_address,
+        &test.token.address,
+        &500,
+        &vec![&test.env, recipient.clone()],
+        &TimeBound {
+            kind: TimeBoundKind::After,
+            timestamp: 12400,
+        },
+    );
+
+    // Top-up event carries the schema version, balance id, depositor and token as topics, a `TopUpEvent` as data
+    test.contract.top_up(&balance_id, &300);
+    assert_eq!(
+        last_event(&test.env),
+        vec![
+            &test.env,
+            (
+                test.contract.address.clone(),
+                (
+                    symbol_short!("top_up"),
+                    EVENT_VERSION,
+                    balance_id,
+                    test.deposit_address.clone(),
+                    test.token.address.clone(),
+                )
+                    .into_val(&test.env),
+                TopUpEvent {
+                    amount: 300,
+                    total: 800,
+                    timestamp: 12345,
+                }
+                .into_val(&test.env),
+            )
+        ]
+    );
+
+    let stream_id = test.contract.create_stream(
+        &test.deposit_address,
+        recipient,
+        &test.token.address,
+        &200,
+        &12345,
+        &12445,
+    );
+    test.env.ledger().set_timestamp(12395);
+    test.contract.cancel_stream(&stream_id);
+
+    // Stream cancellation is keyed by the sender, with the split as a `StreamCancelEvent`
+    assert_eq!(
+        last_event(&test.env),
+        vec![
+            &test.env,
+            (
+                test.contract.address.clone(),
+                (
+                    symbol_short!("stream_cx"),
+                    EVENT_VERSION,
+                    stream_id,
+                    test.deposit_address.clone(),
+                    test.token.address.clone(),
+                )
+                    .into_val(&test.env),
+                StreamCancelEvent {
+                    to_recipient: 100,
+                    to_sender: 100,
+                    timestamp: 12395,
+                }
+                .into_val(&test.env),
+            )
+        ]
+    );
+}
+
+#[test]
+fn test_settle_lets_keepers_push_claimant_shares() {
+    let test = ClaimableBalanceTest::setup();
+    let keeper = Address::generate(&test.env);
+
+    let balance_id = test.contract.deposit_with_options(
+        &test.deposit_address,
+        &test.token.address,
+        &800,
+        &vec![&test.env, test.claim_addresses[0].clone(), test.claim_addresses[1].clone()],
+        &TimeBound {
+            kind: TimeBoundKind::After,
+            timestamp: 12400,
+        },
+        &DepositOptions {
+            distribution: DistributionMode::EqualSplit,
+            ..Default::default()
+        },
+    );
+
+    // Nothing to push before the unlock
+    assert!(test.contract.try_settle(&keeper, &balance_id).is_err());
+
+    test.env.ledger().set_timestamp(12400);
+    assert_eq!(test.contract.settle(&keeper, &balance_id), Settlement::Distributed(800));
+    assert_eq!(test.token.balance(&test.claim_addresses[0]), 400);
+    assert_eq!(test.token.balance(&test.claim_addresses[1]), 400);
+
+    // A first-come-first-served balance has no shares a keeper could push
+    let fcfs_id = test.contract.deposit(
+        &test.deposit_address,
+        &test.token.address,
+        &100,
+        &vec![&test.env, test.claim_addresses[0].clone()],
+        &TimeBound {
+            kind: TimeBoundKind::After,
+            timestamp: 12400,
+        },
+    );
+    assert!(test.contract.try_settle(&keeper, &fcfs_id).is_err());
+}
+
+#[test]
+fn test_merkle_leaves_must_pay_a_positive_amount() {
+    let test = ClaimableBalanceTest::setup();
+
+    // Tree whose root commits to a zero and a negative leaf alongside a valid one
+    let zero_leaf = merkle_leaf(&test.env, &test.claim_addresses[0], 0);
+    let negative_leaf = merkle_leaf(&test.env, &test.claim_addresses[1], -100);
+    let valid_leaf = merkle_leaf(&test.env, &test.claim_addresses[2], 300);
+    let node01 = merkle_parent(&test.env, &zero_leaf, &negative_leaf);
+    let root = merkle_parent(&test.env, &node01, &valid_leaf);
+
+    let balance_id = test.contract.deposit_merkle(
+        &test.deposit_address,
+        &test.token.address,
+        &300,
+        &root,
+        &TimeBound {
+            kind: TimeBoundKind::Before,
+            timestamp: 12346,
+        },
+    );
+
+    // Valid proofs do not make non-positive leaves claimable
+    assert!(test
+        .contract
+        .try_claim_with_proof(
+            &test.claim_addresses[0],
+            &balance_id,
+            &0,
+            &vec![&test.env, negative_leaf.clone(), valid_leaf.clone()]
+        )
+        .is_err());
+    assert!(test
+        .contract
+        .try_claim_with_proof(
+            &test.claim_addresses[1],
+            &balance_id,
+            &-100,
+            &vec![&test.env, zero_leaf, valid_leaf]
+        )
+        .is_err());
+
+    test.contract
+        .claim_with_proof(&test.claim_addresses[2], &balance_id, &300, &vec![&test.env, node01]);
+    assert_eq!(test.token.balance(&test.claim_addresses[2]), 300);
+    assert_eq!(test.contract.claim_count(&balance_id), 1);
+}
+
+#[test]
+fn test_operations_cannot_move_escrowed_tokens() {
+    let test = ClaimableBalanceTest::setup();
+    grant_roles(&test, &[Role::Proposer, Role::Executor], &test.admin);
+    let drain = Call {
+        target: test.token.address.clone(),
+        function: symbol_short!("transfer"),
+        args: vec![
+            &test.env,
+            test.contract.address.into_val(&test.env),
+            test.admin.into_val(&test.env),
+            1000_i128.into_val(&test.env),
+        ],
+    };
+
+    // Scheduled before anything was deposited, the drain is still stopped once the token is held in custody
+    let op_id = test
+        .contract
+        .schedule_batch(&test.admin, &vec![&test.env, drain.clone()], &(12345 + 2 * MIN_DELAY), &None);
+    test.contract.deposit(
+        &test.deposit_address,
+        &test.token.address,
+        &1000,
+        &vec![&test.env, test.claim_addresses[0].clone()],
+        &TimeBound {
+            kind: TimeBoundKind::After,
+            timestamp: 20000,
+        },
+    );
+    test.env.ledger().set_timestamp(12345 + 2 * MIN_DELAY);
+    assert!(test.contract.try_execute(&test.admin, &op_id).is_err());
+
+    // Nor can it be scheduled anew
+    assert!(test
+        .contract
+        .try_schedule_batch(&test.admin, &vec![&test.env, drain], &(12345 + 3 * MIN_DELAY), &None)
+        .is_err());
+    assert_eq!(test.token.balance(&test.contract.address), 1000);
+    assert_eq!(test.token.balance(&test.admin), 0);
+}
+
+#[test]
+fn test_balance_withdrawal_cap_rejected_when_payouts_cannot_be_clipped() {
+    let test = ClaimableBalanceTest::setup();
+    let deposit = |options: DepositOptions| {
+        test.contract.try_deposit_with_options(
+            &test.deposit_address,
+            &test.token.address,
+            &1000,
+            &vec![&test.env, test.claim_addresses[0].clone()],
+            &TimeBound {
+                kind: TimeBoundKind::After,
+                timestamp: 12400,
+            },
+            &options,
+        )
+    };
+    let cap = WithdrawalCap::PerBalance(RollingWindow {
+        max_amount: 100,
+        window: 100,
+    });
+
+    // A payout of 1000 against a cap of 100 would leave the balance unclaimable and, past its unlock, unrefundable
+    assert!(deposit(DepositOptions {
+        withdrawal_cap: cap.clone(),
+        ..Default::default()
+    })
+    .is_err());
+    assert!(deposit(DepositOptions {
+        vesting: Vesting::Tranches(vec![&test.env, Tranche { timestamp: 12400, amount: 1000 }]),
+        withdrawal_cap: cap.clone(),
+        ..Default::default()
+    })
+    .is_err());
+    assert_eq!(test.token.balance(&test.contract.address), 0);
+}
+
+#[test]
+fn test_early_claims_cannot_skip_heartbeats_or_conditions() {
+    let test = ClaimableBalanceTest::setup();
+    let deposit = |options: DepositOptions| {
+        test.contract.try_deposit_with_options(
+            &test.deposit_address,
+            &test.token.address,
+            &1000,
+            &vec![&test.env, test.claim_addresses[0].clone()],
+            &TimeBound {
+                kind: TimeBoundKind::After,
+                timestamp: 20000,
+            },
+            &DepositOptions {
+                early_claim: EarlyClaim::Penalty(EarlyClaimTerms {
+                    penalty_bps: 100,
+                    earliest: 15000,
+                    recipient: None,
+                }),
+                ..options
+            },
+        )
+    };
+
+    // The backup claimant of a dead man's switch cannot take the balance while the depositor is alive
+    assert!(deposit(DepositOptions {
+        dead_man_switch: DeadManSwitch::Heartbeat(10000),
+        ..Default::default()
+    })
+    .is_err());
+
+    // Oracle and holding conditions would never be evaluated by an early claim
+    let price = Predicate::Price(PriceCondition {
+        oracle: Address::generate(&test.env),
+        asset: OracleAsset::Stellar(test.token.address.clone()),
+        comparator: Comparator::Above,
+        price: 100,
+    });
+    assert!(deposit(DepositOptions {
+        predicate: Predicate::And(vec![&test.env, Predicate::AfterAbs(12345), price]),
+        ..Default::default()
+    })
+    .is_err());
+    assert!(deposit(DepositOptions {
+        predicate: Predicate::Holds(HoldingCondition {
+            token: test.native_token.address.clone(),
+            min_balance: 1,
+        }),
+        ..Default::default()
+    })
+    .is_err());
+
+    // Conditions on time alone are waived with the rest of the wait
+    let balance_id = deposit(DepositOptions {
+        predicate: Predicate::AfterAbs(19000),
+        ..Default::default()
+    })
+    .unwrap()
+    .unwrap();
+    test.env.travel_to(15000);
+    assert_eq!(test.contract.claim_early(&test.claim_addresses[0], &balance_id), 990);
+}
+
+#[test]
+fn test_lottery_outcome_is_fixed_by_the_committed_seed() {
+    let test = ClaimableBalanceTest::setup();
+    let claimants = vec![
+        &test.env,
+        test.claim_addresses[0].clone(),
+        test.claim_addresses[1].clone(),
+        test.claim_addresses[2].clone(),
+    ];
+    let seed = BytesN::from_array(&test.env, &[42; 32]);
+    let deposit = || {
+        test.contract.deposit_with_options(
+            &test.deposit_address,
+            &test.token.address,
+            &300,
+            &claimants,
+            &TimeBound {
+                kind: TimeBoundKind::After,
+                timestamp: 12400,
+            },
+            &DepositOptions {
+                distribution: DistributionMode::Lottery(test.env.crypto().sha256(&seed.clone().into()).to_bytes()),
+                ..Default::default()
+            },
+        )
+    };
+    let first = deposit();
+    let second = deposit();
+    test.env.travel_to(12400);
+
+    // A claimant cannot trigger the draw, let alone retry it from a contract until it wins
+    test.env.set_auths(&[]);
+    assert!(test.contract.try_draw(&first, &seed).is_err());
+    test.env.mock_all_auths();
+
+    // Drawn at different ledgers, the same seed picks the same claimant
+    let winner = test.contract.draw(&first, &seed);
+    test.env.travel_by(1000);
+    assert_eq!(test.contract.draw(&second, &seed), winner);
+}
+
+#[test]
+fn test_escrow_resolution_takes_the_fee_on_the_seller_share() {
+    let test = ClaimableBalanceTest::setup();
+    let seller = &test.claim_addresses[0];
+    let arbiter = Address::generate(&test.env);
+    let fee_collector = Address::generate(&test.env);
+    test.contract.update_config(&Config {
+        fee_bps: 250,
+        fee_collector: Some(fee_collector.clone()),
+        ..test.contract.get_config()
+    });
+
+    let balance_id = test.contract.deposit_with_options(
+        &test.deposit_address,
+        &test.token.address,
+        &800,
+        &vec![&test.env, seller.clone()],
+        &TimeBound {
+            kind: TimeBoundKind::Before,
+            timestamp: 12400,
+        },
+        &DepositOptions {
+            escrow: true,
+            arbiter: Some(arbiter),
+            ..Default::default()
+        },
+    );
+    test.contract.raise_dispute(seller, &balance_id);
+
+    // The seller's share pays the fee like a claim, the buyer's refund does not
+    test.contract.resolve(&balance_id, &400, &400);
+    assert_eq!(test.token.balance(seller), 390);
+    assert_eq!(test.token.balance(&fee_collector), 10);
+    assert_eq!(test.token.balance(&test.deposit_address), 600);
+}
@@ -0,0 +1,57 @@
+use soroban_sdk::testutils::Ledger;
+use soroban_sdk::{token, Address, Env};
+
+use crate::{ClaimableBalanceContract, ClaimableBalanceContractClient, Config};
+
+/// Approximate duration of a ledger in seconds, used to advance the ledger sequence along with the clock.
+const LEDGER_DURATION: u64 = 5;
+
+/// Utility function to create a token contract and return both its client and admin interfaces.
+pub fn create_token_contract<'a>(
+    e: &Env,
+    admin: &Address,
+) -> (token::Client<'a>, token::StellarAssetClient<'a>) {
+    let sac = e.register_stellar_asset_contract_v2(admin.clone());
+    (
+        token::Client::new(e, &sac.address()),
+        token::StellarAssetClient::new(e, &sac.address()),
+    )
+}
+
+/// Utility function to deploy the claimable balance contract with its admin and configuration and return its client.
+pub fn create_claimable_balance_contract<'a>(
+    e: &Env,
+    admin: &Address,
+    config: Config,
+) -> ClaimableBalanceContractClient<'a> {
+    ClaimableBalanceContractClient::new(
+        e,
+        &e.register(ClaimableBalanceContract, (admin, config)),
+    )
+}
+
+/// Trait moving the test ledger through time, advancing the ledger sequence with the clock so TTLs age too.
+pub trait TimeTravel {
+    /// Moves the ledger forward by `seconds`.
+    fn travel_by(&self, seconds: u64);
+
+    /// Moves the ledger forward to the UNIX timestamp; panics if it lies in the past.
+    fn travel_to(&self, timestamp: u64);
+}
+
+impl TimeTravel for Env {
+    fn travel_by(&self, seconds: u64) {
+        self.ledger().with_mut(|li| {
+            li.timestamp += seconds;
+            li.sequence_number += (seconds / LEDGER_DURATION) as u32;
+        });
+    }
+
+    fn travel_to(&self, timestamp: u64) {
+        let now = self.ledger().timestamp();
+        if timestamp < now {
+            panic!("cannot travel back in time");
+        }
+        self.travel_by(timestamp - now);
+    }
+}
@@ -0,0 +1,231 @@
+use soroban_sdk::{Address, Vec};
+
+use crate::math::{self, MathError};
+use crate::{
+    Airdrop, ClaimWindow, ClaimableBalance, Config, DepositError, HoldingCondition, Predicate, PriceCondition,
+    TimeBound, RecurringSchedule, TimeBoundKind, Tranche, VestingSchedule, MAX_AMOUNT,
+};
+
+/// Returns whether the time bound is satisfied at `now`.
+pub fn time_bound_holds(time_bound: &TimeBound, now: u64) -> bool {
+    match time_bound.kind {
+        TimeBoundKind::Before => now <= time_bound.timestamp,
+        TimeBoundKind::After => now >= time_bound.timestamp,
+        TimeBoundKind::Between(end) => now >= time_bound.timestamp && now <= end,
+    }
+}
+
+/// Returns whether the time bound can no longer be satisfied after `now`.
+pub fn time_bound_expired(time_bound: &TimeBound, now: u64) -> bool {
+    match time_bound.kind {
+        TimeBoundKind::Before => now > time_bound.timestamp,
+        TimeBoundKind::After => false,
+        TimeBoundKind::Between(end) => now > end,
+    }
+}
+
+/// Returns the last timestamp at which the time bound holds, or `None` when it never stops holding.
+pub fn time_bound_end(time_bound: &TimeBound) -> Option<u64> {
+    match time_bound.kind {
+        TimeBoundKind::Before => Some(time_bound.timestamp),
+        TimeBoundKind::After => None,
+        TimeBoundKind::Between(end) => Some(end),
+    }
+}
+
+/// Recursively evaluates a predicate tree at `now`, asking `price_holds` about oracle price conditions.
+/// Holding conditions are assumed to be met, as no claimant is known; see `evaluate_predicate_for`.
+pub fn evaluate_predicate(
+    predicate: &Predicate,
+    now: u64,
+    price_holds: &impl Fn(&PriceCondition) -> bool,
+) -> bool {
+    evaluate_predicate_for(predicate, now, price_holds, &|_| true)
+}
+
+/// Recursively evaluates a predicate tree at `now` for a given claimant, asking `price_holds` about oracle price
+/// conditions and `holding_holds` about what the claimant holds.
+pub fn evaluate_predicate_for(
+    predicate: &Predicate,
+    now: u64,
+    price_holds: &impl Fn(&PriceCondition) -> bool,
+    holding_holds: &impl Fn(&HoldingCondition) -> bool,
+) -> bool {
+    let evaluate = |inner: Predicate| evaluate_predicate_for(&inner, now, price_holds, holding_holds);
+    match predicate {
+        Predicate::Unconditional => true,
+        Predicate::BeforeAbs(timestamp) => now < *timestamp,
+        Predicate::AfterAbs(timestamp) => now >= *timestamp,
+        Predicate::And(predicates) => predicates.iter().all(evaluate),
+        Predicate::Or(predicates) => predicates.iter().any(evaluate),
+        Predicate::Not(predicates) => !predicates.iter().any(evaluate),
+        Predicate::Price(condition) => price_holds(condition),
+        Predicate::Holds(condition) => holding_holds(condition),
+    }
+}
+
+/// Returns whether a predicate tree contains a holding condition, which can only be evaluated for a claimant.
+pub fn has_holding_condition(predicate: &Predicate) -> bool {
+    match predicate {
+        Predicate::Holds(_) => true,
+        Predicate::And(predicates) | Predicate::Or(predicates) | Predicate::Not(predicates) => {
+            predicates.iter().any(|inner| has_holding_condition(&inner))
+        }
+        Predicate::Unconditional | Predicate::BeforeAbs(_) | Predicate::AfterAbs(_) | Predicate::Price(_) => false,
+    }
+}
+
+/// Returns whether a predicate tree only depends on time, without oracle prices or holdings.
+pub fn is_time_only(predicate: &Predicate) -> bool {
+    match predicate {
+        Predicate::Unconditional | Predicate::BeforeAbs(_) | Predicate::AfterAbs(_) => true,
+        Predicate::And(predicates) | Predicate::Or(predicates) | Predicate::Not(predicates) => {
+            predicates.iter().all(|inner| is_time_only(&inner))
+        }
+        Predicate::Price(_) | Predicate::Holds(_) => false,
+    }
+}
+
+/// Returns whether the claimant may claim at `now` under the claim windows; any time if there are none.
+pub fn claim_window_open(windows: &Vec<ClaimWindow>, claimant: &Address, now: u64) -> bool {
+    windows.is_empty()
+        || windows
+            .iter()
+            .any(|window| window.claimant == *claimant && window.start <= now && now <= window.end)
+}
+
+/// Returns the part of `amount` vested at `now` under a linear schedule with a cliff.
+pub fn linear_vested(vesting: &VestingSchedule, amount: i128, now: u64) -> Result<i128, MathError> {
+    if now < vesting.cliff {
+        Ok(0)
+    } else if now >= vesting.end {
+        Ok(amount)
+    } else {
+        math::mul_div(amount, (now - vesting.start) as i128, (vesting.end - vesting.start) as i128)
+    }
+}
+
+/// Sums the tranches matured at `now` that follow the `paid` leading ones.
+/// Returns the amount and the number of tranches paid once it is paid out.
+pub fn matured_tranches(tranches: &Vec<Tranche>, paid: u32, now: u64) -> Result<(i128, u32), MathError> {
+    let mut amount = 0;
+    let mut count = paid;
+    while count < tranches.len() {
+        let tranche = tranches.get_unchecked(count);
+        if tranche.timestamp > now {
+            break;
+        }
+        amount = math::add(amount, tranche.amount)?;
+        count += 1;
+    }
+    Ok((amount, count))
+}
+
+/// Sums the recurring installments of `amount` matured at `now` that follow the `paid` leading ones.
+/// Returns the amount and the number of installments paid once it is paid out.
+pub fn matured_installments(
+    schedule: &RecurringSchedule,
+    amount: i128,
+    paid: u32,
+    now: u64,
+) -> Result<(i128, u32), MathError> {
+    if now < schedule.first_unlock {
+        return Ok((0, paid));
+    }
+    let elapsed_intervals = (now - schedule.first_unlock) / schedule.interval;
+    let matured = elapsed_intervals.saturating_add(1).min(schedule.installments as u64) as u32;
+    if matured <= paid {
+        return Ok((0, paid));
+    }
+
+    // The last installment also pays the rounding remainder
+    let installment = math::div(amount, schedule.installments as i128)?;
+    let mut payout = math::mul(installment, (matured - paid) as i128)?;
+    if matured == schedule.installments {
+        let remainder = math::sub(amount, math::mul(installment, schedule.installments as i128)?)?;
+        payout = math::add(payout, remainder)?;
+    }
+    Ok((payout, matured))
+}
+
+/// Returns the next equal-split payout; the claimant completing the split also gets the remainder.
+pub fn equal_share(claimable_balance: &ClaimableBalance) -> Result<i128, MathError> {
+    let claimant_count = claimable_balance.claimants.len() as i128;
+    let share = math::div(claimable_balance.amount, claimant_count)?;
+
+    // Every previous claim paid exactly one share
+    let claims_made = math::div(claimable_balance.claimed, share)?;
+    if claims_made == claimant_count - 1 {
+        math::sub(claimable_balance.amount, claimable_balance.claimed)
+    } else {
+        Ok(share)
+    }
+}
+
+/// Returns a claimant's weighted-split payout: its pro-rata share of the amount, rounded down. The rounding
+/// remainder goes to the first claimant with the largest weight; claimants without a weight get nothing.
+pub fn weighted_share(amount: i128, weights: &Vec<(Address, u32)>, claimant: &Address) -> Result<i128, MathError> {
+    let mut total_weight = 0;
+    let mut heaviest: Option<(Address, u32)> = None;
+    for (address, weight) in weights.iter() {
+        total_weight = math::add(total_weight, weight as i128)?;
+        if heaviest.as_ref().is_none_or(|(_, heaviest_weight)| weight > *heaviest_weight) {
+            heaviest = Some((address, weight));
+        }
+    }
+
+    let mut share = 0;
+    let mut distributed = 0;
+    for (address, weight) in weights.iter() {
+        let weighted = math::mul_div(amount, weight as i128, total_weight)?;
+        distributed = math::add(distributed, weighted)?;
+        if address == *claimant {
+            share = weighted;
+        }
+    }
+
+    match heaviest {
+        Some((address, _)) if address == *claimant => math::add(share, math::sub(amount, distributed)?),
+        _ => Ok(share),
+    }
+}
+
+/// Returns the claimants in their original order with duplicates dropped.
+pub fn dedup_claimants(claimants: &Vec<Address>) -> Vec<Address> {
+    let mut unique = Vec::new(claimants.env());
+    for claimant in claimants.iter() {
+        if !unique.contains(&claimant) {
+            unique.push_back(claimant);
+        }
+    }
+    unique
+}
+
+/// Checks a deposit with deduplicated claimants against the configuration at `now`.
+pub fn check_deposit(claimable_balance: &ClaimableBalance, config: &Config, now: u64) -> Result<(), DepositError> {
+    if claimable_balance.amount <= 0 {
+        return Err(DepositError::InvalidAmount);
+    }
+    if claimable_balance.amount > MAX_AMOUNT || claimable_balance.bounty > MAX_AMOUNT {
+        return Err(DepositError::AmountTooLarge);
+    }
+    if claimable_balance.claimants.is_empty() && matches!(claimable_balance.airdrop, Airdrop::None) {
+        return Err(DepositError::NoClaimants);
+    }
+    if claimable_balance.claimants.len() > config.max_claimants {
+        return Err(DepositError::TooManyClaimants);
+    }
+
+    // Bounds that unlock in the future must do so within the configured lock duration range
+    let time_bound = &claimable_balance.time_bound;
+    if let TimeBoundKind::After | TimeBoundKind::Between(_) = time_bound.kind {
+        if time_bound.timestamp < now {
+            return Err(DepositError::UnlockInPast);
+        }
+        let lock_duration = time_bound.timestamp - now;
+        if lock_duration < config.min_lock_duration || lock_duration > config.max_lock_duration {
+            return Err(DepositError::LockDurationOutOfBounds);
+        }
+    }
+    Ok(())
+}
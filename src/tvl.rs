@@ -0,0 +1,66 @@
+use soroban_sdk::{contractimpl, Address, Env, Vec};
+
+use crate::{
+    math, ClaimableBalanceContract, ClaimableBalanceContractArgs, ClaimableBalanceContractClient, DataKey,
+};
+
+#[contractimpl]
+impl ClaimableBalanceContract {
+    /// Returns the amount of a token currently locked in open balances, excluding keeper bounties.
+    pub fn total_locked(env: Env, token: Address) -> i128 {
+        read_total_locked(&env, &token)
+    }
+
+    /// Returns every token that has ever been deposited, in order of first deposit.
+    pub fn tokens(env: Env) -> Vec<Address> {
+        env.storage()
+            .instance()
+            .get(&DataKey::Tokens)
+            .unwrap_or(Vec::new(&env))
+    }
+}
+
+/// Helper function to read the amount of a token locked in open balances.
+pub(crate) fn read_total_locked(env: &Env, token: &Address) -> i128 {
+    env.storage()
+        .instance()
+        .get(&DataKey::TvlByToken(token.clone()))
+        .unwrap_or(0)
+}
+
+/// Helper function moving the locked total of a token by `delta`, registering tokens on their first deposit.
+pub(crate) fn adjust_total_locked(env: &Env, token: &Address, delta: i128) {
+    let key = DataKey::TvlByToken(token.clone());
+    let total_locked: Option<i128> = env.storage().instance().get(&key);
+    if total_locked.is_none() {
+        let mut tokens: Vec<Address> = env
+            .storage()
+            .instance()
+            .get(&DataKey::Tokens)
+            .unwrap_or(Vec::new(env));
+        tokens.push_back(token.clone());
+        env.storage().instance().set(&DataKey::Tokens, &tokens);
+    }
+
+    let total_locked = math::checked(env, math::add(total_locked.unwrap_or(0), delta));
+    env.storage().instance().set(&key, &total_locked);
+}
+
+/// Helper function moving the amount of a token the contract owes outside of open balances by `delta`.
+pub(crate) fn adjust_reserved(env: &Env, token: &Address, delta: i128) {
+    if delta == 0 {
+        return;
+    }
+
+    let key = DataKey::ReservedByToken(token.clone());
+    let reserved = math::checked(env, math::add(read_reserved(env, token), delta));
+    env.storage().instance().set(&key, &reserved);
+}
+
+/// Helper function to read the amount of a token the contract owes outside of open balances.
+pub(crate) fn read_reserved(env: &Env, token: &Address) -> i128 {
+    env.storage()
+        .instance()
+        .get(&DataKey::ReservedByToken(token.clone()))
+        .unwrap_or(0)
+}
@@ -0,0 +1,94 @@
+use soroban_sdk::{contractimpl, contracttype, symbol_short, BytesN, Env};
+
+use crate::{
+    read_admin, read_config, ClaimableBalanceContract, ClaimableBalanceContractArgs,
+    ClaimableBalanceContractClient, DataKey, CONTRACT_VERSION,
+};
+
+/// Struct representing an announced contract upgrade that can be installed once its delay has passed.
+#[derive(Clone)]
+#[contracttype]
+pub struct PendingUpgrade {
+    pub wasm_hash: BytesN<32>,  // Hash of the announced contract code
+    pub eta: u64,               // UNIX timestamp from which the code can be installed
+}
+
+#[contractimpl]
+impl ClaimableBalanceContract {
+    /// Announces the code the contract will be upgraded to, installable after the configured upgrade delay.
+    /// Only callable by the admin; replaces any previous announcement.
+    pub fn announce_upgrade(env: Env, new_wasm_hash: BytesN<32>) -> u64 {
+        // Require that the admin authorizes the announcement
+        read_admin(&env).require_auth();
+
+        // Give claimants time to exit before the new code can be installed
+        let eta = env
+            .ledger()
+            .timestamp()
+            .saturating_add(read_config(&env).upgrade_delay);
+        let pending_upgrade = PendingUpgrade {
+            wasm_hash: new_wasm_hash.clone(),
+            eta,
+        };
+        env.storage()
+            .instance()
+            .set(&DataKey::PendingUpgrade, &pending_upgrade);
+
+        env.events()
+            .publish((symbol_short!("upg_annc"), new_wasm_hash), eta);
+
+        eta
+    }
+
+    /// Installs the announced code once its delay has passed. Only callable by the admin.
+    pub fn upgrade(env: Env, new_wasm_hash: BytesN<32>) {
+        // Require that the admin authorizes the upgrade
+        read_admin(&env).require_auth();
+
+        // Only the announced code can be installed, and only after the delay
+        let pending_upgrade = read_pending_upgrade(&env);
+        if pending_upgrade.wasm_hash != new_wasm_hash {
+            panic!("wasm hash does not match the announced upgrade");
+        }
+        if env.ledger().timestamp() < pending_upgrade.eta {
+            panic!("upgrade delay has not elapsed");
+        }
+
+        env.storage().instance().remove(&DataKey::PendingUpgrade);
+
+        env.events()
+            .publish((symbol_short!("upgrade"), new_wasm_hash.clone()), ());
+
+        env.deployer().update_current_contract_wasm(new_wasm_hash);
+    }
+
+    /// Withdraws the announced upgrade. Only callable by the admin.
+    pub fn cancel_upgrade(env: Env) {
+        // Require that the admin authorizes the cancellation
+        read_admin(&env).require_auth();
+
+        let pending_upgrade = read_pending_upgrade(&env);
+        env.storage().instance().remove(&DataKey::PendingUpgrade);
+
+        env.events()
+            .publish((symbol_short!("upg_cncl"), pending_upgrade.wasm_hash), ());
+    }
+
+    /// Returns the announced upgrade, if any.
+    pub fn pending_upgrade(env: Env) -> Option<PendingUpgrade> {
+        env.storage().instance().get(&DataKey::PendingUpgrade)
+    }
+
+    /// Returns the version of the installed contract code.
+    pub fn version(_env: Env) -> u32 {
+        CONTRACT_VERSION
+    }
+}
+
+/// Helper function to read the announced upgrade; panics if there is none.
+fn read_pending_upgrade(env: &Env) -> PendingUpgrade {
+    env.storage()
+        .instance()
+        .get(&DataKey::PendingUpgrade)
+        .expect("no upgrade has been announced")
+}
@@ -0,0 +1,45 @@
+use soroban_sdk::{contracterror, Address, Env, Error};
+
+use crate::{allowlist, current_time, index, min_deposit, timelock_core, ClaimableBalance, Config};
+
+/// Enum listing why a deposit is rejected, surfaced as the contract error code of the failed call.
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum DepositError {
+    InvalidAmount = 1,            // The amount is zero or negative
+    NoClaimants = 2,              // The claimant list is empty and no airdrop root replaces it
+    TooManyClaimants = 3,         // More distinct claimants than the configured maximum
+    UnlockInPast = 4,             // An `After` or `Between` bound starts before the current time
+    LockDurationOutOfBounds = 5,  // The unlock lies outside the configured min/max lock duration
+    AmountTooLarge = 6,           // The amount or bounty exceeds `MAX_AMOUNT`
+    TokenNotAllowed = 7,          // The token, or a basket token, is missing from a non-empty allowlist
+    BelowMinDeposit = 8,          // The amount is below the minimum deposit set for the token
+    TooManyActiveBalances = 9,    // The depositor already has the configured maximum of open balances
+}
+
+/// Helper function rejecting malformed deposits and dropping duplicate claimants.
+pub(crate) fn validate_deposit(env: &Env, claimable_balance: &mut ClaimableBalance, config: &Config) {
+    // Keep the first occurrence of every claimant
+    claimable_balance.claimants = timelock_core::dedup_claimants(&claimable_balance.claimants);
+
+    if let Err(error) = timelock_core::check_deposit(claimable_balance, config, current_time(env)) {
+        env.panic_with_error(Error::from(error));
+    }
+
+    allowlist::check_token_allowed(env, &claimable_balance.token);
+    for (token, _) in claimable_balance.basket.iter() {
+        allowlist::check_token_allowed(env, &token);
+    }
+    min_deposit::check_min_deposit(env, claimable_balance);
+    check_active_balance_cap(env, &claimable_balance.depositor, 1, config);
+}
+
+/// Helper function rejecting deposits that would take the depositor past the configured number of open balances.
+pub(crate) fn check_active_balance_cap(env: &Env, depositor: &Address, new_balances: u32, config: &Config) {
+    if config.max_active_balances > 0
+        && index::active_balance_count(env, depositor).saturating_add(new_balances) > config.max_active_balances
+    {
+        env.panic_with_error(Error::from(DepositError::TooManyActiveBalances));
+    }
+}